@@ -0,0 +1,528 @@
+//! B-tree (Introduction to Algorithms chapter 18)
+//!
+//! Every node other than the root holds between `T - 1` and `2T - 1` keys,
+//! where `T >= 2` is the minimum degree of the tree. Insertion splits full
+//! nodes on the way down and deletion makes sure a node has at least `T`
+//! keys before descending into it, so both finish in a single pass from the
+//! root.
+use core::cmp::Ordering;
+use core::ops::Bound;
+use core::ops::RangeBounds;
+
+#[derive(Debug)]
+struct BTreeNode<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    // empty for a leaf, otherwise keys.len() + 1 children
+    children: Vec<BTreeNode<K, V>>,
+}
+
+impl<K, V> BTreeNode<K, V> {
+    fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// B-tree ordered map with minimum degree `T`
+///
+/// ```rust
+/// use algorithms_rs::datastruct::BTree;
+///
+/// let mut tree = BTree::<i32, &str, 3>::new();
+///
+/// tree.insert(2, "b");
+/// tree.insert(1, "a");
+///
+/// assert_eq!(tree.search(&1), Some(&"a"));
+/// assert_eq!(tree.remove(&2), Some("b"));
+/// assert_eq!(tree.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct BTree<K, V, const T: usize> {
+    root: BTreeNode<K, V>,
+    len: usize,
+}
+
+impl<K: Ord, V, const T: usize> Default for BTree<K, V, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, const T: usize> FromIterator<(K, V)> for BTree<K, V, T> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+impl<K: Ord, V, const T: usize> BTree<K, V, T> {
+    /// Creating an empty B-tree
+    ///
+    /// ```no
+    /// B-TREE-CREATE(T)
+    ///     x = ALLOCATE-NODE()
+    ///     x.leaf = TRUE
+    ///     x.n = 0
+    ///     T.root = x
+    /// ```
+    pub fn new() -> Self {
+        const { assert!(T >= 2, "minimum degree of a B-tree must be at least 2") };
+        Self {
+            root: BTreeNode::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of keys in the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of levels in the tree, an empty tree has height 0
+    pub fn height(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            height += 1;
+            node = child;
+        }
+        height
+    }
+
+    /// Find the value stored under `key`
+    ///
+    /// ```no
+    /// B-TREE-SEARCH(x, k)
+    ///     i = 1
+    ///     while i <= x.n and k > x.key[i]
+    ///         i = i + 1
+    ///     if i <= x.n and k == x.key[i]
+    ///         return (x, i)
+    ///     elseif x.leaf
+    ///         return NIL
+    ///     else DISK-READ(x.c[i])
+    ///         return B-TREE-SEARCH(x.c[i], k)
+    /// ```
+    pub fn search(&self, key: &K) -> Option<&V> {
+        let mut node = &self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(i) => return Some(&node.values[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &node.children[i],
+            }
+        }
+    }
+
+    /// Mutable reference to the value stored under `key`
+    pub fn search_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(i) => return Some(&mut node.values[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &mut node.children[i],
+            }
+        }
+    }
+
+    /// Determine if the tree contains `key`
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.search(key).is_some()
+    }
+
+    /// Smallest key and its value
+    pub fn minimum(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        node.keys.first().zip(node.values.first())
+    }
+
+    /// Largest key and its value
+    pub fn maximum(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        node.keys.last().zip(node.values.last())
+    }
+
+    /// Insert a key-value pair, returning the old value if `key` was present
+    ///
+    /// ```no
+    /// B-TREE-INSERT(T, k)
+    ///     r = T.root
+    ///     if r.n == 2t - 1
+    ///         s = ALLOCATE-NODE()
+    ///         T.root = s
+    ///         s.leaf = FALSE
+    ///         s.n = 0
+    ///         s.c[1] = r
+    ///         B-TREE-SPLIT-CHILD(s, 1)
+    ///         B-TREE-INSERT-NONFULL(s, k)
+    ///     else B-TREE-INSERT-NONFULL(r, k)
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.root.keys.len() == 2 * T - 1 {
+            let old_root = core::mem::replace(&mut self.root, BTreeNode::new());
+            self.root.children.push(old_root);
+            Self::split_child(&mut self.root, 0);
+        }
+        let old = Self::insert_nonfull(&mut self.root, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Split the full child `x.c[i]` around its median key, which moves up into `x`
+    ///
+    /// ```no
+    /// B-TREE-SPLIT-CHILD(x, i)
+    ///     z = ALLOCATE-NODE()
+    ///     y = x.c[i]
+    ///     z.leaf = y.leaf
+    ///     z.n = t - 1
+    ///     for j = 1 to t - 1
+    ///         z.key[j] = y.key[j + t]
+    ///     if not y.leaf
+    ///         for j = 1 to t
+    ///             z.c[j] = y.c[j + t]
+    ///     y.n = t - 1
+    ///     insert z as x.c[i + 1] and y.key[t] as x.key[i]
+    /// ```
+    fn split_child(x: &mut BTreeNode<K, V>, i: usize) {
+        let y = &mut x.children[i];
+        let z = BTreeNode {
+            keys: y.keys.split_off(T),
+            values: y.values.split_off(T),
+            children: if y.is_leaf() {
+                Vec::new()
+            } else {
+                y.children.split_off(T)
+            },
+        };
+        let median_key = y.keys.pop().expect("full node has a median key");
+        let median_value = y.values.pop().expect("full node has a median value");
+        x.keys.insert(i, median_key);
+        x.values.insert(i, median_value);
+        x.children.insert(i + 1, z);
+    }
+
+    fn insert_nonfull(x: &mut BTreeNode<K, V>, key: K, value: V) -> Option<V> {
+        let mut i = match x.keys.binary_search(&key) {
+            Ok(i) => return Some(core::mem::replace(&mut x.values[i], value)),
+            Err(i) => i,
+        };
+        if x.is_leaf() {
+            x.keys.insert(i, key);
+            x.values.insert(i, value);
+            return None;
+        }
+        if x.children[i].keys.len() == 2 * T - 1 {
+            Self::split_child(x, i);
+            match key.cmp(&x.keys[i]) {
+                Ordering::Equal => return Some(core::mem::replace(&mut x.values[i], value)),
+                Ordering::Greater => i += 1,
+                Ordering::Less => {}
+            }
+        }
+        Self::insert_nonfull(&mut x.children[i], key, value)
+    }
+
+    /// Remove `key` from the tree, returning its value
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::BTree;
+    ///
+    /// let mut tree: BTree<i32, i32, 2> = (0..100).map(|k| (k, k * k)).collect();
+    ///
+    /// assert_eq!(tree.remove(&7), Some(49));
+    /// assert_eq!(tree.remove(&7), None);
+    /// assert_eq!(tree.len(), 99);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = Self::delete(&mut self.root, key);
+        // the root lost its last key through a merge, the tree shrinks by one level
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            self.root = self.root.children.remove(0);
+        }
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed.map(|(_, value)| value)
+    }
+
+    /// B-TREE-DELETE, `x` has at least `t` keys unless it is the root
+    fn delete(x: &mut BTreeNode<K, V>, key: &K) -> Option<(K, V)> {
+        match x.keys.binary_search(key) {
+            // case 1: the key is in the leaf x
+            Ok(i) if x.is_leaf() => Some((x.keys.remove(i), x.values.remove(i))),
+            // case 2: the key is in the internal node x
+            Ok(i) => {
+                if x.children[i].keys.len() >= T {
+                    // 2a: replace the key by its predecessor
+                    let (k, v) = Self::delete_max(&mut x.children[i]);
+                    let k = core::mem::replace(&mut x.keys[i], k);
+                    let v = core::mem::replace(&mut x.values[i], v);
+                    Some((k, v))
+                } else if x.children[i + 1].keys.len() >= T {
+                    // 2b: replace the key by its successor
+                    let (k, v) = Self::delete_min(&mut x.children[i + 1]);
+                    let k = core::mem::replace(&mut x.keys[i], k);
+                    let v = core::mem::replace(&mut x.values[i], v);
+                    Some((k, v))
+                } else {
+                    // 2c: merge the key and both children, then delete from the merged node
+                    Self::merge_children(x, i);
+                    Self::delete(&mut x.children[i], key)
+                }
+            }
+            Err(_) if x.is_leaf() => None,
+            // case 3: the key can only be in the subtree x.c[i]
+            Err(i) => {
+                let i = Self::ensure_child_has_t_keys(x, i);
+                Self::delete(&mut x.children[i], key)
+            }
+        }
+    }
+
+    fn delete_max(x: &mut BTreeNode<K, V>) -> (K, V) {
+        if x.is_leaf() {
+            let k = x.keys.pop().expect("node on a delete path is not empty");
+            let v = x.values.pop().expect("node on a delete path is not empty");
+            return (k, v);
+        }
+        let i = Self::ensure_child_has_t_keys(x, x.children.len() - 1);
+        Self::delete_max(&mut x.children[i])
+    }
+
+    fn delete_min(x: &mut BTreeNode<K, V>) -> (K, V) {
+        if x.is_leaf() {
+            return (x.keys.remove(0), x.values.remove(0));
+        }
+        let i = Self::ensure_child_has_t_keys(x, 0);
+        Self::delete_min(&mut x.children[i])
+    }
+
+    /// Make sure `x.c[i]` has at least `t` keys, returns the index of the child
+    /// which now covers the keys of the old `x.c[i]`
+    fn ensure_child_has_t_keys(x: &mut BTreeNode<K, V>, i: usize) -> usize {
+        if x.children[i].keys.len() >= T {
+            return i;
+        }
+        if i > 0 && x.children[i - 1].keys.len() >= T {
+            // 3a: move a key from x down into x.c[i] and one from the left sibling up
+            let (left, right) = x.children.split_at_mut(i);
+            let (sibling, child) = (&mut left[i - 1], &mut right[0]);
+            let k = sibling.keys.pop().expect("sibling has t keys");
+            let v = sibling.values.pop().expect("sibling has t keys");
+            child
+                .keys
+                .insert(0, core::mem::replace(&mut x.keys[i - 1], k));
+            child
+                .values
+                .insert(0, core::mem::replace(&mut x.values[i - 1], v));
+            if let Some(c) = sibling.children.pop() {
+                child.children.insert(0, c);
+            }
+            i
+        } else if i + 1 < x.children.len() && x.children[i + 1].keys.len() >= T {
+            // 3a: move a key from x down into x.c[i] and one from the right sibling up
+            let (left, right) = x.children.split_at_mut(i + 1);
+            let (child, sibling) = (&mut left[i], &mut right[0]);
+            let k = sibling.keys.remove(0);
+            let v = sibling.values.remove(0);
+            child.keys.push(core::mem::replace(&mut x.keys[i], k));
+            child.values.push(core::mem::replace(&mut x.values[i], v));
+            if !sibling.is_leaf() {
+                child.children.push(sibling.children.remove(0));
+            }
+            i
+        } else if i + 1 < x.children.len() {
+            // 3b: merge x.c[i] with its right sibling
+            Self::merge_children(x, i);
+            i
+        } else {
+            // 3b: merge x.c[i] with its left sibling
+            Self::merge_children(x, i - 1);
+            i - 1
+        }
+    }
+
+    /// Merge `x.key[i]` and `x.c[i + 1]` into `x.c[i]`
+    fn merge_children(x: &mut BTreeNode<K, V>, i: usize) {
+        let right = x.children.remove(i + 1);
+        let key = x.keys.remove(i);
+        let value = x.values.remove(i);
+        let left = &mut x.children[i];
+        left.keys.push(key);
+        left.values.push(value);
+        let BTreeNode {
+            keys,
+            values,
+            children,
+        } = right;
+        left.keys.extend(keys);
+        left.values.extend(values);
+        left.children.extend(children);
+    }
+
+    /// Key-value pairs whose keys fall in `range`, in ascending key order
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::BTree;
+    ///
+    /// let tree: BTree<i32, (), 2> = (0..20).map(|k| (k, ())).collect();
+    ///
+    /// let keys: Vec<i32> = tree.range(5..9).into_iter().map(|(k, _)| *k).collect();
+    ///
+    /// assert_eq!(keys, vec![5, 6, 7, 8]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Vec<(&K, &V)> {
+        let mut result = Vec::new();
+        Self::collect_range(&self.root, &range, &mut result);
+        result
+    }
+
+    fn collect_range<'a, R: RangeBounds<K>>(
+        x: &'a BTreeNode<K, V>,
+        range: &R,
+        result: &mut Vec<(&'a K, &'a V)>,
+    ) -> bool {
+        let start = match range.start_bound() {
+            Bound::Included(s) => x.keys.partition_point(|k| k < s),
+            Bound::Excluded(s) => x.keys.partition_point(|k| k <= s),
+            Bound::Unbounded => 0,
+        };
+        for i in start..=x.keys.len() {
+            if let Some(child) = x.children.get(i) {
+                if !Self::collect_range(child, range, result) {
+                    return false;
+                }
+            }
+            let Some(key) = x.keys.get(i) else {
+                break;
+            };
+            // every key from here on is past the end of the range
+            if !range.contains(key) {
+                return false;
+            }
+            result.push((key, &x.values[i]));
+        }
+        true
+    }
+
+    /// In-order walk of the tree
+    pub fn inorder_tree_walk(&self) -> Vec<(&K, &V)> {
+        self.range(..)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    // returns the depth of the leaves and checks the B-tree properties
+    fn check<const T: usize>(x: &BTreeNode<i32, i32>, is_root: bool) -> usize {
+        assert!(x.keys.len() < 2 * T);
+        if !is_root {
+            assert!(x.keys.len() >= T - 1);
+        }
+        assert_eq!(x.keys.len(), x.values.len());
+        assert!(x.keys.windows(2).all(|w| w[0] < w[1]));
+        if x.is_leaf() {
+            return 1;
+        }
+        assert_eq!(x.children.len(), x.keys.len() + 1);
+        for (i, key) in x.keys.iter().enumerate() {
+            assert!(x.children[i].keys.last() < Some(key));
+            assert!(x.children[i + 1].keys.first() > Some(key));
+        }
+        let depth = check::<T>(&x.children[0], false);
+        for child in &x.children[1..] {
+            assert_eq!(check::<T>(child, false), depth);
+        }
+        depth + 1
+    }
+
+    fn random_against_btree_map<const T: usize>(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tree = BTree::<i32, i32, T>::new();
+        let mut expected = BTreeMap::new();
+        for _ in 0..3000 {
+            let key = rng.gen_range(0..500);
+            if rng.gen_bool(0.55) {
+                assert_eq!(tree.insert(key, -key), expected.insert(key, -key));
+            } else {
+                assert_eq!(tree.remove(&key), expected.remove(&key));
+            }
+            check::<T>(&tree.root, true);
+            assert_eq!(tree.len(), expected.len());
+        }
+        let all: Vec<_> = expected.iter().collect();
+        assert_eq!(tree.inorder_tree_walk(), all);
+        let part: Vec<_> = expected.range(100..=300).collect();
+        assert_eq!(tree.range(100..=300), part);
+    }
+
+    #[test]
+    fn test_random_minimum_degree_2() {
+        random_against_btree_map::<2>(2);
+    }
+
+    #[test]
+    fn test_random_minimum_degree_3() {
+        random_against_btree_map::<3>(3);
+    }
+
+    #[test]
+    fn test_random_minimum_degree_16() {
+        random_against_btree_map::<16>(16);
+    }
+
+    #[test]
+    fn test_insert_replaces_value() {
+        let mut tree = BTree::<&str, i32, 2>::new();
+        assert_eq!(tree.insert("a", 1), None);
+        assert_eq!(tree.insert("a", 2), Some(1));
+        assert_eq!(tree.len(), 1);
+        *tree.search_mut(&"a").unwrap() += 1;
+        assert_eq!(tree.search(&"a"), Some(&3));
+    }
+
+    #[test]
+    fn test_height_and_bounds() {
+        let tree: BTree<i32, i32, 2> = (0..15).map(|k| (k, k)).collect();
+        assert!(tree.height() > 1);
+        assert_eq!(tree.minimum(), Some((&0, &0)));
+        assert_eq!(tree.maximum(), Some((&14, &14)));
+        assert_eq!(tree.range((Bound::Excluded(12), Bound::Unbounded)).len(), 2);
+        assert!(BTree::<i32, i32, 2>::new().range(..).is_empty());
+    }
+}
@@ -0,0 +1,766 @@
+//! Disk-backed B+ tree
+//!
+//! A B+ tree keeps every key-value pair in its leaves and only separator keys
+//! in internal nodes, the leaves are chained left to right so a range scan
+//! walks along the leaf level once it has found its first key. Each node
+//! lives in one fixed-size page of a page file. Pages are cached by a buffer
+//! pool and every `insert`/`remove` is committed to a write-ahead log of full
+//! page images before it returns, so after a crash the tree reopens in the
+//! state of the last finished operation. An operation that fails before its
+//! commit is logged leaves the tree as it was.
+//!
+//! Deletion removes the entry from its leaf without merging underfull nodes,
+//! the space is reused by later inserts into the same key range.
+mod pager;
+mod wal;
+
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use pager::Pager;
+
+/// Size in bytes of a page in the page file
+pub const PAGE_SIZE: usize = 4096;
+
+/// Number of pages the buffer pool caches by default
+pub const DEFAULT_BUFFER_POOL_PAGES: usize = 256;
+
+// write back all pages and empty the log once it grows past this size
+const CHECKPOINT_WAL_SIZE: u64 = 4 << 20;
+
+const MAGIC: &[u8; 8] = b"ALGOBPT1";
+const HEADER_PAGE: u64 = 0;
+const LEAF: u8 = 1;
+const INTERNAL: u8 = 2;
+// kind u8, key count u16, next leaf u64
+const NODE_HEADER_SIZE: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum BPlusTreeError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("page file is corrupted: {0}")]
+    Corrupted(String),
+    #[error("page file was created with key size {0} and value size {1}")]
+    SizeMismatch(usize, usize),
+    #[error("entries of {0} bytes do not fit three to a page")]
+    EntryTooLarge(usize),
+}
+
+/// Types with a fixed-width binary encoding that can be stored in a page
+pub trait FixedSize: Sized {
+    /// Number of bytes of the encoding
+    const SIZE: usize;
+
+    /// Write the value into `buf`, which is exactly `SIZE` bytes long
+    fn encode(&self, buf: &mut [u8]);
+
+    /// Read a value from `buf`, which is exactly `SIZE` bytes long
+    fn decode(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_fixed_size {
+    ($($t:ty),*) => {
+        $(
+            impl FixedSize for $t {
+                const SIZE: usize = core::mem::size_of::<$t>();
+
+                fn encode(&self, buf: &mut [u8]) {
+                    buf.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(buf: &[u8]) -> Self {
+                    let mut bytes = [0u8; core::mem::size_of::<$t>()];
+                    bytes.copy_from_slice(buf);
+                    <$t>::from_le_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_size!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<const N: usize> FixedSize for [u8; N] {
+    const SIZE: usize = N;
+
+    fn encode(&self, buf: &mut [u8]) {
+        buf.copy_from_slice(self);
+    }
+
+    fn decode(buf: &[u8]) -> Self {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(buf);
+        bytes
+    }
+}
+
+#[derive(Debug)]
+enum BPlusNode<K, V> {
+    Leaf {
+        keys: Vec<K>,
+        values: Vec<V>,
+        // page of the right sibling leaf, HEADER_PAGE for the last leaf
+        next: u64,
+    },
+    Internal {
+        keys: Vec<K>,
+        // children[i] holds the keys k with keys[i - 1] <= k < keys[i]
+        children: Vec<u64>,
+    },
+}
+
+/// B+ tree stored in a page file
+///
+/// The write-ahead log lives next to the page file, with `.wal` appended to
+/// its name.
+///
+/// ```rust
+/// use algorithms_rs::datastruct::BPlusTree;
+///
+/// let path = std::env::temp_dir().join(format!("doc-bplus-{}.db", std::process::id()));
+/// # let _ = std::fs::remove_file(&path);
+///
+/// let mut tree = BPlusTree::<u64, u64>::open(&path).unwrap();
+/// for key in 0..1000u64 {
+///     tree.insert(key, key * 2).unwrap();
+/// }
+/// drop(tree);
+///
+/// let mut tree = BPlusTree::<u64, u64>::open(&path).unwrap();
+/// assert_eq!(tree.search(&21).unwrap(), Some(42));
+/// assert_eq!(tree.range(10..13).unwrap(), vec![(10, 20), (11, 22), (12, 24)]);
+/// # drop(tree);
+/// # std::fs::remove_file(&path).unwrap();
+/// # let mut wal = path.into_os_string();
+/// # wal.push(".wal");
+/// # std::fs::remove_file(wal).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct BPlusTree<K, V> {
+    pager: Pager,
+    root: u64,
+    len: u64,
+    max_leaf_keys: usize,
+    max_internal_keys: usize,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K, V> BPlusTree<K, V>
+where
+    K: FixedSize + Ord + Clone,
+    V: FixedSize,
+{
+    /// Open or create the tree stored at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, BPlusTreeError> {
+        Self::open_with_buffer_pool(path, DEFAULT_BUFFER_POOL_PAGES)
+    }
+
+    /// Open or create the tree stored at `path`, caching at most `pages` pages
+    ///
+    /// The buffer pool only grows past `pages` while a single operation has
+    /// more uncommitted pages than that.
+    pub fn open_with_buffer_pool<P: AsRef<Path>>(
+        path: P,
+        pages: usize,
+    ) -> Result<Self, BPlusTreeError> {
+        let max_leaf_keys = (PAGE_SIZE - NODE_HEADER_SIZE) / (K::SIZE + V::SIZE);
+        let max_internal_keys = (PAGE_SIZE - NODE_HEADER_SIZE - 8) / (K::SIZE + 8);
+        if max_leaf_keys < 3 || max_internal_keys < 3 {
+            return Err(BPlusTreeError::EntryTooLarge(K::SIZE + V::SIZE));
+        }
+
+        let path = path.as_ref();
+        let pager = Pager::open(path, &wal_path(path), pages)?;
+        let mut tree = Self {
+            pager,
+            root: HEADER_PAGE,
+            len: 0,
+            max_leaf_keys,
+            max_internal_keys,
+            _marker: PhantomData,
+        };
+
+        if tree.pager.page_count() == 0 {
+            tree.transaction(|tree| {
+                let header = tree.pager.allocate();
+                debug_assert_eq!(header, HEADER_PAGE);
+                tree.root = tree.pager.allocate();
+                tree.write_node(
+                    tree.root,
+                    &BPlusNode::Leaf {
+                        keys: Vec::new(),
+                        values: Vec::new(),
+                        next: HEADER_PAGE,
+                    },
+                )
+            })?;
+        } else {
+            tree.read_header()?;
+        }
+        Ok(tree)
+    }
+
+    /// Number of entries in the tree
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Determine if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Find the value stored under `key`
+    pub fn search(&mut self, key: &K) -> Result<Option<V>, BPlusTreeError> {
+        let leaf = self.find_leaf(Bound::Included(key))?;
+        match self.read_node(leaf)? {
+            BPlusNode::Leaf {
+                keys, mut values, ..
+            } => Ok(keys.binary_search(key).ok().map(|i| values.swap_remove(i))),
+            BPlusNode::Internal { .. } => Err(corrupted(leaf, "expected a leaf")),
+        }
+    }
+
+    /// Insert a key-value pair, returning the old value if `key` was present
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, BPlusTreeError> {
+        self.transaction(|tree| {
+            let (old, split) = tree.insert_into(tree.root, key, value)?;
+            if let Some((separator, right)) = split {
+                let root = tree.pager.allocate();
+                tree.write_node(
+                    root,
+                    &BPlusNode::Internal {
+                        keys: vec![separator],
+                        children: vec![tree.root, right],
+                    },
+                )?;
+                tree.root = root;
+            }
+            if old.is_none() {
+                tree.len += 1;
+            }
+            Ok(old)
+        })
+    }
+
+    /// Remove `key` from the tree, returning its value
+    pub fn remove(&mut self, key: &K) -> Result<Option<V>, BPlusTreeError> {
+        self.transaction(|tree| {
+            let leaf = tree.find_leaf(Bound::Included(key))?;
+            let (mut keys, mut values, next) = match tree.read_node(leaf)? {
+                BPlusNode::Leaf { keys, values, next } => (keys, values, next),
+                BPlusNode::Internal { .. } => return Err(corrupted(leaf, "expected a leaf")),
+            };
+            let Ok(i) = keys.binary_search(key) else {
+                return Ok(None);
+            };
+            keys.remove(i);
+            let value = values.remove(i);
+            tree.write_node(leaf, &BPlusNode::Leaf { keys, values, next })?;
+            tree.len -= 1;
+            Ok(Some(value))
+        })
+    }
+
+    /// Entries whose keys fall in `range`, in ascending key order
+    ///
+    /// Finds the first leaf through the internal nodes, then follows the
+    /// links between leaves.
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> Result<Vec<(K, V)>, BPlusTreeError> {
+        let mut result = Vec::new();
+        let mut page = self.find_leaf(range.start_bound())?;
+        while page != HEADER_PAGE {
+            let (keys, values, next) = match self.read_node(page)? {
+                BPlusNode::Leaf { keys, values, next } => (keys, values, next),
+                BPlusNode::Internal { .. } => return Err(corrupted(page, "expected a leaf")),
+            };
+            for (key, value) in keys.into_iter().zip(values) {
+                if range.contains(&key) {
+                    result.push((key, value));
+                } else if !before_start(&range, &key) {
+                    return Ok(result);
+                }
+            }
+            page = next;
+        }
+        Ok(result)
+    }
+
+    /// Write every cached page back to the page file and empty the log
+    pub fn flush(&mut self) -> Result<(), BPlusTreeError> {
+        self.pager.checkpoint()?;
+        Ok(())
+    }
+
+    // runs `op` and commits its pages, or drops them and restores the root
+    // and length if any step before the commit record is in the log fails
+    fn transaction<R, F>(&mut self, op: F) -> Result<R, BPlusTreeError>
+    where
+        F: FnOnce(&mut Self) -> Result<R, BPlusTreeError>,
+    {
+        let (root, len) = (self.root, self.len);
+        let result = op(self).and_then(|result| {
+            if (self.root, self.len) != (root, len) {
+                self.write_header()?;
+            }
+            self.pager.commit()?;
+            Ok(result)
+        });
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                self.pager.abort();
+                self.root = root;
+                self.len = len;
+                return Err(err);
+            }
+        };
+
+        // the operation is durable, a failure from here on does not undo it
+        self.pager.evict_to_capacity()?;
+        if self.pager.wal_size() > CHECKPOINT_WAL_SIZE {
+            self.pager.checkpoint()?;
+        }
+        Ok(result)
+    }
+
+    // leaf that holds the smallest key inside `start`
+    fn find_leaf(&mut self, start: Bound<&K>) -> Result<u64, BPlusTreeError> {
+        let mut page = self.root;
+        loop {
+            match self.read_node(page)? {
+                BPlusNode::Leaf { .. } => return Ok(page),
+                BPlusNode::Internal { keys, children } => {
+                    let i = match start {
+                        Bound::Included(key) | Bound::Excluded(key) => {
+                            keys.partition_point(|k| k <= key)
+                        }
+                        Bound::Unbounded => 0,
+                    };
+                    page = children[i];
+                }
+            }
+        }
+    }
+
+    // returns the old value and, if the node was split, the separator and new right node
+    #[allow(clippy::type_complexity)]
+    fn insert_into(
+        &mut self,
+        page: u64,
+        key: K,
+        value: V,
+    ) -> Result<(Option<V>, Option<(K, u64)>), BPlusTreeError> {
+        match self.read_node(page)? {
+            BPlusNode::Leaf {
+                mut keys,
+                mut values,
+                next,
+            } => {
+                let old = match keys.binary_search(&key) {
+                    Ok(i) => Some(core::mem::replace(&mut values[i], value)),
+                    Err(i) => {
+                        keys.insert(i, key);
+                        values.insert(i, value);
+                        None
+                    }
+                };
+                if keys.len() <= self.max_leaf_keys {
+                    self.write_node(page, &BPlusNode::Leaf { keys, values, next })?;
+                    return Ok((old, None));
+                }
+
+                let mid = keys.len() / 2;
+                let right_keys = keys.split_off(mid);
+                let right_values = values.split_off(mid);
+                let separator = right_keys[0].clone();
+                let right = self.pager.allocate();
+                self.write_node(
+                    right,
+                    &BPlusNode::Leaf {
+                        keys: right_keys,
+                        values: right_values,
+                        next,
+                    },
+                )?;
+                self.write_node(
+                    page,
+                    &BPlusNode::Leaf {
+                        keys,
+                        values,
+                        next: right,
+                    },
+                )?;
+                Ok((old, Some((separator, right))))
+            }
+            BPlusNode::Internal {
+                mut keys,
+                mut children,
+            } => {
+                let i = keys.partition_point(|k| *k <= key);
+                let (old, split) = self.insert_into(children[i], key, value)?;
+                let Some((separator, child)) = split else {
+                    return Ok((old, None));
+                };
+                keys.insert(i, separator);
+                children.insert(i + 1, child);
+                if keys.len() <= self.max_internal_keys {
+                    self.write_node(page, &BPlusNode::Internal { keys, children })?;
+                    return Ok((old, None));
+                }
+
+                // the middle key moves up, it is not kept in either half
+                let mid = keys.len() / 2;
+                let right_keys = keys.split_off(mid + 1);
+                let right_children = children.split_off(mid + 1);
+                let separator = keys.pop().expect("internal node has a middle key");
+                let right = self.pager.allocate();
+                self.write_node(
+                    right,
+                    &BPlusNode::Internal {
+                        keys: right_keys,
+                        children: right_children,
+                    },
+                )?;
+                self.write_node(page, &BPlusNode::Internal { keys, children })?;
+                Ok((old, Some((separator, right))))
+            }
+        }
+    }
+
+    // magic | page size u32 | key size u32 | value size u32 | pad u32 | root u64 | len u64
+    fn write_header(&mut self) -> Result<(), BPlusTreeError> {
+        let mut buf = vec![0u8; PAGE_SIZE];
+        buf[0..8].copy_from_slice(MAGIC);
+        buf[8..12].copy_from_slice(&(PAGE_SIZE as u32).to_le_bytes());
+        buf[12..16].copy_from_slice(&(K::SIZE as u32).to_le_bytes());
+        buf[16..20].copy_from_slice(&(V::SIZE as u32).to_le_bytes());
+        buf[24..32].copy_from_slice(&self.root.to_le_bytes());
+        buf[32..40].copy_from_slice(&self.len.to_le_bytes());
+        self.pager.write(HEADER_PAGE, &buf)?;
+        Ok(())
+    }
+
+    fn read_header(&mut self) -> Result<(), BPlusTreeError> {
+        let page_count = self.pager.page_count();
+        let buf = self.pager.read(HEADER_PAGE)?;
+        if &buf[0..8] != MAGIC {
+            return Err(corrupted(HEADER_PAGE, "bad magic number"));
+        }
+        if u32_at(buf, 8) as usize != PAGE_SIZE {
+            return Err(corrupted(HEADER_PAGE, "page size mismatch"));
+        }
+        let key_size = u32_at(buf, 12) as usize;
+        let value_size = u32_at(buf, 16) as usize;
+        if key_size != K::SIZE || value_size != V::SIZE {
+            return Err(BPlusTreeError::SizeMismatch(key_size, value_size));
+        }
+        let root = u64_at(buf, 24);
+        let len = u64_at(buf, 32);
+        if root == HEADER_PAGE || root >= page_count {
+            return Err(corrupted(HEADER_PAGE, "root page out of range"));
+        }
+        self.root = root;
+        self.len = len;
+        Ok(())
+    }
+
+    fn read_node(&mut self, page: u64) -> Result<BPlusNode<K, V>, BPlusTreeError> {
+        let (max_leaf_keys, max_internal_keys) = (self.max_leaf_keys, self.max_internal_keys);
+        let buf = self.pager.read(page)?;
+        let n = u16::from_le_bytes([buf[1], buf[2]]) as usize;
+        let mut offset = NODE_HEADER_SIZE;
+        let mut keys = Vec::with_capacity(n + 1);
+        match buf[0] {
+            LEAF if n <= max_leaf_keys => {
+                let mut values = Vec::with_capacity(n + 1);
+                for _ in 0..n {
+                    keys.push(K::decode(&buf[offset..offset + K::SIZE]));
+                    offset += K::SIZE;
+                }
+                for _ in 0..n {
+                    values.push(V::decode(&buf[offset..offset + V::SIZE]));
+                    offset += V::SIZE;
+                }
+                let next = u64_at(buf, 3);
+                Ok(BPlusNode::Leaf { keys, values, next })
+            }
+            INTERNAL if n <= max_internal_keys => {
+                let mut children = Vec::with_capacity(n + 2);
+                for _ in 0..n {
+                    keys.push(K::decode(&buf[offset..offset + K::SIZE]));
+                    offset += K::SIZE;
+                }
+                for _ in 0..=n {
+                    children.push(u64_at(buf, offset));
+                    offset += 8;
+                }
+                Ok(BPlusNode::Internal { keys, children })
+            }
+            kind => Err(corrupted(
+                page,
+                &format!("node kind {kind} with {n} keys is invalid"),
+            )),
+        }
+    }
+
+    fn write_node(&mut self, page: u64, node: &BPlusNode<K, V>) -> Result<(), BPlusTreeError> {
+        let mut buf = vec![0u8; PAGE_SIZE];
+        let mut offset = NODE_HEADER_SIZE;
+        match node {
+            BPlusNode::Leaf { keys, values, next } => {
+                buf[0] = LEAF;
+                buf[1..3].copy_from_slice(&(keys.len() as u16).to_le_bytes());
+                buf[3..11].copy_from_slice(&next.to_le_bytes());
+                for key in keys {
+                    key.encode(&mut buf[offset..offset + K::SIZE]);
+                    offset += K::SIZE;
+                }
+                for value in values {
+                    value.encode(&mut buf[offset..offset + V::SIZE]);
+                    offset += V::SIZE;
+                }
+            }
+            BPlusNode::Internal { keys, children } => {
+                buf[0] = INTERNAL;
+                buf[1..3].copy_from_slice(&(keys.len() as u16).to_le_bytes());
+                for key in keys {
+                    key.encode(&mut buf[offset..offset + K::SIZE]);
+                    offset += K::SIZE;
+                }
+                for child in children {
+                    buf[offset..offset + 8].copy_from_slice(&child.to_le_bytes());
+                    offset += 8;
+                }
+            }
+        }
+        self.pager.write(page, &buf)?;
+        Ok(())
+    }
+}
+
+impl<K, V> Drop for BPlusTree<K, V> {
+    fn drop(&mut self) {
+        // every operation is already in the log, a failed checkpoint only delays the write back
+        let _ = self.pager.checkpoint();
+    }
+}
+
+fn wal_path(path: &Path) -> PathBuf {
+    let mut wal: OsString = path.as_os_str().to_owned();
+    wal.push(".wal");
+    PathBuf::from(wal)
+}
+
+fn before_start<K: Ord, R: RangeBounds<K>>(range: &R, key: &K) -> bool {
+    match range.start_bound() {
+        Bound::Included(start) => key < start,
+        Bound::Excluded(start) => key <= start,
+        Bound::Unbounded => false,
+    }
+}
+
+fn corrupted(page: u64, reason: &str) -> BPlusTreeError {
+    BPlusTreeError::Corrupted(format!("page {page}: {reason}"))
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&buf[offset..offset + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&buf[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+
+    // removes the page file and its log when the test ends
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("algorithms-rs-{name}-{}.db", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            let _ = std::fs::remove_file(wal_path(&path));
+            Self(path)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+            let _ = std::fs::remove_file(wal_path(&self.0));
+        }
+    }
+
+    #[test]
+    fn test_random_against_btree_map() {
+        let path = TempPath::new("random");
+        // big values keep leaves small so the tree gets several levels
+        let mut tree = BPlusTree::<u32, [u8; 200]>::open_with_buffer_pool(&path.0, 8).unwrap();
+        let mut expected = BTreeMap::new();
+        let mut rng = StdRng::seed_from_u64(29);
+        for _ in 0..3000 {
+            let key = rng.gen_range(0..2000u32);
+            let value = [key as u8; 200];
+            if rng.gen_bool(0.7) {
+                assert_eq!(
+                    tree.insert(key, value).unwrap(),
+                    expected.insert(key, value)
+                );
+            } else {
+                assert_eq!(tree.remove(&key).unwrap(), expected.remove(&key));
+            }
+        }
+        assert_eq!(tree.len(), expected.len());
+        let all: Vec<_> = expected.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(tree.range(..).unwrap(), all);
+        let part: Vec<_> = expected.range(500..=900).map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(tree.range(500..=900).unwrap(), part);
+
+        drop(tree);
+        let mut tree = BPlusTree::<u32, [u8; 200]>::open(&path.0).unwrap();
+        assert_eq!(tree.len(), expected.len());
+        for (key, value) in expected.iter().take(100) {
+            assert_eq!(tree.search(key).unwrap(), Some(*value));
+        }
+    }
+
+    #[test]
+    fn test_buffer_pool_shrinks_after_commit() {
+        let path = TempPath::new("shrink");
+        let mut tree = BPlusTree::<u32, [u8; 200]>::open_with_buffer_pool(&path.0, 2).unwrap();
+        for key in 0..1000u32 {
+            tree.insert(key, [key as u8; 200]).unwrap();
+            assert!(tree.pager.cached_pages() <= 2);
+        }
+        for key in (0..1000u32).step_by(3) {
+            assert_eq!(tree.remove(&key).unwrap(), Some([key as u8; 200]));
+            assert!(tree.pager.cached_pages() <= 2);
+        }
+        assert_eq!(tree.len(), 666);
+        assert_eq!(tree.search(&1).unwrap(), Some([1; 200]));
+    }
+
+    #[test]
+    fn test_recover_from_log_after_crash() {
+        let path = TempPath::new("crash");
+        let mut tree = BPlusTree::<u64, u64>::open(&path.0).unwrap();
+        for key in 0..2000 {
+            tree.insert(key, key + 1).unwrap();
+        }
+        // skip the checkpoint in drop, the page file only has what eviction wrote
+        std::mem::forget(tree);
+
+        let mut tree = BPlusTree::<u64, u64>::open(&path.0).unwrap();
+        assert_eq!(tree.len(), 2000);
+        assert_eq!(tree.search(&1999).unwrap(), Some(2000));
+        assert_eq!(tree.range(..).unwrap().len(), 2000);
+    }
+
+    #[test]
+    fn test_torn_log_tail_is_ignored() {
+        let path = TempPath::new("torn");
+        let mut tree = BPlusTree::<u64, u64>::open(&path.0).unwrap();
+        tree.insert(1, 10).unwrap();
+        tree.insert(2, 20).unwrap();
+        std::mem::forget(tree);
+
+        // half written page record of a transaction that never committed
+        let mut wal = OpenOptions::new()
+            .append(true)
+            .open(wal_path(&path.0))
+            .unwrap();
+        wal.write_all(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 42, 42]).unwrap();
+        drop(wal);
+
+        let mut tree = BPlusTree::<u64, u64>::open(&path.0).unwrap();
+        assert_eq!(tree.range(..).unwrap(), vec![(1, 10), (2, 20)]);
+    }
+
+    #[test]
+    fn test_failed_operations_roll_back() {
+        let path = TempPath::new("rollback");
+        let wal = wal_path(&path.0);
+        let writable = |path: &Path| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .unwrap()
+        };
+        let read_only = |path: &Path| File::open(path).unwrap();
+
+        let mut tree = BPlusTree::<u32, [u8; 200]>::open_with_buffer_pool(&path.0, 4).unwrap();
+        let mut expected = BTreeMap::new();
+        for key in (0..400u32).step_by(2) {
+            tree.insert(key, [key as u8; 200]).unwrap();
+            expected.insert(key, [key as u8; 200]);
+        }
+
+        // the log rejects every commit
+        let files = tree.pager.replace_files(writable(&path.0), read_only(&wal));
+        for key in (1..100u32).step_by(2) {
+            assert!(tree.insert(key, [0; 200]).is_err());
+            assert!(tree.remove(&(key - 1)).is_err());
+        }
+        assert_eq!(tree.len(), expected.len());
+        tree.pager.replace_files(files.0, files.1);
+        let all: Vec<_> = expected.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(tree.range(..).unwrap(), all);
+
+        // the page file rejects write backs, which fails operations halfway
+        let files = tree.pager.replace_files(read_only(&path.0), writable(&wal));
+        let mut failed = 0;
+        for key in 400..600u32 {
+            match tree.insert(key, [key as u8; 200]) {
+                Ok(old) => assert_eq!(old, expected.insert(key, [key as u8; 200])),
+                Err(_) => failed += 1,
+            }
+        }
+        assert!(failed > 0);
+        assert_eq!(tree.len(), expected.len());
+        tree.pager.replace_files(files.0, files.1);
+
+        let all: Vec<_> = expected.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(tree.range(..).unwrap(), all);
+        for key in 400..600u32 {
+            tree.insert(key, [key as u8; 200]).unwrap();
+            expected.insert(key, [key as u8; 200]);
+        }
+        drop(tree);
+        let mut tree = BPlusTree::<u32, [u8; 200]>::open(&path.0).unwrap();
+        let all: Vec<_> = expected.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(tree.range(..).unwrap(), all);
+    }
+
+    #[test]
+    fn test_reopen_with_other_sizes_fails() {
+        let path = TempPath::new("mismatch");
+        drop(BPlusTree::<u64, u64>::open(&path.0).unwrap());
+        assert!(matches!(
+            BPlusTree::<u32, u64>::open(&path.0),
+            Err(BPlusTreeError::SizeMismatch(8, 8))
+        ));
+        assert!(matches!(
+            BPlusTree::<u64, [u8; 2000]>::open(&path.0),
+            Err(BPlusTreeError::EntryTooLarge(2008))
+        ));
+    }
+}
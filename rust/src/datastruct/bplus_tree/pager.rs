@@ -0,0 +1,253 @@
+//! Page file with a buffer pool in front of it
+//!
+//! Pages are cached in a fixed number of frames and evicted in least recently
+//! used order. A page modified since the last commit is pinned in memory,
+//! because writing it to the page file before it is in the log would break
+//! atomicity of the transaction. Pages that are dirty but committed can be
+//! written back at any time since replaying the log rewrites them anyway.
+//! Such a page is written back before its first change in a transaction, so
+//! aborting the transaction can drop the frame and read the page file again.
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use super::wal::Wal;
+use super::PAGE_SIZE;
+
+#[derive(Debug)]
+struct Frame {
+    page_id: u64,
+    data: Box<[u8]>,
+    dirty: bool,
+    last_used: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Pager {
+    file: File,
+    file_len: u64,
+    wal: Wal,
+    frames: Vec<Frame>,
+    // page id -> index into frames
+    table: HashMap<u64, usize>,
+    capacity: usize,
+    clock: u64,
+    page_count: u64,
+    // page count as of the last commit
+    committed_page_count: u64,
+    // pages modified since the last commit
+    uncommitted: BTreeSet<u64>,
+}
+
+impl Pager {
+    /// Open the page file, first redoing every committed transaction in the log
+    pub(crate) fn open(path: &Path, wal_path: &Path, capacity: usize) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut wal = Wal::open(wal_path)?;
+
+        let pages = wal.committed_pages()?;
+        for (page_id, data) in &pages {
+            file.seek(SeekFrom::Start(page_id * PAGE_SIZE as u64))?;
+            file.write_all(data)?;
+        }
+        if !pages.is_empty() {
+            file.sync_all()?;
+        }
+        if wal.size() > 0 {
+            wal.truncate()?;
+        }
+
+        let file_len = file.metadata()?.len();
+        let page_count = file_len.div_ceil(PAGE_SIZE as u64);
+        Ok(Self {
+            file,
+            file_len,
+            wal,
+            frames: Vec::with_capacity(capacity),
+            table: HashMap::new(),
+            capacity: capacity.max(1),
+            clock: 0,
+            page_count,
+            committed_page_count: page_count,
+            uncommitted: BTreeSet::new(),
+        })
+    }
+
+    /// Number of pages allocated so far
+    pub(crate) fn page_count(&self) -> u64 {
+        self.page_count
+    }
+
+    /// Size of the write-ahead log in bytes
+    pub(crate) fn wal_size(&self) -> u64 {
+        self.wal.size()
+    }
+
+    /// Reserve a new page at the end of the file, its content is all zeros
+    pub(crate) fn allocate(&mut self) -> u64 {
+        let page_id = self.page_count;
+        self.page_count += 1;
+        page_id
+    }
+
+    pub(crate) fn read(&mut self, page_id: u64) -> io::Result<&[u8]> {
+        let frame = self.fetch(page_id)?;
+        Ok(&self.frames[frame].data)
+    }
+
+    pub(crate) fn write(&mut self, page_id: u64, data: &[u8]) -> io::Result<()> {
+        let frame = self.fetch(page_id)?;
+        if !self.uncommitted.contains(&page_id) {
+            self.write_back(frame)?;
+        }
+        let frame = &mut self.frames[frame];
+        frame.data.copy_from_slice(data);
+        frame.dirty = true;
+        self.uncommitted.insert(page_id);
+        Ok(())
+    }
+
+    /// Make every page written since the last commit durable through the log
+    pub(crate) fn commit(&mut self) -> io::Result<()> {
+        if self.uncommitted.is_empty() {
+            self.committed_page_count = self.page_count;
+            return Ok(());
+        }
+        let frames = &self.frames;
+        let table = &self.table;
+        let pages = self.uncommitted.iter().map(|page_id| {
+            let frame = &frames[table[page_id]];
+            (*page_id, &frame.data[..])
+        });
+        self.wal.append_commit(pages)?;
+        self.uncommitted.clear();
+        self.committed_page_count = self.page_count;
+        Ok(())
+    }
+
+    /// Drop every page written and allocated since the last commit
+    pub(crate) fn abort(&mut self) {
+        let uncommitted = core::mem::take(&mut self.uncommitted);
+        self.frames
+            .retain(|frame| !uncommitted.contains(&frame.page_id));
+        self.table = self
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| (frame.page_id, index))
+            .collect();
+        self.page_count = self.committed_page_count;
+    }
+
+    /// Evict committed pages until the pool is back to its capacity, it grows
+    /// past it while a transaction has more uncommitted pages than that
+    pub(crate) fn evict_to_capacity(&mut self) -> io::Result<()> {
+        while self.frames.len() > self.capacity {
+            let Some(frame) = self.victim() else {
+                break;
+            };
+            self.write_back(frame)?;
+            let evicted = self.frames.swap_remove(frame);
+            self.table.remove(&evicted.page_id);
+            if let Some(moved) = self.frames.get(frame) {
+                self.table.insert(moved.page_id, frame);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write every committed page back to the page file and empty the log
+    pub(crate) fn checkpoint(&mut self) -> io::Result<()> {
+        self.commit()?;
+        for frame in 0..self.frames.len() {
+            self.write_back(frame)?;
+        }
+        self.file.sync_all()?;
+        self.wal.truncate()
+    }
+
+    fn fetch(&mut self, page_id: u64) -> io::Result<usize> {
+        self.clock += 1;
+        if let Some(&frame) = self.table.get(&page_id) {
+            self.frames[frame].last_used = self.clock;
+            return Ok(frame);
+        }
+
+        let mut data = vec![0u8; PAGE_SIZE].into_boxed_slice();
+        let offset = page_id * PAGE_SIZE as u64;
+        if offset < self.file_len {
+            self.file.seek(SeekFrom::Start(offset))?;
+            self.file.read_exact(&mut data)?;
+        }
+        let new_frame = Frame {
+            page_id,
+            data,
+            dirty: false,
+            last_used: self.clock,
+        };
+
+        let victim = if self.frames.len() < self.capacity {
+            None
+        } else {
+            self.victim()
+        };
+        let frame = match victim {
+            Some(frame) => {
+                self.write_back(frame)?;
+                self.table.remove(&self.frames[frame].page_id);
+                self.frames[frame] = new_frame;
+                frame
+            }
+            // every frame holds an uncommitted page, grow past the capacity until the commit
+            None => {
+                self.frames.push(new_frame);
+                self.frames.len() - 1
+            }
+        };
+        self.table.insert(page_id, frame);
+        Ok(frame)
+    }
+
+    fn victim(&self) -> Option<usize> {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter(|(_, frame)| !self.uncommitted.contains(&frame.page_id))
+            .min_by_key(|(_, frame)| frame.last_used)
+            .map(|(index, _)| index)
+    }
+
+    fn write_back(&mut self, frame: usize) -> io::Result<()> {
+        let frame = &mut self.frames[frame];
+        if !frame.dirty {
+            return Ok(());
+        }
+        let offset = frame.page_id * PAGE_SIZE as u64;
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.write_all(&frame.data)?;
+        self.file_len = self.file_len.max(offset + PAGE_SIZE as u64);
+        frame.dirty = false;
+        Ok(())
+    }
+
+    /// Number of pages in the buffer pool
+    #[cfg(test)]
+    pub(crate) fn cached_pages(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Swap in other handles for the page file and the log, returning the old ones
+    #[cfg(test)]
+    pub(crate) fn replace_files(&mut self, file: File, wal: File) -> (File, File) {
+        (
+            core::mem::replace(&mut self.file, file),
+            self.wal.replace_file(wal),
+        )
+    }
+}
@@ -0,0 +1,146 @@
+//! Write-ahead log of full page images
+//!
+//! Every commit appends the new image of each page it modified followed by
+//! a commit record holding the number of pages and a checksum over them. A
+//! commit is durable once the log has been synced, the page file itself may
+//! be written later. When the log is replayed only transactions with an
+//! intact commit record are returned, so a torn tail from a crash while
+//! appending is ignored.
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use super::PAGE_SIZE;
+
+const PAGE_RECORD: u8 = 1;
+const COMMIT_RECORD: u8 = 2;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[derive(Debug)]
+pub(crate) struct Wal {
+    file: File,
+    size: u64,
+}
+
+impl Wal {
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { file, size })
+    }
+
+    /// Number of bytes in the log
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Append one transaction and sync it to disk
+    pub(crate) fn append_commit<'a, I>(&mut self, pages: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = (u64, &'a [u8])>,
+    {
+        let mut buf = Vec::new();
+        let mut checksum = FNV_OFFSET_BASIS;
+        let mut count = 0u64;
+        for (page_id, data) in pages {
+            buf.push(PAGE_RECORD);
+            buf.extend_from_slice(&page_id.to_le_bytes());
+            buf.extend_from_slice(data);
+            checksum = fnv1a(checksum, &page_id.to_le_bytes());
+            checksum = fnv1a(checksum, data);
+            count += 1;
+        }
+        buf.push(COMMIT_RECORD);
+        buf.extend_from_slice(&count.to_le_bytes());
+        buf.extend_from_slice(&checksum.to_le_bytes());
+
+        self.file.seek(SeekFrom::Start(self.size))?;
+        self.file.write_all(&buf)?;
+        self.file.sync_data()?;
+        self.size += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Page images of all committed transactions, in log order
+    pub(crate) fn committed_pages(&mut self) -> io::Result<Vec<(u64, Vec<u8>)>> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(&self.file);
+        let mut committed = Vec::new();
+        let mut pending = Vec::new();
+        let mut checksum = FNV_OFFSET_BASIS;
+        let mut tag = [0u8; 1];
+        let mut word = [0u8; 8];
+        loop {
+            if read_record_part(&mut reader, &mut tag)?.is_none() {
+                break;
+            }
+            match tag[0] {
+                PAGE_RECORD => {
+                    let mut data = vec![0u8; PAGE_SIZE];
+                    if read_record_part(&mut reader, &mut word)?.is_none()
+                        || read_record_part(&mut reader, &mut data)?.is_none()
+                    {
+                        break;
+                    }
+                    checksum = fnv1a(checksum, &word);
+                    checksum = fnv1a(checksum, &data);
+                    pending.push((u64::from_le_bytes(word), data));
+                }
+                COMMIT_RECORD => {
+                    let mut expected = [0u8; 8];
+                    if read_record_part(&mut reader, &mut word)?.is_none()
+                        || read_record_part(&mut reader, &mut expected)?.is_none()
+                    {
+                        break;
+                    }
+                    if u64::from_le_bytes(word) != pending.len() as u64
+                        || u64::from_le_bytes(expected) != checksum
+                    {
+                        break;
+                    }
+                    committed.append(&mut pending);
+                    checksum = FNV_OFFSET_BASIS;
+                }
+                _ => break,
+            }
+        }
+        Ok(committed)
+    }
+
+    /// Drop every record, called once all logged pages reached the page file
+    pub(crate) fn truncate(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        self.file.sync_all()?;
+        self.size = 0;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn replace_file(&mut self, file: File) -> File {
+        core::mem::replace(&mut self.file, file)
+    }
+}
+
+// Ok(None) when the log ends before the buffer is filled
+fn read_record_part<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<Option<()>> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(Some(())),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
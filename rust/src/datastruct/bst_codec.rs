@@ -0,0 +1,454 @@
+//! Building binary search trees from traversals and serializing them
+//!
+//! Besides inserting keys one at a time, a `BinarySearchTree` can be rebuilt
+//! from a sorted slice (giving a balanced tree), from its preorder walk, or
+//! from its preorder and inorder walks. Equal keys follow `tree_insert` and go
+//! to the right subtree.
+//!
+//! The byte encoding lists the nodes in level order with a marker per slot:
+//!
+//! ```no
+//! magic   "BST"
+//! version u8, currently 1
+//! width   u32, FixedSize::SIZE of the keys
+//! slots   u32, number of slots that follow
+//! slot    u8 0 for a missing child, or u8 1 followed by `width` key bytes
+//! ```
+//!
+//! The children of the present slots follow in the order of their parents,
+//! and missing children at the end are left out. All integers are little
+//! endian.
+use super::{BinarySearchTree, FixedSize, Node};
+use std::collections::VecDeque;
+use thiserror::Error;
+
+const MAGIC: &[u8; 3] = b"BST";
+/// Version written by `BinarySearchTree::serialize`
+pub const BST_ENCODING_VERSION: u8 = 1;
+// magic, version, width, slots
+const HEADER_SIZE: usize = 3 + 1 + 4 + 4;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BstCodecError {
+    #[error("traversals have {0} and {1} keys")]
+    LengthMismatch(usize, usize),
+    #[error("traversals do not describe the same tree")]
+    InconsistentTraversals,
+    #[error("keys are not the walk of a binary search tree")]
+    NotSearchTree,
+    #[error("unsupported encoding version {0}")]
+    UnsupportedVersion(u8),
+    #[error("keys were encoded with {0} bytes, expected {1}")]
+    SizeMismatch(usize, usize),
+    #[error("encoding is corrupted: {0}")]
+    Corrupted(String),
+}
+
+fn leaf<T>(data: T) -> Box<Node<T>> {
+    Box::new(Node {
+        data,
+        left: None,
+        right: None,
+    })
+}
+
+impl<T: PartialOrd + Clone> BinarySearchTree<T> {
+    /// Creating a tree of minimum height from keys in ascending order
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` is not sorted.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::BinarySearchTree;
+    ///
+    /// let tree = BinarySearchTree::from_sorted(&[1, 2, 3, 4, 5, 6, 7]);
+    ///
+    /// assert_eq!(tree.inorder_tree_walk(), vec![1, 2, 3, 4, 5, 6, 7]);
+    /// assert_eq!(tree.preorder_tree_walk(), vec![4, 2, 1, 3, 6, 5, 7]);
+    /// ```
+    pub fn from_sorted(keys: &[T]) -> Self {
+        assert!(
+            keys.windows(2).all(|w| w[0] <= w[1]),
+            "keys must be in ascending order"
+        );
+        fn build<T: PartialOrd + Clone>(keys: &[T]) -> Option<Box<Node<T>>> {
+            if keys.is_empty() {
+                return None;
+            }
+            // equal keys belong to the right subtree, so the root is the
+            // first copy of the middle key
+            let middle = &keys[keys.len() / 2];
+            let mid = keys.partition_point(|k| k < middle);
+            let mut node = leaf(keys[mid].clone());
+            node.left = build(&keys[..mid]);
+            node.right = build(&keys[mid + 1..]);
+            Some(node)
+        }
+        BinarySearchTree { root: build(keys) }
+    }
+
+    /// Creating the tree whose preorder walk is `preorder`, in O(n) time
+    ///
+    /// ```no
+    /// BUILD(preorder, low, high)
+    ///     if next key k is missing or not low <= k < high
+    ///         return NIL
+    ///     x = new node with key k, advance to the next key
+    ///     x.left = BUILD(preorder, low, x.key)
+    ///     x.right = BUILD(preorder, x.key, high)
+    ///     return x
+    /// ```
+    pub fn from_preorder(preorder: &[T]) -> Result<Self, BstCodecError> {
+        fn build<T: PartialOrd + Clone>(
+            keys: &[T],
+            next: &mut usize,
+            low: Option<&T>,
+            high: Option<&T>,
+        ) -> Option<Box<Node<T>>> {
+            let key = keys.get(*next)?;
+            if low.is_some_and(|low| key < low) || high.is_some_and(|high| key >= high) {
+                return None;
+            }
+            *next += 1;
+            let mut node = leaf(key.clone());
+            node.left = build(keys, next, low, Some(key));
+            node.right = build(keys, next, Some(key), high);
+            Some(node)
+        }
+        let mut next = 0;
+        let root = build(preorder, &mut next, None, None);
+        if next != preorder.len() {
+            return Err(BstCodecError::NotSearchTree);
+        }
+        Ok(BinarySearchTree { root })
+    }
+
+    /// Creating the tree from its preorder and inorder walks
+    ///
+    /// The first key of every preorder range is the root of that subtree; its
+    /// first copy in the inorder range splits the keys of the left and right
+    /// subtrees. Takes O(n h) time for a tree of height h.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::BinarySearchTree;
+    ///
+    /// let tree = BinarySearchTree::from_preorder_inorder(&[2, 1, 3], &[1, 2, 3]).unwrap();
+    ///
+    /// assert_eq!(tree.preorder_tree_walk(), vec![2, 1, 3]);
+    /// ```
+    pub fn from_preorder_inorder(preorder: &[T], inorder: &[T]) -> Result<Self, BstCodecError> {
+        if preorder.len() != inorder.len() {
+            return Err(BstCodecError::LengthMismatch(preorder.len(), inorder.len()));
+        }
+        if !inorder.windows(2).all(|w| w[0] <= w[1]) {
+            return Err(BstCodecError::NotSearchTree);
+        }
+        fn build<T: PartialOrd + Clone>(
+            preorder: &[T],
+            inorder: &[T],
+        ) -> Result<Option<Box<Node<T>>>, BstCodecError> {
+            let Some((key, rest)) = preorder.split_first() else {
+                return Ok(None);
+            };
+            let mid = inorder
+                .iter()
+                .position(|k| k == key)
+                .ok_or(BstCodecError::InconsistentTraversals)?;
+            let mut node = leaf(key.clone());
+            node.left = build(&rest[..mid], &inorder[..mid])?;
+            node.right = build(&rest[mid..], &inorder[mid + 1..])?;
+            Ok(Some(node))
+        }
+        Ok(BinarySearchTree {
+            root: build(preorder, inorder)?,
+        })
+    }
+
+    /// Keys in preorder, each node before its left and right subtrees
+    pub fn preorder_tree_walk(&self) -> Vec<T> {
+        let mut result = Vec::new();
+        let mut stack: Vec<&Node<T>> = self.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            result.push(node.data.clone());
+            stack.extend(node.right.as_deref());
+            stack.extend(node.left.as_deref());
+        }
+        result
+    }
+}
+
+impl<T: PartialOrd + Clone + FixedSize> BinarySearchTree<T> {
+    /// Encode the tree in level order, see the module documentation
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::BinarySearchTree;
+    ///
+    /// let tree = BinarySearchTree::from_sorted(&[1u32, 2, 3, 4]);
+    ///
+    /// let bytes = tree.serialize();
+    /// let copy = BinarySearchTree::<u32>::deserialize(&bytes).unwrap();
+    ///
+    /// assert_eq!(copy.preorder_tree_walk(), tree.preorder_tree_walk());
+    /// ```
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut slots = Vec::new();
+        let mut queue: VecDeque<Option<&Node<T>>> = VecDeque::from([self.root.as_deref()]);
+        while let Some(slot) = queue.pop_front() {
+            if let Some(node) = slot {
+                queue.push_back(node.left.as_deref());
+                queue.push_back(node.right.as_deref());
+            }
+            slots.push(slot);
+        }
+        while slots.last().is_some_and(|slot| slot.is_none()) {
+            slots.pop();
+        }
+
+        let mut out = Vec::with_capacity(HEADER_SIZE + slots.len() * (1 + T::SIZE));
+        out.extend_from_slice(MAGIC);
+        out.push(BST_ENCODING_VERSION);
+        out.extend_from_slice(&(T::SIZE as u32).to_le_bytes());
+        out.extend_from_slice(&(slots.len() as u32).to_le_bytes());
+        let mut buf = vec![0u8; T::SIZE];
+        for slot in slots {
+            match slot {
+                None => out.push(0),
+                Some(node) => {
+                    out.push(1);
+                    node.data.encode(&mut buf);
+                    out.extend_from_slice(&buf);
+                }
+            }
+        }
+        out
+    }
+
+    /// Decode a tree written by `serialize`
+    ///
+    /// Fails on a different version or key width, on truncated or trailing
+    /// bytes, and on trees that violate the search tree property.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, BstCodecError> {
+        let corrupted = |reason: &str| BstCodecError::Corrupted(reason.to_string());
+        if bytes.len() < HEADER_SIZE || &bytes[..3] != MAGIC {
+            return Err(corrupted("missing header"));
+        }
+        if bytes[3] != BST_ENCODING_VERSION {
+            return Err(BstCodecError::UnsupportedVersion(bytes[3]));
+        }
+        let read_u32 = |at: usize| {
+            let mut word = [0u8; 4];
+            word.copy_from_slice(&bytes[at..at + 4]);
+            u32::from_le_bytes(word) as usize
+        };
+        let width = read_u32(4);
+        if width != T::SIZE {
+            return Err(BstCodecError::SizeMismatch(width, T::SIZE));
+        }
+        let count = read_u32(8);
+
+        let mut keys = Vec::new();
+        let mut rest = &bytes[HEADER_SIZE..];
+        for _ in 0..count {
+            match rest.split_first() {
+                Some((0, tail)) => {
+                    keys.push(None);
+                    rest = tail;
+                }
+                Some((1, tail)) if tail.len() >= width => {
+                    keys.push(Some(T::decode(&tail[..width])));
+                    rest = &tail[width..];
+                }
+                Some((1, _)) | None => return Err(corrupted("truncated slot")),
+                Some((marker, _)) => return Err(corrupted(&format!("slot marker {marker}"))),
+            }
+        }
+        if !rest.is_empty() {
+            return Err(corrupted("trailing bytes"));
+        }
+        if keys.first().is_some_and(|key| key.is_none()) {
+            return Err(corrupted("missing root"));
+        }
+
+        // children[i] are the slots of the left and right child of slot i
+        let mut children = vec![(None, None); keys.len()];
+        let mut next = 1;
+        for (i, key) in keys.iter().enumerate() {
+            if key.is_none() {
+                continue;
+            }
+            if i >= next {
+                return Err(corrupted("slot without a parent"));
+            }
+            children[i] = (Some(next), Some(next + 1));
+            next += 2;
+        }
+        if next < keys.len() {
+            return Err(corrupted("slot without a parent"));
+        }
+
+        // every child comes after its parent, so build from the last slot back
+        let mut nodes: Vec<Option<Box<Node<T>>>> = (0..keys.len()).map(|_| None).collect();
+        for i in (0..keys.len()).rev() {
+            let Some(key) = keys[i].take() else {
+                continue;
+            };
+            let mut node = leaf(key);
+            let (left, right) = children[i];
+            node.left = left.and_then(|l| nodes.get_mut(l)?.take());
+            node.right = right.and_then(|r| nodes.get_mut(r)?.take());
+            nodes[i] = Some(node);
+        }
+        let tree = BinarySearchTree {
+            root: nodes.into_iter().next().flatten(),
+        };
+        if !tree.is_search_tree() {
+            return Err(BstCodecError::NotSearchTree);
+        }
+        Ok(tree)
+    }
+}
+
+impl<T: PartialOrd> BinarySearchTree<T> {
+    // left subtree keys < x.key <= right subtree keys, for every node x
+    fn is_search_tree(&self) -> bool {
+        let mut stack: Vec<(&Node<T>, Option<&T>, Option<&T>)> = Vec::new();
+        stack.extend(self.root.as_deref().map(|root| (root, None, None)));
+        while let Some((node, low, high)) = stack.pop() {
+            let key = &node.data;
+            if low.is_some_and(|low| key < low) || high.is_some_and(|high| key >= high) {
+                return false;
+            }
+            stack.extend(node.left.as_deref().map(|left| (left, low, Some(key))));
+            stack.extend(node.right.as_deref().map(|right| (right, Some(key), high)));
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn height<T>(node: Option<&Node<T>>) -> usize {
+        node.map_or(0, |n| {
+            1 + height(n.left.as_deref()).max(height(n.right.as_deref()))
+        })
+    }
+
+    fn random_tree(rng: &mut StdRng, n: usize) -> BinarySearchTree<i32> {
+        let mut tree = BinarySearchTree::new();
+        for _ in 0..n {
+            tree.tree_insert(rng.gen_range(-50..50));
+        }
+        tree
+    }
+
+    #[test]
+    fn test_from_sorted_is_balanced() {
+        let keys: Vec<i32> = (0..1000).collect();
+        let tree = BinarySearchTree::from_sorted(&keys);
+        assert_eq!(tree.inorder_tree_walk(), keys);
+        assert_eq!(height(tree.root.as_deref()), 10);
+
+        let duplicates = [1, 2, 2, 2, 2, 2, 3];
+        let tree = BinarySearchTree::from_sorted(&duplicates);
+        assert!(tree.is_search_tree());
+        assert_eq!(tree.inorder_tree_walk(), duplicates);
+    }
+
+    #[test]
+    fn test_rebuild_random_trees() {
+        let mut rng = StdRng::seed_from_u64(35);
+        for n in 0..60 {
+            let tree = random_tree(&mut rng, n);
+            let preorder = tree.preorder_tree_walk();
+            let inorder = tree.inorder_tree_walk();
+
+            let rebuilt = BinarySearchTree::from_preorder(&preorder).unwrap();
+            assert_eq!(rebuilt.preorder_tree_walk(), preorder);
+            let rebuilt = BinarySearchTree::from_preorder_inorder(&preorder, &inorder).unwrap();
+            assert_eq!(rebuilt.preorder_tree_walk(), preorder);
+            assert_eq!(rebuilt.inorder_tree_walk(), inorder);
+        }
+    }
+
+    #[test]
+    fn test_invalid_traversals() {
+        assert_eq!(
+            BinarySearchTree::from_preorder(&[5, 7, 3]).unwrap_err(),
+            BstCodecError::NotSearchTree
+        );
+        assert_eq!(
+            BinarySearchTree::from_preorder_inorder(&[1, 2], &[1]).unwrap_err(),
+            BstCodecError::LengthMismatch(2, 1)
+        );
+        assert_eq!(
+            BinarySearchTree::from_preorder_inorder(&[2, 1], &[2, 1]).unwrap_err(),
+            BstCodecError::NotSearchTree
+        );
+        assert_eq!(
+            BinarySearchTree::from_preorder_inorder(&[2, 4], &[1, 2]).unwrap_err(),
+            BstCodecError::InconsistentTraversals
+        );
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let mut rng = StdRng::seed_from_u64(350);
+        for n in 0..60 {
+            let tree = random_tree(&mut rng, n);
+            let copy = BinarySearchTree::<i32>::deserialize(&tree.serialize()).unwrap();
+            assert_eq!(copy.preorder_tree_walk(), tree.preorder_tree_walk());
+            assert_eq!(copy.inorder_tree_walk(), tree.inorder_tree_walk());
+        }
+    }
+
+    #[test]
+    fn test_serialize_layout() {
+        let mut tree = BinarySearchTree::new();
+        tree.tree_insert(2u8);
+        tree.tree_insert(3u8);
+        assert_eq!(
+            tree.serialize(),
+            [b'B', b'S', b'T', 1, 1, 0, 0, 0, 3, 0, 0, 0, 1, 2, 0, 1, 3]
+        );
+        assert_eq!(
+            BinarySearchTree::<u8>::new().serialize(),
+            [b'B', b'S', b'T', 1, 1, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_rejects_bad_input() {
+        let bytes = BinarySearchTree::from_sorted(&[1u16, 2, 3]).serialize();
+        let mut other_version = bytes.clone();
+        other_version[3] = 2;
+        assert_eq!(
+            BinarySearchTree::<u16>::deserialize(&other_version).unwrap_err(),
+            BstCodecError::UnsupportedVersion(2)
+        );
+        assert_eq!(
+            BinarySearchTree::<u32>::deserialize(&bytes).unwrap_err(),
+            BstCodecError::SizeMismatch(2, 4)
+        );
+        assert!(matches!(
+            BinarySearchTree::<u16>::deserialize(&bytes[..bytes.len() - 1]),
+            Err(BstCodecError::Corrupted(_))
+        ));
+
+        let mut swapped = BinarySearchTree::new();
+        swapped.tree_insert(1u16);
+        swapped.tree_insert(2u16);
+        let mut bytes = swapped.serialize();
+        // root 1 with right child 2 becomes root 2 with right child 1
+        bytes[HEADER_SIZE + 1] = 2;
+        bytes[HEADER_SIZE + 5] = 1;
+        assert_eq!(
+            BinarySearchTree::<u16>::deserialize(&bytes).unwrap_err(),
+            BstCodecError::NotSearchTree
+        );
+    }
+}
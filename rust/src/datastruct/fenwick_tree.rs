@@ -0,0 +1,149 @@
+//! Fenwick tree (binary indexed tree)
+//!
+//! Slot i, counting from 1, holds the sum of the `i & -i` elements ending at
+//! element i. A prefix sum adds up the slots found by repeatedly clearing the
+//! lowest set bit of the index, and a point update touches the slots found by
+//! repeatedly adding it, so both take O(lg n) time in a single array.
+use super::segment_tree::to_bounds;
+use core::ops::{RangeBounds, Sub};
+use num_traits::Zero;
+
+/// Fenwick tree for prefix sums with point updates
+///
+/// ```rust
+/// use algorithms_rs::datastruct::FenwickTree;
+///
+/// let mut tree = FenwickTree::from_slice(&[3, 1, 4, 1, 5]);
+///
+/// assert_eq!(tree.prefix_sum(3), 8);
+/// tree.add(2, 10);
+/// assert_eq!(tree.range_sum(1..=2), 15);
+/// assert_eq!(tree.range_sum(..), 24);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    // tree[0] is unused so the bit tricks work on 1-based indices
+    tree: Vec<T>,
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Zero + Sub<Output = T>,
+{
+    /// Creating a tree of `len` zeros
+    pub fn new(len: usize) -> Self {
+        Self {
+            tree: vec![T::zero(); len + 1],
+        }
+    }
+
+    /// Creating a tree over `values` in O(n) time
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut tree = vec![T::zero(); values.len() + 1];
+        tree[1..].copy_from_slice(values);
+        // hand every slot's sum on to the slot that covers it next
+        for i in 1..tree.len() {
+            let parent = i + lowest_bit(i);
+            if parent < tree.len() {
+                tree[parent] = tree[parent] + tree[i];
+            }
+        }
+        Self { tree }
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    /// Determine if there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `delta` to the element at `index`
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(
+            index < self.len(),
+            "index {index} out of range for length {}",
+            self.len()
+        );
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i] + delta;
+            i += lowest_bit(i);
+        }
+    }
+
+    /// Sum of the first `len` elements
+    pub fn prefix_sum(&self, len: usize) -> T {
+        assert!(
+            len <= self.len(),
+            "prefix of {len} out of range for length {}",
+            self.len()
+        );
+        let mut sum = T::zero();
+        let mut i = len;
+        while i > 0 {
+            sum = sum + self.tree[i];
+            i -= lowest_bit(i);
+        }
+        sum
+    }
+
+    /// Sum of the elements in `range`
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (start, end) = to_bounds(range, self.len());
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+
+    /// Element at `index`
+    pub fn get(&self, index: usize) -> T {
+        self.range_sum(index..=index)
+    }
+}
+
+fn lowest_bit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_random_against_vec() {
+        let mut rng = StdRng::seed_from_u64(390);
+        for len in 0..50 {
+            let mut values: Vec<i64> = (0..len).map(|_| rng.gen_range(-50..50)).collect();
+            let mut tree = FenwickTree::from_slice(&values);
+            let mut built = FenwickTree::new(len);
+            for (i, value) in values.iter().enumerate() {
+                built.add(i, *value);
+            }
+            assert_eq!(tree.tree, built.tree);
+            for _ in 0..100 {
+                if len > 0 && rng.gen_bool(0.5) {
+                    let i = rng.gen_range(0..len);
+                    let delta = rng.gen_range(-50..50);
+                    values[i] += delta;
+                    tree.add(i, delta);
+                } else {
+                    let l = rng.gen_range(0..=len);
+                    let r = rng.gen_range(l..=len);
+                    assert_eq!(tree.range_sum(l..r), values[l..r].iter().sum::<i64>());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_floats_and_get() {
+        let tree = FenwickTree::from_slice(&[0.5, 0.25, 0.125]);
+        assert_eq!(tree.prefix_sum(3), 0.875);
+        assert_eq!(tree.get(1), 0.25);
+        assert!(FenwickTree::<f64>::new(0).is_empty());
+    }
+}
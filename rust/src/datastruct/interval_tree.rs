@@ -0,0 +1,443 @@
+//! Interval tree (Introduction to Algorithms 14.3)
+//!
+//! A red-black tree keyed on the low endpoint of closed intervals. Every node
+//! additionally stores `max`, the largest high endpoint in its subtree, which
+//! lets the search for an overlapping interval skip whole subtrees.
+use super::rb_arena::{Augment, RbArena, RbNode, NIL};
+
+/// Closed interval `[low, high]`, built by `Interval::new` so that `low <= high`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    low: T,
+    high: T,
+}
+
+impl<T> Interval<T> {
+    /// Low endpoint
+    pub fn low(&self) -> &T {
+        &self.low
+    }
+
+    /// High endpoint
+    pub fn high(&self) -> &T {
+        &self.high
+    }
+}
+
+impl<T: Ord> Interval<T> {
+    /// Creating a closed interval
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low > high`.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::Interval;
+    ///
+    /// let interval = Interval::new(5, 8);
+    ///
+    /// assert!(interval.overlaps(&Interval::new(8, 9)));
+    /// assert!(!interval.overlaps(&Interval::new(9, 10)));
+    /// ```
+    pub fn new(low: T, high: T) -> Self {
+        assert!(low <= high, "interval low endpoint is greater than high");
+        Self { low, high }
+    }
+
+    /// Two closed intervals overlap if `i.low <= i'.high` and `i'.low <= i.high`
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.low <= other.high && other.low <= self.high
+    }
+
+    /// Determine if `point` lies in the interval
+    pub fn contains(&self, point: &T) -> bool {
+        self.low <= *point && *point <= self.high
+    }
+}
+
+#[derive(Debug)]
+struct IntervalData<T, V> {
+    // None only for the sentinel and for slots on the free list
+    entry: Option<(Interval<T>, V)>,
+    // x.max = max(x.int.high, x.left.max, x.right.max), None for the sentinel
+    max: Option<T>,
+}
+
+impl<T: Ord + Clone, V> Augment for IntervalData<T, V> {
+    fn recompute(nodes: &mut [RbNode<Self>], x: usize) {
+        let (l, r) = (nodes[x].left, nodes[x].right);
+        let high = nodes[x]
+            .data
+            .entry
+            .as_ref()
+            .map(|(interval, _)| &interval.high);
+        let max = high
+            .max(nodes[l].data.max.as_ref())
+            .max(nodes[r].data.max.as_ref())
+            .cloned();
+        nodes[x].data.max = max;
+    }
+}
+
+/// Interval tree mapping closed intervals to values
+///
+/// Nodes are ordered by `(low, high)`, identical intervals may be inserted
+/// more than once.
+#[derive(Debug)]
+pub struct IntervalTree<T, V> {
+    rb: RbArena<IntervalData<T, V>>,
+    len: usize,
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone, V> FromIterator<(Interval<T>, V)> for IntervalTree<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Interval<T>, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (interval, value) in iter {
+            tree.insert(interval, value);
+        }
+        tree
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    /// Creating an empty interval tree
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::IntervalTree;
+    ///
+    /// let tree = IntervalTree::<i32, ()>::new();
+    ///
+    /// assert_eq!(tree.is_empty(), true);
+    /// ```
+    pub fn new() -> Self {
+        let nil = IntervalData {
+            entry: None,
+            max: None,
+        };
+        Self {
+            rb: RbArena::new(nil),
+            len: 0,
+        }
+    }
+
+    /// Number of intervals in the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.rb.root == NIL
+    }
+
+    fn interval(&self, x: usize) -> &Interval<T> {
+        &self.rb.nodes[x]
+            .data
+            .entry
+            .as_ref()
+            .expect("sentinel has no interval")
+            .0
+    }
+
+    fn entry(&self, x: usize) -> (&Interval<T>, &V) {
+        let (interval, value) = self.rb.nodes[x]
+            .data
+            .entry
+            .as_ref()
+            .expect("sentinel has no interval");
+        (interval, value)
+    }
+
+    fn max(&self, x: usize) -> Option<&T> {
+        self.rb.nodes[x].data.max.as_ref()
+    }
+
+    /// Insert an interval together with its value
+    ///
+    /// ```no
+    /// INTERVAL-INSERT(T, x)
+    ///     RB-INSERT(T, x), on the way down every visited node y
+    ///         y.max = max(y.max, x.int.high)
+    /// ```
+    pub fn insert(&mut self, interval: Interval<T>, value: V) {
+        let mut y = NIL;
+        let mut x = self.rb.root;
+        while x != NIL {
+            y = x;
+            if self.max(x) < Some(&interval.high) {
+                self.rb.nodes[x].data.max = Some(interval.high.clone());
+            }
+            if interval < *self.interval(x) {
+                x = self.rb.nodes[x].left;
+            } else {
+                x = self.rb.nodes[x].right;
+            }
+        }
+
+        let go_left = y != NIL && interval < *self.interval(y);
+        let data = IntervalData {
+            max: Some(interval.high.clone()),
+            entry: Some((interval, value)),
+        };
+        self.rb.insert(data, y, go_left);
+        self.len += 1;
+    }
+
+    /// Remove one node holding exactly `interval`, returning its value
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::{Interval, IntervalTree};
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(Interval::new(1, 3), "a");
+    ///
+    /// assert_eq!(tree.delete(&Interval::new(1, 4)), None);
+    /// assert_eq!(tree.delete(&Interval::new(1, 3)), Some("a"));
+    /// assert_eq!(tree.is_empty(), true);
+    /// ```
+    pub fn delete(&mut self, interval: &Interval<T>) -> Option<V> {
+        let z = self.search(interval);
+        if z == NIL {
+            return None;
+        }
+        let data = self.rb.delete(z);
+        data.max = None;
+        let (_, value) = data.entry.take()?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Find any interval overlapping `query`
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::{Interval, IntervalTree};
+    ///
+    /// let tree: IntervalTree<i32, &str> = vec![
+    ///     (Interval::new(16, 21), "a"),
+    ///     (Interval::new(8, 9), "b"),
+    ///     (Interval::new(25, 30), "c"),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    ///
+    /// assert_eq!(
+    ///     tree.search_overlap(&Interval::new(22, 25)),
+    ///     Some((&Interval::new(25, 30), &"c"))
+    /// );
+    /// assert_eq!(tree.search_overlap(&Interval::new(11, 14)), None);
+    /// ```
+    /// ```no
+    /// INTERVAL-SEARCH(T, i)
+    ///     x = T.root
+    ///     while x != T.nil and i does not overlap x.int
+    ///         if x.left != T.nil and x.left.max >= i.low
+    ///             x = x.left
+    ///         else x = x.right
+    ///     return x
+    /// ```
+    pub fn search_overlap(&self, query: &Interval<T>) -> Option<(&Interval<T>, &V)> {
+        let mut x = self.rb.root;
+        while x != NIL && !query.overlaps(self.interval(x)) {
+            let left = self.rb.nodes[x].left;
+            if left != NIL && self.max(left) >= Some(&query.low) {
+                x = left;
+            } else {
+                x = self.rb.nodes[x].right;
+            }
+        }
+        if x == NIL {
+            None
+        } else {
+            Some(self.entry(x))
+        }
+    }
+
+    /// All intervals overlapping `query`, ordered by `(low, high)`
+    ///
+    /// Runs in O(min(n, k lg n)) for `k` reported intervals.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::{Interval, IntervalTree};
+    ///
+    /// let tree: IntervalTree<i32, ()> = vec![(1, 4), (2, 3), (5, 9), (10, 12)]
+    ///     .into_iter()
+    ///     .map(|(low, high)| (Interval::new(low, high), ()))
+    ///     .collect();
+    ///
+    /// let lows: Vec<i32> = tree
+    ///     .all_overlapping(&Interval::new(3, 5))
+    ///     .into_iter()
+    ///     .map(|(interval, _)| *interval.low())
+    ///     .collect();
+    ///
+    /// assert_eq!(lows, vec![1, 2, 5]);
+    /// ```
+    pub fn all_overlapping(&self, query: &Interval<T>) -> Vec<(&Interval<T>, &V)> {
+        let mut result = Vec::new();
+        self.collect_overlapping(self.rb.root, query, &mut result);
+        result
+    }
+
+    fn collect_overlapping<'a>(
+        &'a self,
+        x: usize,
+        query: &Interval<T>,
+        result: &mut Vec<(&'a Interval<T>, &'a V)>,
+    ) {
+        // no interval in this subtree ends at or after query.low
+        if x == NIL || self.max(x) < Some(&query.low) {
+            return;
+        }
+        self.collect_overlapping(self.rb.nodes[x].left, query, result);
+        let interval = self.interval(x);
+        if interval.overlaps(query) {
+            result.push(self.entry(x));
+        }
+        // every interval in the right subtree starts at or after x.int.low
+        if interval.low <= query.high {
+            self.collect_overlapping(self.rb.nodes[x].right, query, result);
+        }
+    }
+
+    /// All intervals containing `point`, ordered by `(low, high)`
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::{Interval, IntervalTree};
+    ///
+    /// let mut tree = IntervalTree::new();
+    /// tree.insert(Interval::new(9, 17), "morning");
+    /// tree.insert(Interval::new(13, 14), "lunch");
+    ///
+    /// let names: Vec<&str> = tree.stab(&13).into_iter().map(|(_, v)| *v).collect();
+    ///
+    /// assert_eq!(names, vec!["morning", "lunch"]);
+    /// ```
+    pub fn stab(&self, point: &T) -> Vec<(&Interval<T>, &V)> {
+        // a single point is a valid interval, no need to check it
+        self.all_overlapping(&Interval {
+            low: point.clone(),
+            high: point.clone(),
+        })
+    }
+
+    /// In-order walk of the tree
+    pub fn inorder_tree_walk(&self) -> Vec<(&Interval<T>, &V)> {
+        self.rb
+            .inorder()
+            .into_iter()
+            .map(|x| self.entry(x))
+            .collect()
+    }
+
+    fn search(&self, interval: &Interval<T>) -> usize {
+        self.rb.find(|data| {
+            let (other, _) = data.entry.as_ref().expect("sentinel has no interval");
+            interval.cmp(other)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // checks max and the red-black properties
+    fn check<V>(tree: &IntervalTree<i32, V>) {
+        tree.rb.black_height(tree.rb.root);
+        for x in tree.rb.inorder() {
+            let (l, r) = (tree.rb.nodes[x].left, tree.rb.nodes[x].right);
+            let expected = Some(&tree.interval(x).high)
+                .max(tree.max(l))
+                .max(tree.max(r));
+            assert_eq!(tree.max(x), expected);
+        }
+    }
+
+    #[test]
+    fn test_clrs_figure_14_4() {
+        let tree: IntervalTree<i32, ()> = vec![
+            (16, 21),
+            (8, 9),
+            (25, 30),
+            (5, 8),
+            (15, 23),
+            (17, 19),
+            (26, 26),
+            (0, 3),
+            (6, 10),
+            (19, 20),
+        ]
+        .into_iter()
+        .map(|(low, high)| (Interval::new(low, high), ()))
+        .collect();
+        check(&tree);
+
+        let found = tree.search_overlap(&Interval::new(22, 25)).unwrap().0;
+        assert!(found.overlaps(&Interval::new(22, 25)));
+        assert_eq!(tree.search_overlap(&Interval::new(11, 14)), None);
+        assert_eq!(tree.stab(&26).len(), 2);
+    }
+
+    #[test]
+    fn test_delete_keeps_max() {
+        let mut tree = IntervalTree::new();
+        tree.insert(Interval::new(1, 100), 1);
+        tree.insert(Interval::new(2, 3), 2);
+        tree.insert(Interval::new(4, 5), 3);
+        assert_eq!(tree.stab(&50).len(), 1);
+        assert_eq!(tree.delete(&Interval::new(1, 100)), Some(1));
+        check(&tree);
+        assert!(tree.stab(&50).is_empty());
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_interval() {
+        Interval::new(3, 1);
+    }
+
+    #[test]
+    fn test_random_against_linear_scan() {
+        let mut rng = StdRng::seed_from_u64(27);
+        let mut tree = IntervalTree::new();
+        let mut expected: Vec<Interval<i32>> = Vec::new();
+        for _ in 0..1000 {
+            let low = rng.gen_range(0..500);
+            let interval = Interval::new(low, low + rng.gen_range(0..40));
+            if rng.gen_bool(0.6) {
+                tree.insert(interval, ());
+                expected.push(interval);
+            } else {
+                let removed = tree.delete(&interval);
+                match expected.iter().position(|v| *v == interval) {
+                    Some(pos) => {
+                        expected.swap_remove(pos);
+                        assert_eq!(removed, Some(()));
+                    }
+                    None => assert_eq!(removed, None),
+                }
+            }
+            check(&tree);
+
+            let query = Interval::new(low, low + 10);
+            let mut naive: Vec<_> = expected.iter().filter(|v| v.overlaps(&query)).collect();
+            naive.sort();
+            let found: Vec<_> = tree
+                .all_overlapping(&query)
+                .into_iter()
+                .map(|(v, _)| v)
+                .collect();
+            assert_eq!(found, naive);
+            assert_eq!(tree.search_overlap(&query).is_some(), !naive.is_empty());
+        }
+    }
+}
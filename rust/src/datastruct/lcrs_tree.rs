@@ -0,0 +1,440 @@
+//! Rooted trees with unbounded branching (CLRS 10.4)
+//!
+//! Every node keeps a pointer to its leftmost child and one to the sibling
+//! immediately to its right, so a node of any degree uses O(1) space. The
+//! parent pointer allows walking the tree in preorder and postorder without a
+//! stack.
+use super::{BinaryTreeView, RootedTree, RootedTreeError};
+use core::fmt::Display;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+struct LcrsNode<T> {
+    value: T,
+    parent: Option<usize>,
+    left_child: Option<usize>,
+    right_sibling: Option<usize>,
+    // not part of the representation, kept so appending a child is O(1)
+    last_child: Option<usize>,
+}
+
+/// Left-child right-sibling tree, nodes are numbered from 0 in the order they are added
+///
+/// ```rust
+/// use algorithms_rs::datastruct::LcrsTree;
+///
+/// let mut tree = LcrsTree::new("/");
+/// let usr = tree.push_child(0, "usr");
+/// let bin = tree.push_child(usr, "bin");
+/// tree.push_child(0, "etc");
+///
+/// assert_eq!(tree.children(0).collect::<Vec<_>>(), vec![usr, 3]);
+/// assert_eq!(tree.preorder(0).map(|id| tree[id]).collect::<Vec<_>>(), vec!["/", "usr", "bin", "etc"]);
+/// assert_eq!(tree.depth(bin), 2);
+/// assert_eq!(tree.subtree_size(usr), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LcrsTree<T> {
+    nodes: Vec<LcrsNode<T>>,
+    root: usize,
+}
+
+impl<T> core::ops::Index<usize> for LcrsTree<T> {
+    type Output = T;
+
+    fn index(&self, id: usize) -> &T {
+        &self.nodes[id].value
+    }
+}
+
+impl<T> core::ops::IndexMut<usize> for LcrsTree<T> {
+    fn index_mut(&mut self, id: usize) -> &mut T {
+        &mut self.nodes[id].value
+    }
+}
+
+impl<T> LcrsTree<T> {
+    /// Creating a tree holding only the root, whose id is 0
+    pub fn new(root: T) -> Self {
+        Self {
+            nodes: vec![LcrsNode {
+                value: root,
+                parent: None,
+                left_child: None,
+                right_sibling: None,
+                last_child: None,
+            }],
+            root: 0,
+        }
+    }
+
+    /// Creating a tree from node values and the parent of every node
+    ///
+    /// Node `i` holds `values[i]` and children keep the order of their ids.
+    /// There must be exactly one value for every entry of `parents`.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::LcrsTree;
+    ///
+    /// let tree = LcrsTree::from_parents(
+    ///     vec!["ceo", "cto", "cfo", "dev"],
+    ///     &[None, Some(0), Some(0), Some(1)],
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(tree.root(), 0);
+    /// assert_eq!(tree.parent(3), Some(1));
+    /// assert_eq!(tree.subtree_sizes(), vec![4, 2, 1, 1]);
+    /// ```
+    pub fn from_parents(
+        values: Vec<T>,
+        parents: &[Option<usize>],
+    ) -> Result<Self, RootedTreeError> {
+        if values.len() != parents.len() {
+            return Err(RootedTreeError::ValueCount {
+                nodes: parents.len(),
+                values: values.len(),
+            });
+        }
+        let shape = RootedTree::from_parents(parents)?;
+        let mut nodes: Vec<LcrsNode<T>> = values
+            .into_iter()
+            .zip(parents)
+            .map(|(value, parent)| LcrsNode {
+                value,
+                parent: *parent,
+                left_child: None,
+                right_sibling: None,
+                last_child: None,
+            })
+            .collect();
+        for (id, node) in nodes.iter_mut().enumerate() {
+            let children = shape.children(id);
+            node.left_child = children.first().copied();
+            node.last_child = children.last().copied();
+        }
+        for id in 0..nodes.len() {
+            let children = shape.children(id);
+            for pair in children.windows(2) {
+                nodes[pair[0]].right_sibling = Some(pair[1]);
+            }
+        }
+        Ok(Self {
+            nodes,
+            root: shape.root(),
+        })
+    }
+
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// A tree always has a root, so it is never empty
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The root node, 0 unless the tree was built from parents
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Parent of `id`, None for the root
+    pub fn parent(&self, id: usize) -> Option<usize> {
+        self.nodes[id].parent
+    }
+
+    /// Leftmost child of `id`
+    pub fn left_child(&self, id: usize) -> Option<usize> {
+        self.nodes[id].left_child
+    }
+
+    /// Sibling immediately to the right of `id`
+    pub fn right_sibling(&self, id: usize) -> Option<usize> {
+        self.nodes[id].right_sibling
+    }
+
+    /// Add `value` as the new rightmost child of `parent`, returning its id
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` is not a node of the tree.
+    pub fn push_child(&mut self, parent: usize, value: T) -> usize {
+        let id = self.nodes.len();
+        assert!(
+            parent < id,
+            "parent {parent} is not a node of a tree of {id}"
+        );
+        self.nodes.push(LcrsNode {
+            value,
+            parent: Some(parent),
+            left_child: None,
+            right_sibling: None,
+            last_child: None,
+        });
+        match self.nodes[parent].last_child {
+            Some(last) => self.nodes[last].right_sibling = Some(id),
+            None => self.nodes[parent].left_child = Some(id),
+        }
+        self.nodes[parent].last_child = Some(id);
+        id
+    }
+
+    /// Children of `id` from left to right
+    pub fn children(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut next = self.nodes[id].left_child;
+        core::iter::from_fn(move || {
+            let child = next?;
+            next = self.nodes[child].right_sibling;
+            Some(child)
+        })
+    }
+
+    /// Number of edges between `id` and the root
+    pub fn depth(&self, mut id: usize) -> usize {
+        let mut depth = 0;
+        while let Some(parent) = self.nodes[id].parent {
+            id = parent;
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Number of nodes in the subtree rooted at `id`
+    pub fn subtree_size(&self, id: usize) -> usize {
+        self.preorder(id).count()
+    }
+
+    /// Subtree size of every node, computed in one postorder walk
+    pub fn subtree_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![1; self.len()];
+        for id in self.postorder(self.root()) {
+            if let Some(parent) = self.nodes[id].parent {
+                sizes[parent] += sizes[id];
+            }
+        }
+        sizes
+    }
+
+    /// Walk the subtree rooted at `id`, each node before its children
+    ///
+    /// ```no
+    /// NEXT(x)
+    ///     if x.left-child != NIL
+    ///         return x.left-child
+    ///     while x != root
+    ///         if x.right-sibling != NIL
+    ///             return x.right-sibling
+    ///         x = x.p
+    ///     return NIL
+    /// ```
+    pub fn preorder(&self, id: usize) -> LcrsPreorder<'_, T> {
+        LcrsPreorder {
+            tree: self,
+            root: id,
+            next: Some(id),
+        }
+    }
+
+    /// Walk the subtree rooted at `id`, each node after its children
+    pub fn postorder(&self, id: usize) -> LcrsPostorder<'_, T> {
+        LcrsPostorder {
+            tree: self,
+            root: id,
+            next: Some(self.leftmost_leaf(id)),
+        }
+    }
+
+    /// Walk the subtree rooted at `id` level by level
+    pub fn level_order(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut queue = VecDeque::from([id]);
+        core::iter::from_fn(move || {
+            let id = queue.pop_front()?;
+            queue.extend(self.children(id));
+            Some(id)
+        })
+    }
+
+    fn leftmost_leaf(&self, mut id: usize) -> usize {
+        while let Some(child) = self.nodes[id].left_child {
+            id = child;
+        }
+        id
+    }
+}
+
+/// Preorder walk of an `LcrsTree` in O(1) extra space
+#[derive(Debug)]
+pub struct LcrsPreorder<'a, T> {
+    tree: &'a LcrsTree<T>,
+    root: usize,
+    next: Option<usize>,
+}
+
+impl<T> Iterator for LcrsPreorder<'_, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let current = self.next?;
+        let nodes = &self.tree.nodes;
+        self.next = nodes[current].left_child;
+        let mut x = current;
+        while self.next.is_none() && x != self.root {
+            self.next = nodes[x].right_sibling;
+            x = nodes[x].parent.expect("only the root has no parent");
+        }
+        Some(current)
+    }
+}
+
+/// Postorder walk of an `LcrsTree` in O(1) extra space
+#[derive(Debug)]
+pub struct LcrsPostorder<'a, T> {
+    tree: &'a LcrsTree<T>,
+    root: usize,
+    next: Option<usize>,
+}
+
+impl<T> Iterator for LcrsPostorder<'_, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let current = self.next?;
+        let node = &self.tree.nodes[current];
+        self.next = if current == self.root {
+            None
+        } else if let Some(sibling) = node.right_sibling {
+            Some(self.tree.leftmost_leaf(sibling))
+        } else {
+            node.parent
+        };
+        Some(current)
+    }
+}
+
+/// Draws the underlying binary tree, the left child below a node and its
+/// right sibling as the right child
+impl<T: Display> BinaryTreeView for LcrsTree<T> {
+    type Handle<'a>
+        = usize
+    where
+        T: 'a;
+
+    fn root(&self) -> Option<usize> {
+        Some(self.root)
+    }
+
+    fn left(&self, node: usize) -> Option<usize> {
+        self.nodes[node].left_child
+    }
+
+    fn right(&self, node: usize) -> Option<usize> {
+        self.nodes[node].right_sibling
+    }
+
+    fn label(&self, node: usize) -> String {
+        self.nodes[node].value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_parents(rng: &mut StdRng, n: usize) -> Vec<Option<usize>> {
+        (0..n)
+            .map(|v| (v > 0).then(|| rng.gen_range(0..v)))
+            .collect()
+    }
+
+    fn recursive_preorder(tree: &RootedTree, v: usize, out: &mut Vec<usize>) {
+        out.push(v);
+        for &c in tree.children(v) {
+            recursive_preorder(tree, c, out);
+        }
+    }
+
+    fn recursive_postorder(tree: &RootedTree, v: usize, out: &mut Vec<usize>) {
+        for &c in tree.children(v) {
+            recursive_postorder(tree, c, out);
+        }
+        out.push(v);
+    }
+
+    #[test]
+    fn test_walks_against_rooted_tree() {
+        let mut rng = StdRng::seed_from_u64(37);
+        for n in 1..60 {
+            let parents = random_parents(&mut rng, n);
+            let shape = RootedTree::from_parents(&parents).unwrap();
+            let tree = LcrsTree::from_parents((0..n).collect(), &parents).unwrap();
+            for v in 0..n {
+                let mut expected = Vec::new();
+                recursive_preorder(&shape, v, &mut expected);
+                assert_eq!(tree.preorder(v).collect::<Vec<_>>(), expected);
+                assert_eq!(tree.subtree_size(v), expected.len());
+
+                let mut expected = Vec::new();
+                recursive_postorder(&shape, v, &mut expected);
+                assert_eq!(tree.postorder(v).collect::<Vec<_>>(), expected);
+
+                assert_eq!(tree.depth(v), shape.depth(v));
+                assert_eq!(tree.children(v).collect::<Vec<_>>(), shape.children(v));
+            }
+            let levels: Vec<usize> = tree.level_order(0).map(|v| shape.depth(v)).collect();
+            assert!(levels.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(levels.len(), n);
+        }
+    }
+
+    #[test]
+    fn test_root_other_than_zero() {
+        let tree = LcrsTree::from_parents(vec!['a', 'b', 'c'], &[Some(2), Some(2), None]).unwrap();
+        assert_eq!(tree.root(), 2);
+        assert_eq!(
+            tree.children(2).map(|id| tree[id]).collect::<String>(),
+            "ab"
+        );
+        assert_eq!(tree.depth(1), 1);
+        assert_eq!(tree.subtree_sizes(), vec![1, 1, 3]);
+        assert_eq!(tree.level_order(2).collect::<Vec<_>>(), vec![2, 0, 1]);
+        assert_eq!(
+            LcrsTree::from_parents(vec![1, 2], &[None, None]).unwrap_err(),
+            RootedTreeError::MultipleRoots(0, 1)
+        );
+        assert_eq!(
+            LcrsTree::from_parents(vec![1, 2], &[None]).unwrap_err(),
+            RootedTreeError::ValueCount {
+                nodes: 1,
+                values: 2
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "parent 1 is not a node")]
+    fn test_push_child_to_missing_parent() {
+        LcrsTree::new(0).push_child(1, 5);
+    }
+
+    #[test]
+    fn test_push_child_and_render() {
+        let mut tree = LcrsTree::new(1);
+        let a = tree.push_child(0, 2);
+        tree.push_child(0, 3);
+        tree.push_child(a, 4);
+        tree[a] = 20;
+        assert_eq!(tree.postorder(0).collect::<Vec<_>>(), vec![3, 1, 2, 0]);
+        assert_eq!(
+            tree.to_ascii(),
+            "1\n\
+             ├── 20\n\
+             │   ├── 4\n\
+             │   └── 3\n\
+             └── ·\n"
+        );
+    }
+}
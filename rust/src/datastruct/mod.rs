@@ -1,3 +1,41 @@
+mod order_statistic_tree;
+mod rb_arena;
+pub use order_statistic_tree::*;
+mod interval_tree;
+pub use interval_tree::*;
+mod b_tree;
+pub use b_tree::*;
+mod bplus_tree;
+pub use bplus_tree::*;
+mod treap;
+pub use treap::*;
+mod splay_tree;
+pub use splay_tree::*;
+mod skip_list;
+pub use skip_list::*;
+mod persistent_tree;
+pub use persistent_tree::*;
+mod render;
+pub use render::*;
+mod bst_codec;
+pub use bst_codec::*;
+mod rooted_tree;
+pub use rooted_tree::*;
+mod lcrs_tree;
+pub use lcrs_tree::*;
+mod trie;
+pub use trie::*;
+mod segment_tree;
+pub use segment_tree::*;
+mod fenwick_tree;
+pub use fenwick_tree::*;
+mod sparse_table;
+pub use sparse_table::*;
+
+use std::collections::HashMap;
+
+use render::{render_ascii, render_dot};
+
 #[derive(Debug, Default, PartialEq)]
 pub struct Node<T> {
     data: T,
@@ -117,6 +155,37 @@ where
         None
     }
 
+    /// Lowest common ancestor of the nodes holding `a` and `b`
+    ///
+    /// Walk down from the root while both keys are on the same side, the first
+    /// node that splits them, or holds one of them, is the ancestor.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::BinarySearchTree;
+    ///
+    /// let tree = BinarySearchTree::from_sorted(&[1, 2, 3, 4, 5, 6, 7]);
+    ///
+    /// assert_eq!(tree.lowest_common_ancestor(1, 3), Some(&2));
+    /// assert_eq!(tree.lowest_common_ancestor(3, 6), Some(&4));
+    /// assert_eq!(tree.lowest_common_ancestor(5, 8), None);
+    /// ```
+    pub fn lowest_common_ancestor(&self, a: T, b: T) -> Option<&T> {
+        if !self.contains(a.clone()) || !self.contains(b.clone()) {
+            return None;
+        }
+        let mut node = self.root.as_ref();
+        while let Some(n) = node {
+            if a < n.data && b < n.data {
+                node = n.left.as_ref();
+            } else if a > n.data && b > n.data {
+                node = n.right.as_ref();
+            } else {
+                return Some(&n.data);
+            }
+        }
+        None
+    }
+
     pub fn inorder(&self, node: Option<&Box<Node<T>>>, result: &mut Vec<T>) {
         if let Some(node) = node {
             self.inorder(node.left.as_ref(), result);
@@ -156,6 +225,77 @@ where
     }
 }
 
+impl<T: std::fmt::Display> BinaryTreeView for BinarySearchTree<T> {
+    type Handle<'a>
+        = &'a Node<T>
+    where
+        T: 'a;
+
+    fn root(&self) -> Option<&Node<T>> {
+        self.root.as_deref()
+    }
+
+    fn left<'a>(&'a self, node: &'a Node<T>) -> Option<&'a Node<T>> {
+        node.left.as_deref()
+    }
+
+    fn right<'a>(&'a self, node: &'a Node<T>) -> Option<&'a Node<T>> {
+        node.right.as_deref()
+    }
+
+    fn label(&self, node: &Node<T>) -> String {
+        node.data.to_string()
+    }
+
+    fn annotations(&self, node: &Node<T>) -> Vec<(&'static str, String)> {
+        let table = heights_and_sizes(Some(node));
+        bst_annotations(&table, node)
+    }
+
+    // the nodes keep no augmentation, so a render computes it for the whole
+    // tree first rather than walking the subtree of every node
+    fn to_ascii(&self) -> String {
+        let table = heights_and_sizes(self.root.as_deref());
+        render_ascii(self, &mut |node| bst_annotations(&table, node))
+    }
+
+    fn to_dot(&self) -> String {
+        let table = heights_and_sizes(self.root.as_deref());
+        render_dot(self, &mut |node| bst_annotations(&table, node))
+    }
+}
+
+type HeightsAndSizes<T> = HashMap<*const Node<T>, (usize, usize)>;
+
+// Height and size of every node under root, children before their parents
+// by walking a preorder backwards, without recursion
+fn heights_and_sizes<T>(root: Option<&Node<T>>) -> HeightsAndSizes<T> {
+    let mut preorder = Vec::new();
+    let mut stack: Vec<&Node<T>> = root.into_iter().collect();
+    while let Some(node) = stack.pop() {
+        preorder.push(node);
+        stack.extend(node.left.as_deref());
+        stack.extend(node.right.as_deref());
+    }
+    let mut table = HashMap::with_capacity(preorder.len());
+    for node in preorder.into_iter().rev() {
+        let child = |child: Option<&Node<T>>| child.map_or((0, 0), |c| table[&(c as *const _)]);
+        let (left_height, left_size) = child(node.left.as_deref());
+        let (right_height, right_size) = child(node.right.as_deref());
+        let entry = (
+            left_height.max(right_height) + 1,
+            left_size + right_size + 1,
+        );
+        table.insert(node as *const _, entry);
+    }
+    table
+}
+
+fn bst_annotations<T>(table: &HeightsAndSizes<T>, node: &Node<T>) -> Vec<(&'static str, String)> {
+    let (height, size) = table[&(node as *const _)];
+    vec![("height", height.to_string()), ("size", size.to_string())]
+}
+
 #[cfg(test)]
 mod test {
 
@@ -0,0 +1,402 @@
+//! Order-statistic tree (Introduction to Algorithms 14.1)
+//!
+//! A red-black tree in which every node additionally stores the size of the
+//! subtree rooted at it. The extra field makes it possible to find the i-th
+//! smallest key and the rank of a key in O(lg n).
+use super::rb_arena::{Augment, Color, RbArena, RbNode, NIL};
+use super::BinaryTreeView;
+use core::cmp::Ordering;
+use core::fmt::Display;
+
+#[derive(Debug)]
+struct OsData<T> {
+    // None only for the sentinel and for slots on the free list
+    key: Option<T>,
+    // x.size = x.left.size + x.right.size + 1
+    size: usize,
+}
+
+impl<T> Augment for OsData<T> {
+    fn recompute(nodes: &mut [RbNode<Self>], x: usize) {
+        let (l, r) = (nodes[x].left, nodes[x].right);
+        nodes[x].data.size = nodes[l].data.size + nodes[r].data.size + 1;
+    }
+}
+
+/// Order-statistic tree
+///
+/// Duplicate keys are allowed, so the tree can be used as a sorted multiset
+/// over a sliding window of samples.
+#[derive(Debug)]
+pub struct OrderStatisticTree<T> {
+    rb: RbArena<OsData<T>>,
+}
+
+impl<T: Ord> Default for OrderStatisticTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for OrderStatisticTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for key in iter {
+            tree.insert(key);
+        }
+        tree
+    }
+}
+
+impl<T: Ord> OrderStatisticTree<T> {
+    /// Creating an empty order-statistic tree
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::OrderStatisticTree;
+    ///
+    /// let tree = OrderStatisticTree::<i32>::new();
+    ///
+    /// assert_eq!(tree.is_empty(), true);
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            rb: RbArena::new(OsData { key: None, size: 0 }),
+        }
+    }
+
+    /// Number of keys in the tree
+    pub fn len(&self) -> usize {
+        self.size(self.rb.root)
+    }
+
+    /// Determine if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.rb.root == NIL
+    }
+
+    fn key(&self, x: usize) -> &T {
+        self.rb.nodes[x]
+            .data
+            .key
+            .as_ref()
+            .expect("sentinel has no key")
+    }
+
+    fn size(&self, x: usize) -> usize {
+        self.rb.nodes[x].data.size
+    }
+
+    fn left(&self, x: usize) -> usize {
+        self.rb.nodes[x].left
+    }
+
+    fn right(&self, x: usize) -> usize {
+        self.rb.nodes[x].right
+    }
+
+    /// Insert a key, duplicates are kept
+    ///
+    /// ```no
+    /// OS-INSERT(T, z)
+    ///     y = T.nil
+    ///     x = T.root
+    ///     while x != T.nil
+    ///         y = x
+    ///         x.size = x.size + 1
+    ///         if z.key < x.key
+    ///             x = x.left
+    ///         else x = x.right
+    ///     ...
+    ///     RB-INSERT-FIXUP(T, z)
+    /// ```
+    pub fn insert(&mut self, key: T) {
+        let mut y = NIL;
+        let mut x = self.rb.root;
+        while x != NIL {
+            y = x;
+            self.rb.nodes[x].data.size += 1;
+            if key < *self.key(x) {
+                x = self.left(x);
+            } else {
+                x = self.right(x);
+            }
+        }
+
+        let go_left = y != NIL && key < *self.key(y);
+        let data = OsData {
+            key: Some(key),
+            size: 1,
+        };
+        self.rb.insert(data, y, go_left);
+    }
+
+    /// Remove one occurrence of `key`, returning it if it was present
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::OrderStatisticTree;
+    ///
+    /// let mut tree: OrderStatisticTree<i32> = vec![3, 1, 3].into_iter().collect();
+    ///
+    /// assert_eq!(tree.remove(&3), Some(3));
+    /// assert_eq!(tree.remove(&4), None);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn remove(&mut self, key: &T) -> Option<T> {
+        let z = self.search(key);
+        if z == NIL {
+            return None;
+        }
+        self.rb.delete(z).key.take()
+    }
+
+    /// Determine if the tree contains `key`
+    pub fn contains(&self, key: &T) -> bool {
+        self.search(key) != NIL
+    }
+
+    /// Smallest key in the tree
+    pub fn minimum(&self) -> Option<&T> {
+        self.os_select(1)
+    }
+
+    /// Largest key in the tree
+    pub fn maximum(&self) -> Option<&T> {
+        self.os_select(self.len())
+    }
+
+    /// Find the key with the i-th smallest rank, `i` starts from 1
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::OrderStatisticTree;
+    ///
+    /// let tree: OrderStatisticTree<i32> = vec![26, 17, 41, 14, 21].into_iter().collect();
+    ///
+    /// assert_eq!(tree.os_select(1), Some(&14));
+    /// assert_eq!(tree.os_select(4), Some(&26));
+    /// assert_eq!(tree.os_select(6), None);
+    /// ```
+    /// ```no
+    /// OS-SELECT(x, i)
+    ///     r = x.left.size + 1
+    ///     if i == r
+    ///         return x
+    ///     elseif i < r
+    ///         return OS-SELECT(x.left, i)
+    ///     else return OS-SELECT(x.right, i - r)
+    /// ```
+    pub fn os_select(&self, i: usize) -> Option<&T> {
+        if i == 0 || i > self.len() {
+            return None;
+        }
+        let mut x = self.rb.root;
+        let mut i = i;
+        loop {
+            let r = self.size(self.left(x)) + 1;
+            match i.cmp(&r) {
+                Ordering::Equal => return Some(self.key(x)),
+                Ordering::Less => x = self.left(x),
+                Ordering::Greater => {
+                    x = self.right(x);
+                    i -= r;
+                }
+            }
+        }
+    }
+
+    /// Position of `key` in the sorted order of the tree, starting from 1
+    ///
+    /// If `key` occurs more than once the rank of its first occurrence is returned.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::OrderStatisticTree;
+    ///
+    /// let tree: OrderStatisticTree<i32> = vec![26, 17, 41, 14, 21].into_iter().collect();
+    ///
+    /// assert_eq!(tree.os_rank(&14), Some(1));
+    /// assert_eq!(tree.os_rank(&26), Some(4));
+    /// assert_eq!(tree.os_rank(&30), None);
+    /// ```
+    pub fn os_rank(&self, key: &T) -> Option<usize> {
+        if self.contains(key) {
+            Some(self.count_less_than(key) + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Number of keys strictly less than `key`
+    pub fn count_less_than(&self, key: &T) -> usize {
+        let mut count = 0;
+        let mut x = self.rb.root;
+        while x != NIL {
+            if *key <= *self.key(x) {
+                x = self.left(x);
+            } else {
+                count += self.size(self.left(x)) + 1;
+                x = self.right(x);
+            }
+        }
+        count
+    }
+
+    fn count_less_equal(&self, key: &T) -> usize {
+        let mut count = 0;
+        let mut x = self.rb.root;
+        while x != NIL {
+            if *key < *self.key(x) {
+                x = self.left(x);
+            } else {
+                count += self.size(self.left(x)) + 1;
+                x = self.right(x);
+            }
+        }
+        count
+    }
+
+    /// Number of keys `k` with `low <= k <= high`
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::OrderStatisticTree;
+    ///
+    /// let tree: OrderStatisticTree<i32> = (1..=10).collect();
+    ///
+    /// assert_eq!(tree.count_in_range(&3, &7), 5);
+    /// assert_eq!(tree.count_in_range(&7, &3), 0);
+    /// ```
+    pub fn count_in_range(&self, low: &T, high: &T) -> usize {
+        if low > high {
+            return 0;
+        }
+        self.count_less_equal(high) - self.count_less_than(low)
+    }
+
+    /// In-order walk of the tree
+    pub fn inorder_tree_walk(&self) -> Vec<&T> {
+        self.rb.inorder().into_iter().map(|x| self.key(x)).collect()
+    }
+
+    fn search(&self, key: &T) -> usize {
+        self.rb
+            .find(|data| key.cmp(data.key.as_ref().expect("sentinel has no key")))
+    }
+}
+
+impl<T: Ord + Display> BinaryTreeView for OrderStatisticTree<T> {
+    type Handle<'a>
+        = usize
+    where
+        T: 'a;
+
+    fn root(&self) -> Option<usize> {
+        (self.rb.root != NIL).then_some(self.rb.root)
+    }
+
+    fn left(&self, node: usize) -> Option<usize> {
+        let left = self.rb.nodes[node].left;
+        (left != NIL).then_some(left)
+    }
+
+    fn right(&self, node: usize) -> Option<usize> {
+        let right = self.rb.nodes[node].right;
+        (right != NIL).then_some(right)
+    }
+
+    fn label(&self, node: usize) -> String {
+        self.key(node).to_string()
+    }
+
+    fn annotations(&self, node: usize) -> Vec<(&'static str, String)> {
+        let color = match self.rb.nodes[node].color {
+            Color::Red => "red",
+            Color::Black => "black",
+        };
+        vec![
+            ("color", color.to_string()),
+            ("size", self.size(node).to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // checks sizes and the red-black properties
+    fn check<T: Ord>(tree: &OrderStatisticTree<T>) {
+        tree.rb.black_height(tree.rb.root);
+        for x in tree.rb.inorder() {
+            let (l, r) = (tree.left(x), tree.right(x));
+            assert_eq!(tree.size(x), tree.size(l) + tree.size(r) + 1);
+        }
+    }
+
+    #[test]
+    fn test_os_select_and_rank() {
+        let tree: OrderStatisticTree<i32> = vec![
+            26, 17, 41, 14, 21, 30, 47, 10, 16, 19, 23, 28, 38, 7, 12, 15, 20, 35, 39, 3,
+        ]
+        .into_iter()
+        .collect();
+        check(&tree);
+        assert_eq!(tree.len(), 20);
+        assert_eq!(tree.os_select(17), Some(&38));
+        assert_eq!(tree.os_rank(&38), Some(17));
+        assert_eq!(tree.minimum(), Some(&3));
+        assert_eq!(tree.maximum(), Some(&47));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut tree: OrderStatisticTree<i32> = vec![5, 1, 5, 5, 9].into_iter().collect();
+        assert_eq!(tree.os_rank(&5), Some(2));
+        assert_eq!(tree.count_less_than(&5), 1);
+        assert_eq!(tree.count_in_range(&5, &5), 3);
+        assert_eq!(tree.remove(&5), Some(5));
+        assert_eq!(tree.count_in_range(&5, &5), 2);
+        assert_eq!(tree.inorder_tree_walk(), vec![&1, &5, &5, &9]);
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let mut tree = OrderStatisticTree::<i32>::new();
+        assert_eq!(tree.os_select(1), None);
+        assert_eq!(tree.os_rank(&1), None);
+        assert_eq!(tree.count_less_than(&1), 0);
+        assert_eq!(tree.remove(&1), None);
+    }
+
+    #[test]
+    fn test_random_against_sorted_vec() {
+        let mut rng = StdRng::seed_from_u64(14);
+        let mut tree = OrderStatisticTree::new();
+        let mut expected: Vec<i32> = Vec::new();
+        for _ in 0..2000 {
+            let key = rng.gen_range(0..200);
+            if rng.gen_bool(0.6) {
+                tree.insert(key);
+                let pos = expected.partition_point(|v| *v < key);
+                expected.insert(pos, key);
+            } else {
+                let removed = tree.remove(&key);
+                match expected.binary_search(&key) {
+                    Ok(pos) => {
+                        expected.remove(pos);
+                        assert_eq!(removed, Some(key));
+                    }
+                    Err(_) => assert_eq!(removed, None),
+                }
+            }
+            check(&tree);
+            assert_eq!(tree.len(), expected.len());
+        }
+        for (i, key) in expected.iter().enumerate() {
+            assert_eq!(tree.os_select(i + 1), Some(key));
+        }
+        assert_eq!(
+            tree.count_in_range(&50, &150),
+            expected.iter().filter(|v| (50..=150).contains(*v)).count()
+        );
+    }
+}
@@ -0,0 +1,473 @@
+//! Persistent balanced binary search tree (Introduction to Algorithms problem 13-1)
+//!
+//! Updating a persistent tree never changes an existing node. Instead the
+//! nodes on the path from the root to the change are copied and the copies
+//! point to the untouched subtrees of the old version, so an update costs
+//! O(lg n) time and space and every earlier version stays valid. Nodes are
+//! shared through `Arc`, so versions can be handed to other threads, and the
+//! tree is kept AVL balanced.
+use super::BinaryTreeView;
+use core::cmp::Ordering;
+use core::fmt::Display;
+use std::sync::Arc;
+
+type Link<T> = Option<Arc<PersistentNode<T>>>;
+
+/// Node of a persistent tree, only visible as a [`BinaryTreeView`] handle
+#[derive(Debug)]
+pub struct PersistentNode<T> {
+    value: T,
+    height: usize,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+fn height<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn make_node<T>(value: T, left: Link<T>, right: Link<T>) -> Arc<PersistentNode<T>> {
+    Arc::new(PersistentNode {
+        height: 1 + height(&left).max(height(&right)),
+        size: 1 + size(&left) + size(&right),
+        value,
+        left,
+        right,
+    })
+}
+
+// builds a node whose subtrees differ in height by at most 2, rotating to restore the AVL property
+fn balance<T: Clone>(value: T, left: Link<T>, right: Link<T>) -> Arc<PersistentNode<T>> {
+    let (hl, hr) = (height(&left), height(&right));
+    if hl > hr + 1 {
+        let l = left.expect("taller subtree is not empty");
+        if height(&l.left) >= height(&l.right) {
+            // single right rotation
+            make_node(
+                l.value.clone(),
+                l.left.clone(),
+                Some(make_node(value, l.right.clone(), right)),
+            )
+        } else {
+            // left-right double rotation
+            let lr = l.right.as_ref().expect("taller subtree is not empty");
+            make_node(
+                lr.value.clone(),
+                Some(make_node(l.value.clone(), l.left.clone(), lr.left.clone())),
+                Some(make_node(value, lr.right.clone(), right)),
+            )
+        }
+    } else if hr > hl + 1 {
+        let r = right.expect("taller subtree is not empty");
+        if height(&r.right) >= height(&r.left) {
+            // single left rotation
+            make_node(
+                r.value.clone(),
+                Some(make_node(value, left, r.left.clone())),
+                r.right.clone(),
+            )
+        } else {
+            // right-left double rotation
+            let rl = r.left.as_ref().expect("taller subtree is not empty");
+            make_node(
+                rl.value.clone(),
+                Some(make_node(value, left, rl.left.clone())),
+                Some(make_node(
+                    r.value.clone(),
+                    rl.right.clone(),
+                    r.right.clone(),
+                )),
+            )
+        }
+    } else {
+        make_node(value, left, right)
+    }
+}
+
+// None if the value is already present, the old version is then reused as is
+fn insert<T: Ord + Clone>(link: &Link<T>, value: T) -> Option<Arc<PersistentNode<T>>> {
+    match link {
+        None => Some(make_node(value, None, None)),
+        Some(node) => match value.cmp(&node.value) {
+            Ordering::Equal => None,
+            Ordering::Less => {
+                let left = insert(&node.left, value)?;
+                Some(balance(node.value.clone(), Some(left), node.right.clone()))
+            }
+            Ordering::Greater => {
+                let right = insert(&node.right, value)?;
+                Some(balance(node.value.clone(), node.left.clone(), Some(right)))
+            }
+        },
+    }
+}
+
+// None if the value is absent, otherwise the new subtree
+fn remove<T: Ord + Clone>(link: &Link<T>, value: &T) -> Option<Link<T>> {
+    let node = link.as_ref()?;
+    match value.cmp(&node.value) {
+        Ordering::Less => {
+            let left = remove(&node.left, value)?;
+            Some(Some(balance(node.value.clone(), left, node.right.clone())))
+        }
+        Ordering::Greater => {
+            let right = remove(&node.right, value)?;
+            Some(Some(balance(node.value.clone(), node.left.clone(), right)))
+        }
+        Ordering::Equal => match (&node.left, &node.right) {
+            (None, right) => Some(right.clone()),
+            (left, None) => Some(left.clone()),
+            (left, Some(right)) => {
+                // replace the value by its successor
+                let (right, successor) = remove_min(right);
+                Some(Some(balance(successor, left.clone(), right)))
+            }
+        },
+    }
+}
+
+fn remove_min<T: Clone>(node: &Arc<PersistentNode<T>>) -> (Link<T>, T) {
+    match &node.left {
+        None => (node.right.clone(), node.value.clone()),
+        Some(left) => {
+            let (left, min) = remove_min(left);
+            (
+                Some(balance(node.value.clone(), left, node.right.clone())),
+                min,
+            )
+        }
+    }
+}
+
+/// Persistent ordered set
+///
+/// Cloning a tree is O(1) and gives an independent snapshot.
+///
+/// ```rust
+/// use algorithms_rs::datastruct::PersistentTree;
+///
+/// let v1: PersistentTree<i32> = (1..=5).collect();
+/// let v2 = v1.insert(6);
+/// let v3 = v2.remove(&1);
+///
+/// assert_eq!(v1.inorder_tree_walk(), vec![&1, &2, &3, &4, &5]);
+/// assert_eq!(v2.len(), 6);
+/// assert_eq!(v3.contains(&1), false);
+/// ```
+#[derive(Debug)]
+pub struct PersistentTree<T> {
+    root: Link<T>,
+}
+
+impl<T> Clone for PersistentTree<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T> Default for PersistentTree<T> {
+    fn default() -> Self {
+        Self { root: None }
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for PersistentTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for value in iter {
+            tree = tree.insert(value);
+        }
+        tree
+    }
+}
+
+impl<T: Ord + Clone> PersistentTree<T> {
+    /// Creating an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of values in the tree
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Determine if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Height of the tree, an empty tree has height 0
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Determine if the tree contains `value`
+    pub fn contains(&self, value: &T) -> bool {
+        let mut node = self.root.as_ref();
+        while let Some(n) = node {
+            match value.cmp(&n.value) {
+                Ordering::Equal => return true,
+                Ordering::Less => node = n.left.as_ref(),
+                Ordering::Greater => node = n.right.as_ref(),
+            }
+        }
+        false
+    }
+
+    /// New version with `value` added, sharing every untouched node with `self`
+    pub fn insert(&self, value: T) -> Self {
+        match insert(&self.root, value) {
+            Some(root) => Self { root: Some(root) },
+            None => self.clone(),
+        }
+    }
+
+    /// New version without `value`, sharing every untouched node with `self`
+    pub fn remove(&self, value: &T) -> Self {
+        match remove(&self.root, value) {
+            Some(root) => Self { root },
+            None => self.clone(),
+        }
+    }
+
+    /// Smallest value in the tree
+    pub fn minimum(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = node.left.as_ref() {
+            node = left;
+        }
+        Some(&node.value)
+    }
+
+    /// Largest value in the tree
+    pub fn maximum(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = node.right.as_ref() {
+            node = right;
+        }
+        Some(&node.value)
+    }
+
+    /// Value with the i-th smallest rank, `i` starts from 1
+    pub fn select(&self, i: usize) -> Option<&T> {
+        let mut node = self.root.as_ref();
+        let mut i = i;
+        while let Some(n) = node {
+            let r = size(&n.left) + 1;
+            match i.cmp(&r) {
+                Ordering::Equal => return Some(&n.value),
+                Ordering::Less => node = n.left.as_ref(),
+                Ordering::Greater => {
+                    i -= r;
+                    node = n.right.as_ref();
+                }
+            }
+        }
+        None
+    }
+
+    /// In-order walk of the tree
+    pub fn inorder_tree_walk(&self) -> Vec<&T> {
+        let mut result = Vec::with_capacity(self.len());
+        let mut stack = Vec::new();
+        let mut node = self.root.as_deref();
+        while node.is_some() || !stack.is_empty() {
+            while let Some(n) = node {
+                stack.push(n);
+                node = n.left.as_deref();
+            }
+            if let Some(n) = stack.pop() {
+                result.push(&n.value);
+                node = n.right.as_deref();
+            }
+        }
+        result
+    }
+}
+
+/// Persistent ordered set that keeps every version it went through
+///
+/// ```rust
+/// use algorithms_rs::datastruct::VersionedTree;
+///
+/// let mut history = VersionedTree::new();
+///
+/// history.insert("alice");
+/// history.insert("bob");
+/// history.remove(&"alice");
+///
+/// assert_eq!(history.version(2).unwrap().contains(&"alice"), true);
+/// assert_eq!(history.current().contains(&"alice"), false);
+/// assert_eq!(history.versions(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VersionedTree<T> {
+    // history[0] is the empty tree
+    history: Vec<PersistentTree<T>>,
+}
+
+impl<T: Ord + Clone> Default for VersionedTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> VersionedTree<T> {
+    /// Creating a history whose only version is the empty tree
+    pub fn new() -> Self {
+        Self {
+            history: vec![PersistentTree::new()],
+        }
+    }
+
+    /// Number of versions, including the initial empty one
+    pub fn versions(&self) -> usize {
+        self.history.len()
+    }
+
+    /// The latest version
+    pub fn current(&self) -> &PersistentTree<T> {
+        self.history.last().expect("history is never empty")
+    }
+
+    /// Version number `version`, 0 is the initial empty tree
+    pub fn version(&self, version: usize) -> Option<&PersistentTree<T>> {
+        self.history.get(version)
+    }
+
+    /// Record a new version with `value` added, returns the new version number
+    pub fn insert(&mut self, value: T) -> usize {
+        let next = self.current().insert(value);
+        self.history.push(next);
+        self.history.len() - 1
+    }
+
+    /// Record a new version without `value`, returns the new version number
+    pub fn remove(&mut self, value: &T) -> usize {
+        let next = self.current().remove(value);
+        self.history.push(next);
+        self.history.len() - 1
+    }
+}
+
+impl<T: Display> BinaryTreeView for PersistentTree<T> {
+    type Handle<'a>
+        = &'a PersistentNode<T>
+    where
+        T: 'a;
+
+    fn root(&self) -> Option<&PersistentNode<T>> {
+        self.root.as_deref()
+    }
+
+    fn left<'a>(&'a self, node: &'a PersistentNode<T>) -> Option<&'a PersistentNode<T>> {
+        node.left.as_deref()
+    }
+
+    fn right<'a>(&'a self, node: &'a PersistentNode<T>) -> Option<&'a PersistentNode<T>> {
+        node.right.as_deref()
+    }
+
+    fn label(&self, node: &PersistentNode<T>) -> String {
+        node.value.to_string()
+    }
+
+    fn annotations(&self, node: &PersistentNode<T>) -> Vec<(&'static str, String)> {
+        vec![
+            ("height", node.height.to_string()),
+            ("size", node.size.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    fn check<T: Ord>(link: &Link<T>) {
+        if let Some(node) = link {
+            assert!(height(&node.left).abs_diff(height(&node.right)) <= 1);
+            assert_eq!(node.height, 1 + height(&node.left).max(height(&node.right)));
+            assert_eq!(node.size, 1 + size(&node.left) + size(&node.right));
+            check(&node.left);
+            check(&node.right);
+        }
+    }
+
+    #[test]
+    fn test_every_version_stays_valid() {
+        let mut rng = StdRng::seed_from_u64(33);
+        let mut versions = vec![PersistentTree::new()];
+        let mut expected = vec![BTreeSet::new()];
+        for _ in 0..1000 {
+            let value = rng.gen_range(0..200);
+            let (tree, set) = (versions.last().unwrap(), expected.last().unwrap());
+            let (mut next, mut next_set) = (tree.clone(), set.clone());
+            if rng.gen_bool(0.6) {
+                next = next.insert(value);
+                next_set.insert(value);
+            } else {
+                next = next.remove(&value);
+                next_set.remove(&value);
+            }
+            check(&next.root);
+            versions.push(next);
+            expected.push(next_set);
+        }
+        for (tree, set) in versions.iter().zip(&expected) {
+            assert_eq!(tree.inorder_tree_walk(), set.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_untouched_subtrees_are_shared() {
+        let v1: PersistentTree<i32> = (0..1000).collect();
+        let v2 = v1.insert(1000);
+        // inserting at the far right copies only the right spine
+        let (left1, left2) = (
+            v1.root.as_ref().unwrap().left.as_ref().unwrap(),
+            v2.root.as_ref().unwrap().left.as_ref().unwrap(),
+        );
+        assert!(Arc::ptr_eq(left1, left2));
+        // inserting a present value reuses the whole version
+        let v3 = v2.insert(5);
+        assert!(Arc::ptr_eq(
+            v2.root.as_ref().unwrap(),
+            v3.root.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_select_and_bounds() {
+        let tree: PersistentTree<i32> = vec![5, 1, 9, 3, 7].into_iter().collect();
+        assert_eq!(tree.select(1), Some(&1));
+        assert_eq!(tree.select(4), Some(&7));
+        assert_eq!(tree.select(6), None);
+        assert_eq!(tree.minimum(), Some(&1));
+        assert_eq!(tree.maximum(), Some(&9));
+        assert!(tree.height() <= 3);
+    }
+
+    #[test]
+    fn test_versions_can_be_sent_to_threads() {
+        let mut history = VersionedTree::new();
+        for value in 0..100 {
+            history.insert(value);
+        }
+        let snapshot = history.version(50).unwrap().clone();
+        let len = std::thread::spawn(move || snapshot.len()).join().unwrap();
+        assert_eq!(len, 50);
+        assert_eq!(history.current().len(), 100);
+    }
+}
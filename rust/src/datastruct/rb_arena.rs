@@ -0,0 +1,374 @@
+//! Red-black tree core shared by the augmented trees (Introduction to Algorithms 13, 14.2)
+//!
+//! Nodes live in a `Vec` and refer to each other by index, slot 0 being the
+//! sentinel `T.nil`. The trees built on top own the keys and the descent on
+//! insert, this module links nodes in and out and keeps the tree balanced.
+//! Each tree stores its augmentation in the node payload and says how to
+//! recompute it through `Augment`, which is called for every node whose
+//! subtree changes, by rotations and on the path up from a deletion.
+use core::cmp::Ordering;
+
+/// index of the sentinel `T.nil`
+pub(super) const NIL: usize = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Color {
+    Red,
+    Black,
+}
+
+/// Node payload with a field computed from the node and its children
+pub(super) trait Augment: Sized {
+    /// Recompute the field of `nodes[x]`, its children being up to date
+    fn recompute(nodes: &mut [RbNode<Self>], x: usize);
+}
+
+#[derive(Debug)]
+pub(super) struct RbNode<D> {
+    pub(super) data: D,
+    pub(super) color: Color,
+    pub(super) parent: usize,
+    pub(super) left: usize,
+    pub(super) right: usize,
+}
+
+#[derive(Debug)]
+pub(super) struct RbArena<D> {
+    // nodes[NIL] is the sentinel, every other slot is a tree node or free
+    pub(super) nodes: Vec<RbNode<D>>,
+    pub(super) root: usize,
+    free: Vec<usize>,
+}
+
+impl<D: Augment> RbArena<D> {
+    /// Creating an empty tree whose sentinel holds `nil`
+    pub(super) fn new(nil: D) -> Self {
+        let nil = RbNode {
+            data: nil,
+            color: Color::Black,
+            parent: NIL,
+            left: NIL,
+            right: NIL,
+        };
+        Self {
+            nodes: vec![nil],
+            root: NIL,
+            free: Vec::new(),
+        }
+    }
+
+    /// Link a new red node below `parent`, found by the caller's descent, and
+    /// restore the red-black properties
+    pub(super) fn insert(&mut self, data: D, parent: usize, go_left: bool) -> usize {
+        let node = RbNode {
+            data,
+            color: Color::Red,
+            parent,
+            left: NIL,
+            right: NIL,
+        };
+        let z = if let Some(z) = self.free.pop() {
+            self.nodes[z] = node;
+            z
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        };
+        if parent == NIL {
+            self.root = z;
+        } else if go_left {
+            self.nodes[parent].left = z;
+        } else {
+            self.nodes[parent].right = z;
+        }
+        self.insert_fixup(z);
+        z
+    }
+
+    /// Unlink `z` and put its slot on the free list, returning its payload
+    /// for the caller to take the key out of
+    ///
+    /// ```no
+    /// RB-DELETE(T, z)
+    ///     y = z
+    ///     y-original-color = y.color
+    ///     if z.left == T.nil
+    ///         x = z.right
+    ///         RB-TRANSPLANT(T, z, z.right)
+    ///     elseif z.right == T.nil
+    ///         x = z.left
+    ///         RB-TRANSPLANT(T, z, z.left)
+    ///     else y = TREE-MINIMUM(z.right)
+    ///         ...
+    ///     if y-original-color == BLACK
+    ///         RB-DELETE-FIXUP(T, x)
+    /// ```
+    pub(super) fn delete(&mut self, z: usize) -> &mut D {
+        let mut y = z;
+        let mut y_original_color = self.nodes[y].color;
+        let x;
+        if self.nodes[z].left == NIL {
+            x = self.nodes[z].right;
+            self.transplant(z, x);
+        } else if self.nodes[z].right == NIL {
+            x = self.nodes[z].left;
+            self.transplant(z, x);
+        } else {
+            y = self.minimum(self.nodes[z].right);
+            y_original_color = self.nodes[y].color;
+            x = self.nodes[y].right;
+            if self.nodes[y].parent == z {
+                self.nodes[x].parent = y;
+            } else {
+                self.transplant(y, x);
+                self.nodes[y].right = self.nodes[z].right;
+                let yr = self.nodes[y].right;
+                self.nodes[yr].parent = y;
+            }
+            self.transplant(z, y);
+            self.nodes[y].left = self.nodes[z].left;
+            let yl = self.nodes[y].left;
+            self.nodes[yl].parent = y;
+            self.nodes[y].color = self.nodes[z].color;
+        }
+
+        // the subtrees changed only on the path from x's new parent to the root
+        let mut p = self.nodes[x].parent;
+        while p != NIL {
+            D::recompute(&mut self.nodes, p);
+            p = self.nodes[p].parent;
+        }
+
+        if y_original_color == Color::Black {
+            self.delete_fixup(x);
+        }
+        self.free.push(z);
+        &mut self.nodes[z].data
+    }
+
+    /// Node for which `compare` is Equal, NIL if there is none; `compare`
+    /// says whether the sought key is less or greater than the node's
+    pub(super) fn find<F>(&self, mut compare: F) -> usize
+    where
+        F: FnMut(&D) -> Ordering,
+    {
+        let mut x = self.root;
+        while x != NIL {
+            match compare(&self.nodes[x].data) {
+                Ordering::Equal => return x,
+                Ordering::Less => x = self.nodes[x].left,
+                Ordering::Greater => x = self.nodes[x].right,
+            }
+        }
+        NIL
+    }
+
+    /// Nodes in sorted order
+    pub(super) fn inorder(&self) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut stack = Vec::new();
+        let mut x = self.root;
+        while x != NIL || !stack.is_empty() {
+            while x != NIL {
+                stack.push(x);
+                x = self.nodes[x].left;
+            }
+            if let Some(top) = stack.pop() {
+                result.push(top);
+                x = self.nodes[top].right;
+            }
+        }
+        result
+    }
+
+    fn minimum(&self, mut x: usize) -> usize {
+        while self.nodes[x].left != NIL {
+            x = self.nodes[x].left;
+        }
+        x
+    }
+
+    fn left_rotate(&mut self, x: usize) {
+        let y = self.nodes[x].right;
+        self.nodes[x].right = self.nodes[y].left;
+        if self.nodes[y].left != NIL {
+            let yl = self.nodes[y].left;
+            self.nodes[yl].parent = x;
+        }
+        self.nodes[y].parent = self.nodes[x].parent;
+        let xp = self.nodes[x].parent;
+        if xp == NIL {
+            self.root = y;
+        } else if x == self.nodes[xp].left {
+            self.nodes[xp].left = y;
+        } else {
+            self.nodes[xp].right = y;
+        }
+        self.nodes[y].left = x;
+        self.nodes[x].parent = y;
+        D::recompute(&mut self.nodes, x);
+        D::recompute(&mut self.nodes, y);
+    }
+
+    fn right_rotate(&mut self, x: usize) {
+        let y = self.nodes[x].left;
+        self.nodes[x].left = self.nodes[y].right;
+        if self.nodes[y].right != NIL {
+            let yr = self.nodes[y].right;
+            self.nodes[yr].parent = x;
+        }
+        self.nodes[y].parent = self.nodes[x].parent;
+        let xp = self.nodes[x].parent;
+        if xp == NIL {
+            self.root = y;
+        } else if x == self.nodes[xp].right {
+            self.nodes[xp].right = y;
+        } else {
+            self.nodes[xp].left = y;
+        }
+        self.nodes[y].right = x;
+        self.nodes[x].parent = y;
+        D::recompute(&mut self.nodes, x);
+        D::recompute(&mut self.nodes, y);
+    }
+
+    fn insert_fixup(&mut self, mut z: usize) {
+        while self.nodes[self.nodes[z].parent].color == Color::Red {
+            let zp = self.nodes[z].parent;
+            let zpp = self.nodes[zp].parent;
+            if zp == self.nodes[zpp].left {
+                let y = self.nodes[zpp].right;
+                if self.nodes[y].color == Color::Red {
+                    self.nodes[zp].color = Color::Black;
+                    self.nodes[y].color = Color::Black;
+                    self.nodes[zpp].color = Color::Red;
+                    z = zpp;
+                } else {
+                    if z == self.nodes[zp].right {
+                        z = zp;
+                        self.left_rotate(z);
+                    }
+                    let zp = self.nodes[z].parent;
+                    let zpp = self.nodes[zp].parent;
+                    self.nodes[zp].color = Color::Black;
+                    self.nodes[zpp].color = Color::Red;
+                    self.right_rotate(zpp);
+                }
+            } else {
+                let y = self.nodes[zpp].left;
+                if self.nodes[y].color == Color::Red {
+                    self.nodes[zp].color = Color::Black;
+                    self.nodes[y].color = Color::Black;
+                    self.nodes[zpp].color = Color::Red;
+                    z = zpp;
+                } else {
+                    if z == self.nodes[zp].left {
+                        z = zp;
+                        self.right_rotate(z);
+                    }
+                    let zp = self.nodes[z].parent;
+                    let zpp = self.nodes[zp].parent;
+                    self.nodes[zp].color = Color::Black;
+                    self.nodes[zpp].color = Color::Red;
+                    self.left_rotate(zpp);
+                }
+            }
+        }
+        let root = self.root;
+        self.nodes[root].color = Color::Black;
+    }
+
+    fn transplant(&mut self, u: usize, v: usize) {
+        let up = self.nodes[u].parent;
+        if up == NIL {
+            self.root = v;
+        } else if u == self.nodes[up].left {
+            self.nodes[up].left = v;
+        } else {
+            self.nodes[up].right = v;
+        }
+        self.nodes[v].parent = up;
+    }
+
+    fn delete_fixup(&mut self, mut x: usize) {
+        while x != self.root && self.nodes[x].color == Color::Black {
+            let xp = self.nodes[x].parent;
+            if x == self.nodes[xp].left {
+                let mut w = self.nodes[xp].right;
+                if self.nodes[w].color == Color::Red {
+                    self.nodes[w].color = Color::Black;
+                    self.nodes[xp].color = Color::Red;
+                    self.left_rotate(xp);
+                    w = self.nodes[xp].right;
+                }
+                if self.nodes[self.nodes[w].left].color == Color::Black
+                    && self.nodes[self.nodes[w].right].color == Color::Black
+                {
+                    self.nodes[w].color = Color::Red;
+                    x = xp;
+                } else {
+                    if self.nodes[self.nodes[w].right].color == Color::Black {
+                        let wl = self.nodes[w].left;
+                        self.nodes[wl].color = Color::Black;
+                        self.nodes[w].color = Color::Red;
+                        self.right_rotate(w);
+                        w = self.nodes[xp].right;
+                    }
+                    self.nodes[w].color = self.nodes[xp].color;
+                    self.nodes[xp].color = Color::Black;
+                    let wr = self.nodes[w].right;
+                    self.nodes[wr].color = Color::Black;
+                    self.left_rotate(xp);
+                    x = self.root;
+                }
+            } else {
+                let mut w = self.nodes[xp].left;
+                if self.nodes[w].color == Color::Red {
+                    self.nodes[w].color = Color::Black;
+                    self.nodes[xp].color = Color::Red;
+                    self.right_rotate(xp);
+                    w = self.nodes[xp].left;
+                }
+                if self.nodes[self.nodes[w].right].color == Color::Black
+                    && self.nodes[self.nodes[w].left].color == Color::Black
+                {
+                    self.nodes[w].color = Color::Red;
+                    x = xp;
+                } else {
+                    if self.nodes[self.nodes[w].left].color == Color::Black {
+                        let wr = self.nodes[w].right;
+                        self.nodes[wr].color = Color::Black;
+                        self.nodes[w].color = Color::Red;
+                        self.left_rotate(w);
+                        w = self.nodes[xp].left;
+                    }
+                    self.nodes[w].color = self.nodes[xp].color;
+                    self.nodes[xp].color = Color::Black;
+                    let wl = self.nodes[w].left;
+                    self.nodes[wl].color = Color::Black;
+                    self.right_rotate(xp);
+                    x = self.root;
+                }
+            }
+        }
+        self.nodes[x].color = Color::Black;
+    }
+
+    // checks that red nodes have black children and returns the black
+    // height of the subtree, which must be the same on both sides
+    #[cfg(test)]
+    pub(super) fn black_height(&self, x: usize) -> usize {
+        if x == NIL {
+            return 1;
+        }
+        let (l, r) = (self.nodes[x].left, self.nodes[x].right);
+        if self.nodes[x].color == Color::Red {
+            assert_eq!(self.nodes[l].color, Color::Black);
+            assert_eq!(self.nodes[r].color, Color::Black);
+        }
+        let lh = self.black_height(l);
+        let rh = self.black_height(r);
+        assert_eq!(lh, rh);
+        lh + usize::from(self.nodes[x].color == Color::Black)
+    }
+}
@@ -0,0 +1,249 @@
+//! Rendering binary trees as ASCII diagrams and Graphviz DOT
+//!
+//! A tree type only has to say how to get from a node to its children and how
+//! to label it by implementing [`BinaryTreeView`], both renderers come with
+//! the trait. Annotations such as the color, height or subtree size of a node
+//! are printed next to its label. An annotation named `color` is also used as
+//! the fill color of the node in DOT output.
+use std::fmt::Write;
+
+/// Read-only view of a binary tree used by the renderers
+pub trait BinaryTreeView {
+    /// Cheap reference to a node, such as `&Node` or an arena index
+    type Handle<'a>: Copy
+    where
+        Self: 'a;
+
+    /// Root of the tree, None for an empty tree
+    fn root(&self) -> Option<Self::Handle<'_>>;
+
+    /// Left child of `node`
+    fn left<'a>(&'a self, node: Self::Handle<'a>) -> Option<Self::Handle<'a>>;
+
+    /// Right child of `node`
+    fn right<'a>(&'a self, node: Self::Handle<'a>) -> Option<Self::Handle<'a>>;
+
+    /// Text shown for `node`
+    fn label<'a>(&'a self, node: Self::Handle<'a>) -> String;
+
+    /// Extra `(name, value)` pairs shown next to the label
+    fn annotations<'a>(&'a self, _node: Self::Handle<'a>) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Draw the tree sideways, the left child above the right one
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::{BinarySearchTree, BinaryTreeView};
+    ///
+    /// let mut tree = BinarySearchTree::new();
+    /// tree.tree_insert(5);
+    /// tree.tree_insert(3);
+    /// tree.tree_insert(7);
+    /// tree.tree_insert(4);
+    ///
+    /// assert_eq!(
+    ///     tree.to_ascii(),
+    ///     "5 [height=3, size=4]\n\
+    ///      ├── 3 [height=2, size=2]\n\
+    ///      │   ├── ·\n\
+    ///      │   └── 4 [height=1, size=1]\n\
+    ///      └── 7 [height=1, size=1]\n"
+    /// );
+    /// ```
+    fn to_ascii(&self) -> String {
+        render_ascii(self, &mut |node| self.annotations(node))
+    }
+
+    /// Graphviz DOT source of the tree, missing children are drawn as points
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::{BinarySearchTree, BinaryTreeView};
+    ///
+    /// let mut tree = BinarySearchTree::new();
+    /// tree.tree_insert(2);
+    /// tree.tree_insert(1);
+    ///
+    /// let dot = tree.to_dot();
+    ///
+    /// assert!(dot.starts_with("digraph tree {"));
+    /// assert!(dot.contains("n0 -> n1 [label=\"L\"];"));
+    /// ```
+    fn to_dot(&self) -> String {
+        render_dot(self, &mut |node| self.annotations(node))
+    }
+}
+
+// Both renderers take the annotations from `annotate`, so a view can compute
+// them for the whole tree at once instead of node by node
+pub(super) fn render_ascii<'a, V: BinaryTreeView + ?Sized>(
+    tree: &'a V,
+    annotate: &mut dyn FnMut(V::Handle<'a>) -> Vec<(&'static str, String)>,
+) -> String {
+    let mut out = String::new();
+    let Some(root) = tree.root() else {
+        return out;
+    };
+    // (node, prefix of its children's lines, line of the node itself)
+    let mut stack = vec![(Some(root), String::new(), String::new())];
+    while let Some((node, prefix, line)) = stack.pop() {
+        out.push_str(&line);
+        let Some(node) = node else {
+            out.push_str("·\n");
+            continue;
+        };
+        out.push_str(&describe(tree, node, annotate));
+        out.push('\n');
+        let (left, right) = (tree.left(node), tree.right(node));
+        if left.is_none() && right.is_none() {
+            continue;
+        }
+        stack.push((right, format!("{prefix}    "), format!("{prefix}└── ")));
+        stack.push((left, format!("{prefix}│   "), format!("{prefix}├── ")));
+    }
+    out
+}
+
+pub(super) fn render_dot<'a, V: BinaryTreeView + ?Sized>(
+    tree: &'a V,
+    annotate: &mut dyn FnMut(V::Handle<'a>) -> Vec<(&'static str, String)>,
+) -> String {
+    let mut out = String::from("digraph tree {\n    node [shape=box];\n");
+    let mut next_id = 0usize;
+    let mut stack = Vec::new();
+    if let Some(root) = tree.root() {
+        stack.push((root, next_id));
+        next_id += 1;
+    }
+    while let Some((node, id)) = stack.pop() {
+        let annotations = annotate(node);
+        let mut label = escape(&tree.label(node));
+        for (name, value) in &annotations {
+            let _ = write!(label, "\\n{}={}", escape(name), escape(value));
+        }
+        let _ = write!(out, "    n{id} [label=\"{label}\"");
+        if let Some((_, color)) = annotations.iter().find(|(name, _)| *name == "color") {
+            let _ = write!(out, ", style=filled, fillcolor=\"{}\"", escape(color));
+            if color == "black" {
+                out.push_str(", fontcolor=white");
+            }
+        }
+        out.push_str("];\n");
+
+        let children = [(tree.left(node), "L"), (tree.right(node), "R")];
+        if children.iter().all(|(child, _)| child.is_none()) {
+            continue;
+        }
+        // push the right child first so the left subtree gets the smaller ids
+        let mut pending = Vec::with_capacity(2);
+        for (child, side) in children {
+            let child_id = next_id;
+            next_id += 1;
+            match child {
+                Some(child) => pending.push((child, child_id)),
+                None => {
+                    let _ = writeln!(out, "    n{child_id} [shape=point];");
+                }
+            }
+            let _ = writeln!(out, "    n{id} -> n{child_id} [label=\"{side}\"];");
+        }
+        stack.extend(pending.into_iter().rev());
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn describe<'a, V: BinaryTreeView + ?Sized>(
+    tree: &'a V,
+    node: V::Handle<'a>,
+    annotate: &mut dyn FnMut(V::Handle<'a>) -> Vec<(&'static str, String)>,
+) -> String {
+    let mut text = tree.label(node);
+    let annotations = annotate(node);
+    if !annotations.is_empty() {
+        let pairs: Vec<String> = annotations
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        let _ = write!(text, " [{}]", pairs.join(", "));
+    }
+    text
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastruct::{BinarySearchTree, OrderStatisticTree, PersistentTree, Treap};
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = BinarySearchTree::<i32>::new();
+        assert_eq!(tree.to_ascii(), "");
+        assert_eq!(tree.to_dot(), "digraph tree {\n    node [shape=box];\n}\n");
+    }
+
+    #[test]
+    fn test_red_black_colors() {
+        let tree: OrderStatisticTree<i32> = (1..=3).collect();
+        assert_eq!(
+            tree.to_ascii(),
+            "2 [color=black, size=3]\n\
+             ├── 1 [color=red, size=1]\n\
+             └── 3 [color=red, size=1]\n"
+        );
+        let dot = tree.to_dot();
+        assert!(dot.contains(
+            "n0 [label=\"2\\ncolor=black\\nsize=3\", style=filled, fillcolor=\"black\", fontcolor=white];"
+        ));
+        assert!(
+            dot.contains("n1 [label=\"1\\ncolor=red\\nsize=1\", style=filled, fillcolor=\"red\"];")
+        );
+        assert!(dot.contains("n0 -> n2 [label=\"R\"];"));
+    }
+
+    #[test]
+    fn test_every_node_is_drawn() {
+        let persistent: PersistentTree<i32> = (0..100).collect();
+        let ascii = persistent.to_ascii();
+        assert_eq!(
+            ascii.lines().filter(|line| !line.ends_with('·')).count(),
+            100
+        );
+        assert!(ascii.lines().next().unwrap().ends_with("size=100]"));
+
+        let treap: Treap<i32> = (0..50).collect();
+        let dot = treap.to_dot();
+        assert_eq!(dot.matches("priority=").count(), 50);
+        // one edge into every node but the root, plus one into every missing child
+        assert_eq!(
+            dot.matches(" -> ").count(),
+            49 + dot.matches("[shape=point]").count()
+        );
+    }
+
+    #[test]
+    fn test_degenerate_search_tree() {
+        // sorted inserts make a path, which the annotations walk only once
+        let mut tree = BinarySearchTree::new();
+        for i in 0..3000 {
+            tree.tree_insert(i);
+        }
+        let dot = tree.to_dot();
+        assert!(dot.contains("n0 [label=\"0\\nheight=3000\\nsize=3000\"];"));
+        assert!(dot.contains("[label=\"2999\\nheight=1\\nsize=1\"];"));
+        assert_eq!(dot.matches("height=").count(), 3000);
+    }
+
+    #[test]
+    fn test_dot_escapes_labels() {
+        let mut tree = BinarySearchTree::new();
+        tree.tree_insert("say \"hi\"".to_string());
+        assert!(tree
+            .to_dot()
+            .contains("n0 [label=\"say \\\"hi\\\"\\nheight=1\\nsize=1\"];"));
+    }
+}
@@ -0,0 +1,549 @@
+//! Rooted trees and ancestor queries
+//!
+//! A `RootedTree` numbers its nodes 0..n and is built from a parent array or
+//! an edge list. After preprocessing, the lowest common ancestor of two nodes
+//! is found in O(lg n) time by binary lifting or in O(1) time from the Euler
+//! tour and a sparse table. Path sums, k-th ancestors and the diameter are
+//! answered on top of these.
+use super::{MinMonoid, SparseTable};
+use core::ops::{Add, Sub};
+use std::collections::VecDeque;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RootedTreeError {
+    #[error("tree has no nodes")]
+    Empty,
+    #[error("tree has no root")]
+    NoRoot,
+    #[error("nodes {0} and {1} are both roots")]
+    MultipleRoots(usize, usize),
+    #[error("node {0} is out of range")]
+    NodeOutOfRange(usize),
+    #[error("node {0} is not reachable from the root")]
+    Unreachable(usize),
+    #[error("{edges} edges on {nodes} nodes do not form a tree")]
+    NotATree { nodes: usize, edges: usize },
+    #[error("{values} values for {nodes} nodes")]
+    ValueCount { nodes: usize, values: usize },
+}
+
+/// Rooted tree on the nodes 0..n
+///
+/// ```rust
+/// use algorithms_rs::datastruct::RootedTree;
+///
+/// //     0
+/// //    / \
+/// //   1   2
+/// //  / \
+/// // 3   4
+/// let tree = RootedTree::from_parents(&[None, Some(0), Some(0), Some(1), Some(1)]).unwrap();
+///
+/// assert_eq!(tree.root(), 0);
+/// assert_eq!(tree.children(1), &[3, 4]);
+/// assert_eq!(tree.depth(4), 2);
+/// assert_eq!(tree.diameter(), (3, 3, 2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RootedTree {
+    root: usize,
+    parent: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    depth: Vec<usize>,
+    // nodes in breadth-first order, every parent before its children
+    order: Vec<usize>,
+}
+
+impl RootedTree {
+    /// Creating a tree from the parent of every node, None for the root
+    pub fn from_parents(parents: &[Option<usize>]) -> Result<Self, RootedTreeError> {
+        let n = parents.len();
+        if n == 0 {
+            return Err(RootedTreeError::Empty);
+        }
+        let mut root = None;
+        let mut children = vec![Vec::new(); n];
+        for (v, parent) in parents.iter().enumerate() {
+            match *parent {
+                None => match root {
+                    None => root = Some(v),
+                    Some(r) => return Err(RootedTreeError::MultipleRoots(r, v)),
+                },
+                Some(p) if p >= n => return Err(RootedTreeError::NodeOutOfRange(p)),
+                Some(p) => children[p].push(v),
+            }
+        }
+        let root = root.ok_or(RootedTreeError::NoRoot)?;
+
+        let mut depth = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        let mut queue = VecDeque::from([root]);
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            for &c in &children[u] {
+                depth[c] = depth[u] + 1;
+                queue.push_back(c);
+            }
+        }
+        // a node on a cycle is never reached from the root
+        if order.len() < n {
+            let mut seen = vec![false; n];
+            for &v in &order {
+                seen[v] = true;
+            }
+            let v = seen
+                .iter()
+                .position(|s| !s)
+                .expect("some node was not reached");
+            return Err(RootedTreeError::Unreachable(v));
+        }
+
+        Ok(Self {
+            root,
+            parent: parents.to_vec(),
+            children,
+            depth,
+            order,
+        })
+    }
+
+    /// Creating a tree on `n` nodes from undirected edges, hung from `root`
+    ///
+    /// Once every node is reachable, more than n - 1 edges means a cycle, a
+    /// self-loop or a repeated edge, which is an error rather than dropped.
+    pub fn from_edges(
+        n: usize,
+        root: usize,
+        edges: &[(usize, usize)],
+    ) -> Result<Self, RootedTreeError> {
+        if n == 0 {
+            return Err(RootedTreeError::Empty);
+        }
+        if root >= n {
+            return Err(RootedTreeError::NodeOutOfRange(root));
+        }
+        let mut adjacent = vec![Vec::new(); n];
+        for &(u, v) in edges {
+            let out_of_range = u.max(v);
+            if out_of_range >= n {
+                return Err(RootedTreeError::NodeOutOfRange(out_of_range));
+            }
+            adjacent[u].push(v);
+            adjacent[v].push(u);
+        }
+        let mut parents = vec![None; n];
+        let mut seen = vec![false; n];
+        seen[root] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(u) = queue.pop_front() {
+            for &v in &adjacent[u] {
+                if !seen[v] {
+                    seen[v] = true;
+                    parents[v] = Some(u);
+                    queue.push_back(v);
+                }
+            }
+        }
+        if let Some(v) = seen.iter().position(|s| !s) {
+            return Err(RootedTreeError::Unreachable(v));
+        }
+        if edges.len() != n - 1 {
+            return Err(RootedTreeError::NotATree {
+                nodes: n,
+                edges: edges.len(),
+            });
+        }
+        Self::from_parents(&parents)
+    }
+
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// A tree always has a root, so it is never empty
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The root node
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Parent of `v`, None for the root
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        self.parent[v]
+    }
+
+    /// Children of `v` in ascending order
+    pub fn children(&self, v: usize) -> &[usize] {
+        &self.children[v]
+    }
+
+    /// Number of edges between `v` and the root
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+
+    /// Longest path in the tree as `(length, u, v)`, length counted in edges
+    ///
+    /// The node farthest from any start node is an end of a longest path, so
+    /// two breadth-first searches find both ends.
+    pub fn diameter(&self) -> (usize, usize, usize) {
+        let (u, _) = self.farthest_from(self.root);
+        let (v, length) = self.farthest_from(u);
+        (length, u, v)
+    }
+
+    fn farthest_from(&self, start: usize) -> (usize, usize) {
+        let mut distance = vec![usize::MAX; self.len()];
+        distance[start] = 0;
+        let mut farthest = (start, 0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(u) = queue.pop_front() {
+            if distance[u] > farthest.1 {
+                farthest = (u, distance[u]);
+            }
+            for &v in self.children[u].iter().chain(self.parent[u].as_ref()) {
+                if distance[v] == usize::MAX {
+                    distance[v] = distance[u] + 1;
+                    queue.push_back(v);
+                }
+            }
+        }
+        farthest
+    }
+}
+
+/// Ancestor table answering LCA and k-th ancestor queries in O(lg n) time
+///
+/// ```rust
+/// use algorithms_rs::datastruct::{BinaryLifting, RootedTree};
+///
+/// let tree = RootedTree::from_parents(&[None, Some(0), Some(1), Some(2), Some(1)]).unwrap();
+/// let lifting = BinaryLifting::new(&tree);
+///
+/// assert_eq!(lifting.lca(3, 4), 1);
+/// assert_eq!(lifting.kth_ancestor(3, 2), Some(1));
+/// assert_eq!(lifting.kth_ancestor(3, 4), None);
+/// assert_eq!(lifting.distance(3, 4), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinaryLifting {
+    // up[j][v] is the 2^j-th ancestor of v, or the root if there is none
+    up: Vec<Vec<usize>>,
+    depth: Vec<usize>,
+}
+
+impl BinaryLifting {
+    /// Build the table in O(n lg n) time
+    pub fn new(tree: &RootedTree) -> Self {
+        let n = tree.len();
+        let levels = (usize::BITS - n.leading_zeros()).max(1) as usize;
+        let mut up = Vec::with_capacity(levels);
+        up.push(
+            (0..n)
+                .map(|v| tree.parent(v).unwrap_or(tree.root()))
+                .collect::<Vec<_>>(),
+        );
+        for j in 1..levels {
+            let prev = &up[j - 1];
+            let next = (0..n).map(|v| prev[prev[v]]).collect();
+            up.push(next);
+        }
+        Self {
+            up,
+            depth: tree.depth.clone(),
+        }
+    }
+
+    /// Ancestor of `v` that is `k` edges above it
+    pub fn kth_ancestor(&self, mut v: usize, k: usize) -> Option<usize> {
+        if k > self.depth[v] {
+            return None;
+        }
+        for (j, up) in self.up.iter().enumerate() {
+            if k >> j & 1 == 1 {
+                v = up[v];
+            }
+        }
+        Some(v)
+    }
+
+    /// Lowest common ancestor of `u` and `v`
+    ///
+    /// ```no
+    /// LCA(u, v)
+    ///     if u.depth < v.depth
+    ///         exchange u with v
+    ///     lift u to the depth of v
+    ///     if u == v return u
+    ///     for j = lg n downto 0
+    ///         if up[j][u] != up[j][v]
+    ///             u = up[j][u]
+    ///             v = up[j][v]
+    ///     return up[0][u]
+    /// ```
+    pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        if self.depth[u] < self.depth[v] {
+            core::mem::swap(&mut u, &mut v);
+        }
+        u = self
+            .kth_ancestor(u, self.depth[u] - self.depth[v])
+            .expect("v is not deeper than u");
+        if u == v {
+            return u;
+        }
+        for up in self.up.iter().rev() {
+            if up[u] != up[v] {
+                u = up[u];
+                v = up[v];
+            }
+        }
+        self.up[0][u]
+    }
+
+    /// Number of edges on the path between `u` and `v`
+    pub fn distance(&self, u: usize, v: usize) -> usize {
+        let w = self.lca(u, v);
+        self.depth[u] + self.depth[v] - 2 * self.depth[w]
+    }
+}
+
+/// LCA in O(1) time per query from the Euler tour and a sparse table
+///
+/// The Euler tour lists a node every time the depth-first walk enters it or
+/// comes back to it from a child. Between the first visits of `u` and `v` the
+/// tour passes through their LCA and nothing higher, so the LCA is the
+/// shallowest node in that range.
+///
+/// ```rust
+/// use algorithms_rs::datastruct::{EulerTourLca, RootedTree};
+///
+/// let tree = RootedTree::from_edges(6, 0, &[(0, 1), (0, 2), (2, 3), (2, 4), (4, 5)]).unwrap();
+/// let lca = EulerTourLca::new(&tree);
+///
+/// assert_eq!(lca.lca(3, 5), 2);
+/// assert_eq!(lca.lca(1, 5), 0);
+/// assert_eq!(lca.lca(4, 5), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EulerTourLca {
+    // first[v] is the index of the first visit of v in the tour
+    first: Vec<usize>,
+    // (depth, node) of every step of the tour
+    table: SparseTable<MinMonoid<(usize, usize)>>,
+}
+
+impl EulerTourLca {
+    /// Build the tour and the table in O(n lg n) time
+    pub fn new(tree: &RootedTree) -> Self {
+        let n = tree.len();
+        let mut tour = Vec::with_capacity(2 * n - 1);
+        let mut first = vec![0; n];
+        // (node, index of the next child to visit)
+        let mut stack = vec![(tree.root(), 0)];
+        first[tree.root()] = 0;
+        tour.push(tree.root());
+        while let Some((u, next)) = stack.last_mut() {
+            let u = *u;
+            match tree.children(u).get(*next) {
+                Some(&c) => {
+                    *next += 1;
+                    first[c] = tour.len();
+                    tour.push(c);
+                    stack.push((c, 0));
+                }
+                None => {
+                    stack.pop();
+                    if let Some((parent, _)) = stack.last() {
+                        tour.push(*parent);
+                    }
+                }
+            }
+        }
+
+        let steps: Vec<(usize, usize)> = tour.iter().map(|&v| (tree.depth(v), v)).collect();
+        Self {
+            first,
+            table: SparseTable::from_slice(&steps),
+        }
+    }
+
+    /// Lowest common ancestor of `u` and `v`
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        let (i, j) = (self.first[u], self.first[v]);
+        let (_, w) = self.table.query(i.min(j)..=i.max(j));
+        w
+    }
+}
+
+/// Sum of the node values on the path between two nodes, in O(1) time
+///
+/// ```rust
+/// use algorithms_rs::datastruct::{PathSum, RootedTree};
+///
+/// let tree = RootedTree::from_parents(&[None, Some(0), Some(0), Some(1)]).unwrap();
+/// let sums = PathSum::new(&tree, &[1, 10, 100, 1000]);
+///
+/// assert_eq!(sums.query(3, 2), 1111);
+/// assert_eq!(sums.query(3, 3), 1000);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PathSum<T> {
+    values: Vec<T>,
+    // sum of the values from the root down to v
+    prefix: Vec<T>,
+    lca: EulerTourLca,
+}
+
+impl<T> PathSum<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T>,
+{
+    /// Precompute the root path sums of `values`, one value per node
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not exactly one value for every node.
+    pub fn new(tree: &RootedTree, values: &[T]) -> Self {
+        assert_eq!(values.len(), tree.len(), "one value per node");
+        let mut prefix = values.to_vec();
+        for &v in &tree.order {
+            if let Some(p) = tree.parent(v) {
+                prefix[v] = prefix[p] + values[v];
+            }
+        }
+        Self {
+            values: values.to_vec(),
+            prefix,
+            lca: EulerTourLca::new(tree),
+        }
+    }
+
+    /// Sum of the values on the path from `u` to `v`, both included
+    pub fn query(&self, u: usize, v: usize) -> T {
+        let w = self.lca.lca(u, v);
+        // subtract before adding so unsigned sums never go below zero
+        (self.prefix[u] - self.prefix[w]) + (self.prefix[v] - self.prefix[w]) + self.values[w]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_parents(rng: &mut StdRng, n: usize) -> Vec<Option<usize>> {
+        (0..n)
+            .map(|v| (v > 0).then(|| rng.gen_range(0..v)))
+            .collect()
+    }
+
+    fn naive_lca(tree: &RootedTree, mut u: usize, mut v: usize) -> usize {
+        while tree.depth(u) > tree.depth(v) {
+            u = tree.parent(u).unwrap();
+        }
+        while tree.depth(v) > tree.depth(u) {
+            v = tree.parent(v).unwrap();
+        }
+        while u != v {
+            u = tree.parent(u).unwrap();
+            v = tree.parent(v).unwrap();
+        }
+        u
+    }
+
+    #[test]
+    fn test_lca_against_naive_walk() {
+        let mut rng = StdRng::seed_from_u64(36);
+        for n in 1..80 {
+            let tree = RootedTree::from_parents(&random_parents(&mut rng, n)).unwrap();
+            let lifting = BinaryLifting::new(&tree);
+            let euler = EulerTourLca::new(&tree);
+            for _ in 0..50 {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                let expected = naive_lca(&tree, u, v);
+                assert_eq!(lifting.lca(u, v), expected);
+                assert_eq!(euler.lca(u, v), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_path_queries_on_a_chain() {
+        let n: usize = 1000;
+        let parents: Vec<Option<usize>> = (0..n).map(|v| v.checked_sub(1)).collect();
+        let tree = RootedTree::from_parents(&parents).unwrap();
+        let lifting = BinaryLifting::new(&tree);
+        assert_eq!(lifting.kth_ancestor(999, 999), Some(0));
+        assert_eq!(lifting.kth_ancestor(999, 1000), None);
+        assert_eq!(lifting.kth_ancestor(500, 0), Some(500));
+        assert_eq!(tree.diameter(), (999, 999, 0));
+
+        let values: Vec<u64> = (0..n as u64).collect();
+        let sums = PathSum::new(&tree, &values);
+        assert_eq!(sums.query(10, 20), (10..=20).sum());
+        assert_eq!(sums.query(999, 0), (0..1000).sum());
+    }
+
+    #[test]
+    fn test_diameter_against_all_pairs() {
+        let mut rng = StdRng::seed_from_u64(360);
+        for n in 1..40 {
+            let tree = RootedTree::from_parents(&random_parents(&mut rng, n)).unwrap();
+            let lifting = BinaryLifting::new(&tree);
+            let longest = (0..n)
+                .flat_map(|u| (0..n).map(move |v| (u, v)))
+                .map(|(u, v)| lifting.distance(u, v))
+                .max()
+                .unwrap();
+            let (length, u, v) = tree.diameter();
+            assert_eq!(length, longest);
+            assert_eq!(lifting.distance(u, v), length);
+        }
+    }
+
+    #[test]
+    fn test_invalid_trees() {
+        assert_eq!(
+            RootedTree::from_parents(&[]).unwrap_err(),
+            RootedTreeError::Empty
+        );
+        assert_eq!(
+            RootedTree::from_parents(&[Some(1), Some(0)]).unwrap_err(),
+            RootedTreeError::NoRoot
+        );
+        assert_eq!(
+            RootedTree::from_parents(&[None, None]).unwrap_err(),
+            RootedTreeError::MultipleRoots(0, 1)
+        );
+        assert_eq!(
+            RootedTree::from_parents(&[None, Some(3)]).unwrap_err(),
+            RootedTreeError::NodeOutOfRange(3)
+        );
+        assert_eq!(
+            RootedTree::from_parents(&[None, Some(2), Some(1)]).unwrap_err(),
+            RootedTreeError::Unreachable(1)
+        );
+        assert_eq!(
+            RootedTree::from_edges(3, 0, &[(0, 1)]).unwrap_err(),
+            RootedTreeError::Unreachable(2)
+        );
+        // a cycle, a self-loop and a repeated edge all reach every node
+        let not_a_tree = RootedTreeError::NotATree { nodes: 3, edges: 3 };
+        for edges in [
+            [(0, 1), (1, 2), (2, 0)],
+            [(0, 1), (1, 2), (2, 2)],
+            [(0, 1), (1, 2), (2, 1)],
+        ] {
+            assert_eq!(
+                RootedTree::from_edges(3, 0, &edges).unwrap_err(),
+                not_a_tree
+            );
+        }
+    }
+}
@@ -0,0 +1,547 @@
+//! Segment trees over a monoid
+//!
+//! Every node of a segment tree stores the combination of a contiguous range
+//! of the array, the root covering all of it and the leaves single elements.
+//! A range query combines O(lg n) nodes and a point update changes the
+//! O(lg n) nodes above one leaf. `LazySegmentTree` also adds to or assigns
+//! whole ranges in O(lg n) time by leaving pending updates on the highest
+//! nodes they cover and pushing them down only when a later operation needs
+//! the children.
+use core::marker::PhantomData;
+use core::ops::{Add, Bound, RangeBounds};
+use num_traits::{Bounded, Zero};
+
+/// Associative operation with an identity element
+///
+/// `combine(identity(), a) == a == combine(a, identity())` and
+/// `combine(combine(a, b), c) == combine(a, combine(b, c))` for all values.
+pub trait Monoid {
+    type Value: Clone;
+
+    /// Neutral element, the aggregate of an empty range
+    fn identity() -> Self::Value;
+
+    /// Aggregate of two adjacent ranges, `a` to the left of `b`
+    fn combine(a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// Update applied to every element of a range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeUpdate<T> {
+    /// Add the value to every element
+    Add(T),
+    /// Replace every element with the value
+    Assign(T),
+}
+
+impl<T: Clone + Add<Output = T>> RangeUpdate<T> {
+    // a single update with the effect of `self` followed by `later`
+    fn then(self, later: RangeUpdate<T>) -> RangeUpdate<T> {
+        match (self, later) {
+            (_, RangeUpdate::Assign(x)) => RangeUpdate::Assign(x),
+            (RangeUpdate::Add(a), RangeUpdate::Add(b)) => RangeUpdate::Add(a + b),
+            (RangeUpdate::Assign(a), RangeUpdate::Add(b)) => RangeUpdate::Assign(a + b),
+        }
+    }
+}
+
+/// Monoid whose aggregates can be updated without looking at the elements
+pub trait RangeAction: Monoid {
+    /// Aggregate of `len` elements, previously `aggregate`, after `update`
+    fn apply(update: &RangeUpdate<Self::Value>, aggregate: &Self::Value, len: usize)
+        -> Self::Value;
+}
+
+/// Sums under `+`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SumMonoid<T>(PhantomData<T>);
+
+impl<T: Copy + Zero> Monoid for SumMonoid<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::zero()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+impl<T: Copy + Zero> RangeAction for SumMonoid<T> {
+    fn apply(update: &RangeUpdate<T>, aggregate: &T, len: usize) -> T {
+        match update {
+            RangeUpdate::Add(x) => *aggregate + times(*x, len),
+            RangeUpdate::Assign(x) => times(*x, len),
+        }
+    }
+}
+
+// x added to itself n times by doubling, without converting n to T; the
+// partial sums never exceed x * n, so nothing overflows if the result fits
+fn times<T: Copy + Zero>(mut x: T, mut n: usize) -> T {
+    let mut result = T::zero();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = result + x;
+        }
+        n >>= 1;
+        if n > 0 {
+            x = x + x;
+        }
+    }
+    result
+}
+
+/// Minimums, the identity is the largest value
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinMonoid<T>(PhantomData<T>);
+
+impl<T: Copy + Ord + Bounded> Monoid for MinMonoid<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::max_value()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a.min(b)
+    }
+}
+
+impl<T: Copy + Ord + Bounded + Add<Output = T>> RangeAction for MinMonoid<T> {
+    fn apply(update: &RangeUpdate<T>, aggregate: &T, _len: usize) -> T {
+        match update {
+            // the identity stands for no elements, adding to it changes nothing
+            RangeUpdate::Add(_) if *aggregate == T::max_value() => *aggregate,
+            RangeUpdate::Add(x) => *aggregate + *x,
+            RangeUpdate::Assign(x) => *x,
+        }
+    }
+}
+
+/// Maximums, the identity is the smallest value
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxMonoid<T>(PhantomData<T>);
+
+impl<T: Copy + Ord + Bounded> Monoid for MaxMonoid<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::min_value()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a.max(b)
+    }
+}
+
+impl<T: Copy + Ord + Bounded + Add<Output = T>> RangeAction for MaxMonoid<T> {
+    fn apply(update: &RangeUpdate<T>, aggregate: &T, _len: usize) -> T {
+        match update {
+            // the identity stands for no elements, adding to it changes nothing
+            RangeUpdate::Add(_) if *aggregate == T::min_value() => *aggregate,
+            RangeUpdate::Add(x) => *aggregate + *x,
+            RangeUpdate::Assign(x) => *x,
+        }
+    }
+}
+
+// the half-open range of indices in `range`, panicking like slice indexing
+pub(super) fn to_bounds<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "range starts at {start} but ends at {end}");
+    assert!(end <= len, "range end {end} out of range for length {len}");
+    (start, end)
+}
+
+/// Segment tree with point updates and range queries
+///
+/// ```rust
+/// use algorithms_rs::datastruct::{MinMonoid, SegmentTree};
+///
+/// let mut tree = SegmentTree::<MinMonoid<i32>>::from_slice(&[5, 3, 8, 6, 1]);
+///
+/// assert_eq!(tree.query(0..3), 3);
+/// tree.set(1, 7);
+/// assert_eq!(tree.query(0..3), 5);
+/// assert_eq!(tree.query(..), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SegmentTree<M: Monoid> {
+    len: usize,
+    // tree[1] is the root, tree[2i] and tree[2i + 1] are the children of i
+    tree: Vec<M::Value>,
+}
+
+impl<M: Monoid> SegmentTree<M> {
+    /// Creating a tree of `len` identity elements
+    pub fn new(len: usize) -> Self {
+        Self::from_slice(&vec![M::identity(); len])
+    }
+
+    /// Creating a tree over `values` in O(n) time
+    pub fn from_slice(values: &[M::Value]) -> Self {
+        let len = values.len();
+        let mut tree = vec![M::identity(); 4 * len.max(1)];
+        if len > 0 {
+            Self::build(&mut tree, values, 1, 0, len);
+        }
+        Self { len, tree }
+    }
+
+    fn build(tree: &mut [M::Value], values: &[M::Value], node: usize, lo: usize, hi: usize) {
+        if hi - lo == 1 {
+            tree[node] = values[lo].clone();
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        Self::build(tree, values, 2 * node, lo, mid);
+        Self::build(tree, values, 2 * node + 1, mid, hi);
+        tree[node] = M::combine(&tree[2 * node], &tree[2 * node + 1]);
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Element at `index`
+    pub fn get(&self, index: usize) -> M::Value {
+        self.query(index..=index)
+    }
+
+    /// Replace the element at `index` with `value`
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(
+            index < self.len,
+            "index {index} out of range for length {}",
+            self.len
+        );
+        let (mut node, mut lo, mut hi) = (1, 0, self.len);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if index < mid {
+                (node, hi) = (2 * node, mid);
+            } else {
+                (node, lo) = (2 * node + 1, mid);
+            }
+        }
+        self.tree[node] = value;
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = M::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+        }
+    }
+
+    /// Aggregate of the elements in `range`, the identity for an empty range
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Value {
+        let (l, r) = to_bounds(range, self.len);
+        if l == r {
+            return M::identity();
+        }
+        self.query_node(1, 0, self.len, l, r)
+    }
+
+    fn query_node(&self, node: usize, lo: usize, hi: usize, l: usize, r: usize) -> M::Value {
+        if l <= lo && hi <= r {
+            return self.tree[node].clone();
+        }
+        let mid = lo + (hi - lo) / 2;
+        if r <= mid {
+            self.query_node(2 * node, lo, mid, l, r)
+        } else if l >= mid {
+            self.query_node(2 * node + 1, mid, hi, l, r)
+        } else {
+            M::combine(
+                &self.query_node(2 * node, lo, mid, l, r),
+                &self.query_node(2 * node + 1, mid, hi, l, r),
+            )
+        }
+    }
+}
+
+/// Segment tree that also adds to or assigns whole ranges
+///
+/// ```rust
+/// use algorithms_rs::datastruct::{LazySegmentTree, RangeUpdate, SumMonoid};
+///
+/// let mut tree = LazySegmentTree::<SumMonoid<i64>>::from_slice(&[1, 2, 3, 4, 5]);
+///
+/// tree.update(1..4, RangeUpdate::Add(10));
+/// assert_eq!(tree.query(..), 45);
+/// tree.update(..2, RangeUpdate::Assign(0));
+/// assert_eq!(tree.query(0..3), 13);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazySegmentTree<M: Monoid> {
+    len: usize,
+    tree: Vec<M::Value>,
+    // update already applied to tree[i] but not yet to its children
+    pending: Vec<Option<RangeUpdate<M::Value>>>,
+}
+
+impl<M> LazySegmentTree<M>
+where
+    M: RangeAction,
+    M::Value: Add<Output = M::Value>,
+{
+    /// Creating a tree of `len` identity elements
+    pub fn new(len: usize) -> Self {
+        Self::from_slice(&vec![M::identity(); len])
+    }
+
+    /// Creating a tree over `values` in O(n) time
+    pub fn from_slice(values: &[M::Value]) -> Self {
+        let plain = SegmentTree::<M>::from_slice(values);
+        Self {
+            len: plain.len,
+            pending: vec![None; plain.tree.len()],
+            tree: plain.tree,
+        }
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn apply_node(&mut self, node: usize, update: &RangeUpdate<M::Value>, len: usize) {
+        self.tree[node] = M::apply(update, &self.tree[node], len);
+        if len > 1 {
+            self.pending[node] = Some(match self.pending[node].take() {
+                None => update.clone(),
+                Some(earlier) => earlier.then(update.clone()),
+            });
+        }
+    }
+
+    fn push_down(&mut self, node: usize, lo: usize, hi: usize) {
+        if let Some(update) = self.pending[node].take() {
+            let mid = lo + (hi - lo) / 2;
+            self.apply_node(2 * node, &update, mid - lo);
+            self.apply_node(2 * node + 1, &update, hi - mid);
+        }
+    }
+
+    /// Element at `index`
+    pub fn get(&self, index: usize) -> M::Value {
+        self.query(index..=index)
+    }
+
+    /// Replace the element at `index` with `value`
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(
+            index < self.len,
+            "index {index} out of range for length {}",
+            self.len
+        );
+        self.update(index..=index, RangeUpdate::Assign(value));
+    }
+
+    /// Apply `update` to every element in `range`
+    ///
+    /// ```no
+    /// UPDATE(x, [lo, hi), [l, r), u)
+    ///     if [lo, hi) is inside [l, r)
+    ///         x.value = APPLY(u, x.value, hi - lo)
+    ///         x.pending = x.pending followed by u
+    ///         return
+    ///     PUSH-DOWN(x)
+    ///     UPDATE on the children overlapping [l, r)
+    ///     x.value = COMBINE(x.left.value, x.right.value)
+    /// ```
+    pub fn update<R: RangeBounds<usize>>(&mut self, range: R, update: RangeUpdate<M::Value>) {
+        let (l, r) = to_bounds(range, self.len);
+        if l < r {
+            self.update_node(1, 0, self.len, l, r, &update);
+        }
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        l: usize,
+        r: usize,
+        update: &RangeUpdate<M::Value>,
+    ) {
+        if l <= lo && hi <= r {
+            self.apply_node(node, update, hi - lo);
+            return;
+        }
+        self.push_down(node, lo, hi);
+        let mid = lo + (hi - lo) / 2;
+        if l < mid {
+            self.update_node(2 * node, lo, mid, l, r, update);
+        }
+        if r > mid {
+            self.update_node(2 * node + 1, mid, hi, l, r, update);
+        }
+        self.tree[node] = M::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    /// Aggregate of the elements in `range`, the identity for an empty range
+    ///
+    /// Pending updates are applied to the partial results on the way back up
+    /// instead of being pushed down, so queries do not need `&mut self`.
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Value {
+        let (l, r) = to_bounds(range, self.len);
+        if l == r {
+            return M::identity();
+        }
+        self.query_node(1, 0, self.len, l, r)
+    }
+
+    fn query_node(&self, node: usize, lo: usize, hi: usize, l: usize, r: usize) -> M::Value {
+        if l <= lo && hi <= r {
+            return self.tree[node].clone();
+        }
+        let mid = lo + (hi - lo) / 2;
+        let result = if r <= mid {
+            self.query_node(2 * node, lo, mid, l, r)
+        } else if l >= mid {
+            self.query_node(2 * node + 1, mid, hi, l, r)
+        } else {
+            M::combine(
+                &self.query_node(2 * node, lo, mid, l, r),
+                &self.query_node(2 * node + 1, mid, hi, l, r),
+            )
+        };
+        match &self.pending[node] {
+            Some(update) => M::apply(update, &result, r.min(hi) - l.max(lo)),
+            None => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// String concatenation, a monoid that is not commutative
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn combine(a: &String, b: &String) -> String {
+            format!("{a}{b}")
+        }
+    }
+
+    #[test]
+    fn test_user_monoid_keeps_order() {
+        let words: Vec<String> = ["a", "b", "c", "d", "e"].map(String::from).to_vec();
+        let mut tree = SegmentTree::<Concat>::from_slice(&words);
+        assert_eq!(tree.query(1..4), "bcd");
+        tree.set(2, "X".to_string());
+        assert_eq!(tree.query(..), "abXde");
+        assert_eq!(tree.query(3..3), "");
+        assert_eq!(SegmentTree::<Concat>::new(0).query(..), "");
+    }
+
+    #[test]
+    fn test_updates_near_the_type_limits() {
+        let mut mins = LazySegmentTree::<MinMonoid<i64>>::new(4);
+        mins.update(.., RangeUpdate::Add(1));
+        assert_eq!(mins.query(..), i64::MAX);
+        mins.update(1..3, RangeUpdate::Assign(5));
+        mins.update(.., RangeUpdate::Add(1));
+        assert_eq!(mins.query(..), 6);
+
+        let mut maxs = LazySegmentTree::<MaxMonoid<i64>>::new(4);
+        maxs.update(.., RangeUpdate::Add(-1));
+        assert_eq!(maxs.query(..), i64::MIN);
+
+        // a range longer than u8::MAX, with sums that still fit
+        let mut sums = LazySegmentTree::<SumMonoid<u8>>::from_slice(&[0; 300]);
+        sums.update(.., RangeUpdate::Assign(0));
+        assert_eq!(sums.query(..), 0);
+        sums.update(..255, RangeUpdate::Add(1));
+        assert_eq!(sums.query(..), 255);
+    }
+
+    #[test]
+    fn test_random_against_vec() {
+        let mut rng = StdRng::seed_from_u64(39);
+        for len in 1..40 {
+            let mut values: Vec<i64> = (0..len).map(|_| rng.gen_range(-100..100)).collect();
+            let mut plain = SegmentTree::<MaxMonoid<i64>>::from_slice(&values);
+            let mut sums = LazySegmentTree::<SumMonoid<i64>>::from_slice(&values);
+            let mut mins = LazySegmentTree::<MinMonoid<i64>>::from_slice(&values);
+            for _ in 0..200 {
+                let l = rng.gen_range(0..len);
+                let r = rng.gen_range(l..=len);
+                let x = rng.gen_range(-100..100);
+                match rng.gen_range(0..4) {
+                    0 => {
+                        values[l..r].iter_mut().for_each(|v| *v += x);
+                        sums.update(l..r, RangeUpdate::Add(x));
+                        mins.update(l..r, RangeUpdate::Add(x));
+                        for (i, value) in values.iter().enumerate().take(r).skip(l) {
+                            plain.set(i, *value);
+                        }
+                    }
+                    1 => {
+                        values[l..r].iter_mut().for_each(|v| *v = x);
+                        sums.update(l..r, RangeUpdate::Assign(x));
+                        mins.update(l..r, RangeUpdate::Assign(x));
+                        for (i, value) in values.iter().enumerate().take(r).skip(l) {
+                            plain.set(i, *value);
+                        }
+                    }
+                    2 => {
+                        values[l] = x;
+                        sums.set(l, x);
+                        mins.set(l, x);
+                        plain.set(l, x);
+                    }
+                    _ => {
+                        let range = &values[l..r];
+                        assert_eq!(sums.query(l..r), range.iter().sum::<i64>());
+                        assert_eq!(mins.query(l..r), *range.iter().min().unwrap_or(&i64::MAX));
+                        assert_eq!(plain.query(l..r), *range.iter().max().unwrap_or(&i64::MIN));
+                    }
+                }
+            }
+            for (i, value) in values.iter().enumerate() {
+                assert_eq!(sums.get(i), *value);
+                assert_eq!(mins.get(i), *value);
+                assert_eq!(plain.get(i), *value);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_query_past_the_end() {
+        SegmentTree::<SumMonoid<i32>>::new(3).query(1..4);
+    }
+}
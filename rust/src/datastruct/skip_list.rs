@@ -0,0 +1,379 @@
+//! Skip list
+//!
+//! A sorted linked list with extra express lanes: every node is on level 0
+//! and, with probability 1/2, also on the next level up, and so on. A search
+//! starts on the highest level and drops down a level whenever the next key
+//! is too large, which takes O(lg n) expected steps. The levels come from a
+//! random number generator that can be seeded to make the structure
+//! reproducible.
+use core::ops::{Bound, RangeBounds};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Highest level a node can reach, enough for 2^32 keys
+const MAX_LEVEL: usize = 32;
+
+#[derive(Debug)]
+struct SkipNode<K, V> {
+    key: K,
+    value: V,
+    // forward[i] is the next node on level i
+    forward: Vec<Option<usize>>,
+}
+
+/// Skip list ordered map
+///
+/// ```rust
+/// use algorithms_rs::datastruct::SkipList;
+///
+/// let mut list = SkipList::with_seed(42);
+///
+/// list.insert(3, "c");
+/// list.insert(1, "a");
+/// list.insert(2, "b");
+///
+/// assert_eq!(list.get(&2), Some(&"b"));
+/// assert_eq!(list.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec![1, 2, 3]);
+/// ```
+#[derive(Debug)]
+pub struct SkipList<K, V> {
+    // forward pointers of the header, which holds no key
+    head: Vec<Option<usize>>,
+    // node slots, None for slots on the free list
+    nodes: Vec<Option<SkipNode<K, V>>>,
+    free: Vec<usize>,
+    // number of levels in use
+    level: usize,
+    len: usize,
+    rng: StdRng,
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for (key, value) in iter {
+            list.insert(key, value);
+        }
+        list
+    }
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// Creating an empty skip list with levels drawn from system entropy
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// Creating an empty skip list whose levels are reproducible from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
+        Self {
+            head: vec![None; MAX_LEVEL],
+            nodes: Vec::new(),
+            free: Vec::new(),
+            level: 1,
+            len: 0,
+            rng,
+        }
+    }
+
+    /// Number of keys in the list
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if the list is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, index: usize) -> &SkipNode<K, V> {
+        self.nodes[index]
+            .as_ref()
+            .expect("linked slot holds a node")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut SkipNode<K, V> {
+        self.nodes[index]
+            .as_mut()
+            .expect("linked slot holds a node")
+    }
+
+    // next node on `level` after `node`, where None stands for the header
+    fn next(&self, node: Option<usize>, level: usize) -> Option<usize> {
+        match node {
+            None => self.head[level],
+            Some(index) => self.node(index).forward[level],
+        }
+    }
+
+    fn set_next(&mut self, node: Option<usize>, level: usize, next: Option<usize>) {
+        match node {
+            None => self.head[level] = next,
+            Some(index) => self.node_mut(index).forward[level] = next,
+        }
+    }
+
+    // for every level, the last node whose key is before `key`
+    fn predecessors<F>(&self, before: F) -> [Option<usize>; MAX_LEVEL]
+    where
+        F: Fn(&K) -> bool,
+    {
+        let mut update = [None; MAX_LEVEL];
+        let mut x = None;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.next(x, level) {
+                if before(&self.node(next).key) {
+                    x = Some(next);
+                } else {
+                    break;
+                }
+            }
+            update[level] = x;
+        }
+        update
+    }
+
+    fn random_level(&mut self) -> usize {
+        let mut level = 1;
+        while level < MAX_LEVEL && self.rng.gen_bool(0.5) {
+            level += 1;
+        }
+        level
+    }
+
+    fn find(&self, key: &K) -> Option<usize> {
+        let update = self.predecessors(|k| k < key);
+        self.next(update[0], 0)
+            .filter(|index| self.node(*index).key == *key)
+    }
+
+    /// Find the value stored under `key`
+    ///
+    /// ```no
+    /// SEARCH(list, key)
+    ///     x = list.header
+    ///     for i = list.level downto 1
+    ///         while x.forward[i].key < key
+    ///             x = x.forward[i]
+    ///     x = x.forward[1]
+    ///     if x.key == key return x.value
+    ///     else return NIL
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|index| &self.node(index).value)
+    }
+
+    /// Mutable reference to the value stored under `key`
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.find(key)?;
+        Some(&mut self.node_mut(index).value)
+    }
+
+    /// Determine if the list contains `key`
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Insert a key-value pair, returning the old value if `key` was present
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let update = self.predecessors(|k| *k < key);
+        if let Some(index) = self.next(update[0], 0) {
+            if self.node(index).key == key {
+                return Some(core::mem::replace(&mut self.node_mut(index).value, value));
+            }
+        }
+
+        let level = self.random_level();
+        // levels above the current top have the header as their predecessor
+        self.level = self.level.max(level);
+        let forward = (0..level).map(|i| self.next(update[i], i)).collect();
+        let node = SkipNode {
+            key,
+            value,
+            forward,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        for (i, pred) in update.iter().enumerate().take(level) {
+            self.set_next(*pred, i, Some(index));
+        }
+        self.len += 1;
+        None
+    }
+
+    /// Remove `key` from the list, returning its value
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let update = self.predecessors(|k| k < key);
+        let index = self
+            .next(update[0], 0)
+            .filter(|index| self.node(*index).key == *key)?;
+        let node = self.nodes[index].take().expect("linked slot holds a node");
+        for (i, next) in node.forward.iter().enumerate() {
+            self.set_next(update[i], i, *next);
+        }
+        while self.level > 1 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.free.push(index);
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    /// Smallest key and its value
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// Largest key and its value
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut x = None;
+        for level in (0..self.level).rev() {
+            while let Some(next) = self.next(x, level) {
+                x = Some(next);
+            }
+        }
+        x.map(|index| {
+            let node = self.node(index);
+            (&node.key, &node.value)
+        })
+    }
+
+    /// Iterate over the entries in ascending key order
+    pub fn iter(&self) -> SkipListIter<'_, K, V> {
+        SkipListIter {
+            list: self,
+            next: self.head[0],
+        }
+    }
+
+    /// Iterate over the entries whose keys fall in `range`, in ascending key order
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::SkipList;
+    ///
+    /// let list: SkipList<i32, ()> = (0..100).map(|k| (k, ())).collect();
+    ///
+    /// let keys: Vec<i32> = list.range(10..=13).map(|(k, _)| *k).collect();
+    ///
+    /// assert_eq!(keys, vec![10, 11, 12, 13]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> impl Iterator<Item = (&K, &V)> {
+        let update = match range.start_bound() {
+            Bound::Included(start) => self.predecessors(|k| k < start),
+            Bound::Excluded(start) => self.predecessors(|k| k <= start),
+            Bound::Unbounded => [None; MAX_LEVEL],
+        };
+        let iter = SkipListIter {
+            list: self,
+            next: self.next(update[0], 0),
+        };
+        iter.take_while(move |(key, _)| match range.end_bound() {
+            Bound::Included(end) => *key <= end,
+            Bound::Excluded(end) => *key < end,
+            Bound::Unbounded => true,
+        })
+    }
+}
+
+/// Iterator over the entries of a skip list in ascending key order
+#[derive(Debug)]
+pub struct SkipListIter<'a, K, V> {
+    list: &'a SkipList<K, V>,
+    next: Option<usize>,
+}
+
+impl<'a, K: Ord, V> Iterator for SkipListIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.list.node(self.next?);
+        self.next = node.forward[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn levels<K: Ord, V>(list: &SkipList<K, V>) -> Vec<usize> {
+        let mut result = Vec::new();
+        let mut x = list.head[0];
+        while let Some(index) = x {
+            let node = list.node(index);
+            result.push(node.forward.len());
+            x = node.forward[0];
+        }
+        result
+    }
+
+    #[test]
+    fn test_random_against_btree_map() {
+        let mut rng = StdRng::seed_from_u64(32);
+        let mut list = SkipList::with_seed(32);
+        let mut expected = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.gen_range(0..500);
+            match rng.gen_range(0..3) {
+                0 | 1 => assert_eq!(list.insert(key, -key), expected.insert(key, -key)),
+                _ => assert_eq!(list.remove(&key), expected.remove(&key)),
+            }
+            assert_eq!(list.len(), expected.len());
+        }
+        assert!(list.iter().eq(expected.iter()));
+        assert!(list.range(100..200).eq(expected.range(100..200)));
+        assert!(list
+            .range((Bound::Excluded(250), Bound::Unbounded))
+            .eq(expected.range((Bound::Excluded(250), Bound::Unbounded))));
+        assert_eq!(list.first(), expected.iter().next());
+        assert_eq!(list.last(), expected.iter().next_back());
+        for key in 0..500 {
+            assert_eq!(list.get(&key), expected.get(&key));
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_structure() {
+        let a: Vec<i32> = (0..200).collect();
+        let mut first = SkipList::with_seed(7);
+        let mut second = SkipList::with_seed(7);
+        for key in &a {
+            first.insert(*key, ());
+            second.insert(*key, ());
+        }
+        assert_eq!(levels(&first), levels(&second));
+        assert!(first.level > 1);
+    }
+
+    #[test]
+    fn test_empty_list() {
+        let mut list = SkipList::<i32, i32>::with_seed(0);
+        assert_eq!(list.first(), None);
+        assert_eq!(list.last(), None);
+        assert_eq!(list.remove(&1), None);
+        assert_eq!(list.range(..).count(), 0);
+        list.insert(1, 1);
+        *list.get_mut(&1).unwrap() = 5;
+        assert_eq!(list.remove(&1), Some(5));
+        assert_eq!(list.level, 1);
+    }
+}
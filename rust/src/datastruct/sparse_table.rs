@@ -0,0 +1,331 @@
+//! Range queries over static arrays
+//!
+//! A `SparseTable` stores the aggregate of every range whose length is a
+//! power of two, O(n lg n) values in all. When combining a value with itself
+//! changes nothing, as for minimum, maximum or gcd, any range is covered by
+//! two overlapping power-of-two ranges, so a query takes O(1) time.
+//!
+//! `CartesianRmq` answers range minimum queries in O(1) time after O(n)
+//! preprocessing. The minimum of a range is the lowest common ancestor of
+//! its ends in the Cartesian tree of the array, and the right spine of the
+//! Cartesian tree of a prefix is the stack of its suffix minimums. Storing
+//! that stack as a bit mask for every position answers queries inside a
+//! block of 64 elements, and a sparse table over the block minimums answers
+//! the rest.
+use super::segment_tree::to_bounds;
+use super::{MaxMonoid, MinMonoid, Monoid};
+use core::marker::PhantomData;
+use core::ops::RangeBounds;
+use num_traits::{Bounded, Unsigned};
+
+/// Monoid whose operation is idempotent, `combine(a, a) == a`
+pub trait Idempotent: Monoid {}
+
+impl<T: Copy + Ord + Bounded> Idempotent for MinMonoid<T> {}
+
+impl<T: Copy + Ord + Bounded> Idempotent for MaxMonoid<T> {}
+
+/// Greatest common divisors, the identity is 0
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GcdMonoid<T>(PhantomData<T>);
+
+impl<T: Copy + Unsigned> Monoid for GcdMonoid<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::zero()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        let (mut a, mut b) = (*a, *b);
+        while !b.is_zero() {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+}
+
+impl<T: Copy + Unsigned> Idempotent for GcdMonoid<T> {}
+
+/// Sparse table for O(1) range queries under an idempotent monoid
+///
+/// ```rust
+/// use algorithms_rs::datastruct::{GcdMonoid, MinMonoid, SparseTable};
+///
+/// let mins = SparseTable::<MinMonoid<i32>>::from_slice(&[4, 6, 1, 5, 7, 3]);
+/// let gcds = SparseTable::<GcdMonoid<u32>>::from_slice(&[12, 18, 24, 9]);
+///
+/// assert_eq!(mins.query(3..6), 3);
+/// assert_eq!(mins.query(..), 1);
+/// assert_eq!(gcds.query(0..3), 6);
+/// assert_eq!(gcds.query(1..), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SparseTable<M: Monoid> {
+    // table[j][i] is the aggregate of values[i..i + 2^j]
+    table: Vec<Vec<M::Value>>,
+}
+
+impl<M: Idempotent> SparseTable<M> {
+    /// Creating the table over `values` in O(n lg n) time
+    pub fn from_slice(values: &[M::Value]) -> Self {
+        let mut table = vec![values.to_vec()];
+        let mut width = 1;
+        while 2 * width <= values.len() {
+            let prev = table.last().expect("table has a first row");
+            let next = (0..prev.len() - width)
+                .map(|i| M::combine(&prev[i], &prev[i + width]))
+                .collect();
+            table.push(next);
+            width *= 2;
+        }
+        Self { table }
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    /// Determine if there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Aggregate of the elements in `range`, the identity for an empty range
+    ///
+    /// ```no
+    /// QUERY(l, r)
+    ///     j = floor(lg(r - l))
+    ///     return COMBINE(table[j][l], table[j][r - 2^j])
+    /// ```
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> M::Value {
+        let (l, r) = to_bounds(range, self.len());
+        if l == r {
+            return M::identity();
+        }
+        let level = (r - l).ilog2() as usize;
+        M::combine(&self.table[level][l], &self.table[level][r - (1 << level)])
+    }
+}
+
+// leftmost minimum, None is the identity
+#[derive(Debug, Clone)]
+struct ArgMin<T>(PhantomData<T>);
+
+impl<T: Ord + Clone> Monoid for ArgMin<T> {
+    type Value = Option<(T, usize)>;
+
+    fn identity() -> Self::Value {
+        None
+    }
+
+    fn combine(a: &Self::Value, b: &Self::Value) -> Self::Value {
+        match (a, b) {
+            (Some(x), Some(y)) => Some(x.min(y).clone()),
+            (Some(_), None) => a.clone(),
+            (None, _) => b.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> Idempotent for ArgMin<T> {}
+
+const BLOCK: usize = u64::BITS as usize;
+
+/// Range minimum queries in O(1) time after O(n) preprocessing
+///
+/// ```rust
+/// use algorithms_rs::datastruct::CartesianRmq;
+///
+/// let rmq = CartesianRmq::from_slice(&[5, 2, 8, 2, 9, 1]);
+///
+/// assert_eq!(rmq.argmin(0..5), Some(1));
+/// assert_eq!(rmq.argmin(2..5), Some(3));
+/// assert_eq!(rmq.min(..), Some(&1));
+/// assert_eq!(rmq.min(3..3), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CartesianRmq<T: Ord + Clone> {
+    values: Vec<T>,
+    // bit k of masks[i] is set if element (i rounded down to a block) + k
+    // is on the right spine of the Cartesian tree of its block up to i
+    masks: Vec<u64>,
+    blocks: SparseTable<ArgMin<T>>,
+}
+
+impl<T: Ord + Clone> CartesianRmq<T> {
+    /// Creating the structure over `values` in O(n) time
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut masks = vec![0u64; values.len()];
+        let mut block_minimums = Vec::with_capacity(values.len().div_ceil(BLOCK));
+        for (b, block) in values.chunks(BLOCK).enumerate() {
+            let start = b * BLOCK;
+            let mut spine = 0u64;
+            for (k, value) in block.iter().enumerate() {
+                // pop the spine elements larger than the new one
+                while spine != 0 {
+                    let top = (u64::BITS - 1 - spine.leading_zeros()) as usize;
+                    if block[top] <= *value {
+                        break;
+                    }
+                    spine &= !(1 << top);
+                }
+                spine |= 1 << k;
+                masks[start + k] = spine;
+            }
+            let k = masks[start + block.len() - 1].trailing_zeros() as usize;
+            block_minimums.push(Some((block[k].clone(), start + k)));
+        }
+        Self {
+            values: values.to_vec(),
+            masks,
+            blocks: SparseTable::from_slice(&block_minimums),
+        }
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Determine if there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // leftmost minimum of values[l..=r], both in the same block
+    fn in_block(&self, l: usize, r: usize) -> Option<(T, usize)> {
+        let start = l - l % BLOCK;
+        let spine = self.masks[r] & (u64::MAX << (l - start));
+        let i = start + spine.trailing_zeros() as usize;
+        Some((self.values[i].clone(), i))
+    }
+
+    /// Index of the leftmost minimum in `range`, None for an empty range
+    pub fn argmin<R: RangeBounds<usize>>(&self, range: R) -> Option<usize> {
+        let (l, r) = to_bounds(range, self.len());
+        if l == r {
+            return None;
+        }
+        let r = r - 1;
+        let (lb, rb) = (l / BLOCK, r / BLOCK);
+        if lb == rb {
+            return self.in_block(l, r).map(|(_, i)| i);
+        }
+        let left = self.in_block(l, (lb + 1) * BLOCK - 1);
+        let middle = self.blocks.query(lb + 1..rb);
+        let right = self.in_block(rb * BLOCK, r);
+        let best = ArgMin::combine(&ArgMin::combine(&left, &middle), &right);
+        best.map(|(_, i)| i)
+    }
+
+    /// Smallest element in `range`, None for an empty range
+    pub fn min<R: RangeBounds<usize>>(&self, range: R) -> Option<&T> {
+        self.argmin(range).map(|i| &self.values[i])
+    }
+}
+
+/// Parent of every element in the Cartesian tree of `values`, in O(n) time
+///
+/// The root is the leftmost minimum and the subtrees to its left and right
+/// are the Cartesian trees of the elements before and after it.
+///
+/// ```rust
+/// use algorithms_rs::datastruct::{cartesian_tree_parents, RootedTree};
+///
+/// let parents = cartesian_tree_parents(&[3, 1, 4, 1, 5]);
+///
+/// assert_eq!(parents, vec![Some(1), None, Some(3), Some(1), Some(3)]);
+/// assert_eq!(RootedTree::from_parents(&parents).unwrap().root(), 1);
+/// ```
+pub fn cartesian_tree_parents<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    let mut parents = vec![None; values.len()];
+    let mut spine: Vec<usize> = Vec::new();
+    for i in 0..values.len() {
+        let mut last = None;
+        while let Some(&top) = spine.last() {
+            if values[top] <= values[i] {
+                break;
+            }
+            last = spine.pop();
+        }
+        // the popped chain becomes the left subtree of i
+        if let Some(last) = last {
+            parents[last] = Some(i);
+        }
+        if let Some(&top) = spine.last() {
+            parents[i] = Some(top);
+        }
+        spine.push(i);
+    }
+    parents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn naive_argmin(values: &[i32], l: usize, r: usize) -> Option<usize> {
+        (l..r).min_by_key(|&i| (values[i], i))
+    }
+
+    #[test]
+    fn test_sparse_table_against_scan() {
+        let mut rng = StdRng::seed_from_u64(40);
+        for len in 0..70 {
+            let values: Vec<u32> = (0..len).map(|_| rng.gen_range(1..1000) * 6).collect();
+            let mins = SparseTable::<MinMonoid<u32>>::from_slice(&values);
+            let maxs = SparseTable::<MaxMonoid<u32>>::from_slice(&values);
+            let gcds = SparseTable::<GcdMonoid<u32>>::from_slice(&values);
+            for l in 0..=len {
+                for r in l..=len {
+                    let range = &values[l..r];
+                    assert_eq!(mins.query(l..r), *range.iter().min().unwrap_or(&u32::MAX));
+                    assert_eq!(maxs.query(l..r), *range.iter().max().unwrap_or(&0));
+                    let gcd = range.iter().fold(0, |g, v| GcdMonoid::combine(&g, v));
+                    assert_eq!(gcds.query(l..r), gcd);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cartesian_rmq_across_blocks() {
+        let mut rng = StdRng::seed_from_u64(400);
+        for len in [0, 1, 63, 64, 65, 200, 1000] {
+            // few distinct values so ties are common
+            let values: Vec<i32> = (0..len).map(|_| rng.gen_range(0..20)).collect();
+            let rmq = CartesianRmq::from_slice(&values);
+            for _ in 0..2000 {
+                let l = rng.gen_range(0..=len);
+                let r = rng.gen_range(l..=len);
+                assert_eq!(rmq.argmin(l..r), naive_argmin(&values, l, r));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cartesian_tree_shape() {
+        let mut rng = StdRng::seed_from_u64(4000);
+        let values: Vec<i32> = (0..300).map(|_| rng.gen_range(0..50)).collect();
+        let parents = cartesian_tree_parents(&values);
+        let tree = crate::datastruct::RootedTree::from_parents(&parents).unwrap();
+        assert_eq!(Some(tree.root()), naive_argmin(&values, 0, values.len()));
+        for v in 0..values.len() {
+            // heap ordered, and every subtree is a contiguous range whose
+            // leftmost minimum is its root
+            let mut lo = v;
+            let mut hi = v;
+            let mut stack = vec![v];
+            while let Some(u) = stack.pop() {
+                lo = lo.min(u);
+                hi = hi.max(u);
+                stack.extend(tree.children(u));
+            }
+            assert_eq!(naive_argmin(&values, lo, hi + 1), Some(v));
+        }
+    }
+}
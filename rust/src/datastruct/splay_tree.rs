@@ -0,0 +1,567 @@
+//! Splay tree
+//!
+//! A self-adjusting binary search tree: every access moves the accessed key
+//! to the root, so keys that are used often stay close to the top. No balance
+//! information is stored, yet any sequence of m operations on a tree of n
+//! keys takes O(m lg n) time, which makes each operation O(lg n) amortized.
+//!
+//! Splaying is done top-down in a single pass: the nodes passed on the way
+//! down are split off into a left tree of smaller keys and a right tree of
+//! larger keys, and are hung below the accessed node once it is found.
+//! Every node also keeps the size of its subtree, which is fixed up as the
+//! two trees are reassembled, so a split knows how many keys it moved.
+use core::cmp::Ordering;
+use core::fmt;
+
+type Link<K, V> = Option<Box<SplayNode<K, V>>>;
+
+#[derive(Debug)]
+struct SplayNode<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    // number of nodes in the subtree rooted here
+    size: usize,
+}
+
+impl<K, V> SplayNode<K, V> {
+    fn update_size(&mut self) {
+        self.size = size(&self.left) + size(&self.right) + 1;
+    }
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// Operation that triggered a splay, reported to the stats hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplayOperation {
+    Get,
+    Insert,
+    Remove,
+    Split,
+    Join,
+}
+
+/// Moves the node selected by `cmp` to the root, or the last node on the
+/// search path if there is none, and returns the number of rotations
+///
+/// `cmp` tells how the wanted key compares to the key of a node.
+fn splay<K, V, F>(mut t: Box<SplayNode<K, V>>, cmp: F) -> (Box<SplayNode<K, V>>, usize)
+where
+    F: Fn(&K) -> Ordering,
+{
+    // nodes smaller than the wanted key, each one's right child is still to be linked
+    let mut left_nodes: Vec<Box<SplayNode<K, V>>> = Vec::new();
+    // nodes larger than the wanted key, each one's left child is still to be linked
+    let mut right_nodes: Vec<Box<SplayNode<K, V>>> = Vec::new();
+    let mut rotations = 0;
+    loop {
+        match cmp(&t.key) {
+            Ordering::Equal => break,
+            Ordering::Less => {
+                let Some(mut l) = t.left.take() else {
+                    break;
+                };
+                if cmp(&l.key) == Ordering::Less {
+                    // zig-zig: rotate right
+                    t.left = l.right.take();
+                    t.update_size();
+                    l.right = Some(t);
+                    t = l;
+                    rotations += 1;
+                    match t.left.take() {
+                        None => break,
+                        Some(next) => {
+                            right_nodes.push(t);
+                            t = next;
+                        }
+                    }
+                } else {
+                    // link right
+                    right_nodes.push(t);
+                    t = l;
+                }
+            }
+            Ordering::Greater => {
+                let Some(mut r) = t.right.take() else {
+                    break;
+                };
+                if cmp(&r.key) == Ordering::Greater {
+                    // zag-zag: rotate left
+                    t.right = r.left.take();
+                    t.update_size();
+                    r.left = Some(t);
+                    t = r;
+                    rotations += 1;
+                    match t.right.take() {
+                        None => break,
+                        Some(next) => {
+                            left_nodes.push(t);
+                            t = next;
+                        }
+                    }
+                } else {
+                    // link left
+                    left_nodes.push(t);
+                    t = r;
+                }
+            }
+        }
+    }
+
+    // assemble: the left tree takes t.left as its rightmost subtree and the
+    // right tree takes t.right as its leftmost subtree, sizes are fixed
+    // bottom-up as every node on the path gets its new child
+    let mut left = t.left.take();
+    while let Some(mut node) = left_nodes.pop() {
+        node.right = left;
+        node.update_size();
+        left = Some(node);
+    }
+    let mut right = t.right.take();
+    while let Some(mut node) = right_nodes.pop() {
+        node.left = right;
+        node.update_size();
+        right = Some(node);
+    }
+    t.left = left;
+    t.right = right;
+    t.update_size();
+    (t, rotations)
+}
+
+/// Splay tree ordered map
+///
+/// Lookups go through `&mut self` because they restructure the tree.
+///
+/// ```rust
+/// use algorithms_rs::datastruct::SplayTree;
+///
+/// let mut tree = SplayTree::new();
+///
+/// tree.insert(3, "c");
+/// tree.insert(1, "a");
+/// tree.insert(2, "b");
+///
+/// assert_eq!(tree.get(&1), Some(&"a"));
+/// assert_eq!(tree.root_key(), Some(&1));
+/// ```
+pub struct SplayTree<K, V> {
+    root: Link<K, V>,
+    last_rotations: usize,
+    total_rotations: usize,
+    stats_hook: Option<Box<dyn FnMut(SplayOperation, usize)>>,
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SplayTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SplayTree")
+            .field("root", &self.root)
+            .field("len", &size(&self.root))
+            .field("last_rotations", &self.last_rotations)
+            .field("total_rotations", &self.total_rotations)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> Drop for SplayTree<K, V> {
+    fn drop(&mut self) {
+        // a splay tree can degenerate into a path, free it without recursion
+        let mut stack: Vec<Box<SplayNode<K, V>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<K: Ord, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SplayTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+impl<K: Ord, V> SplayTree<K, V> {
+    /// Creating an empty splay tree
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            last_rotations: 0,
+            total_rotations: 0,
+            stats_hook: None,
+        }
+    }
+
+    /// Number of keys in the tree
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Determine if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Key at the root, the most recently accessed one
+    pub fn root_key(&self) -> Option<&K> {
+        self.root.as_ref().map(|node| &node.key)
+    }
+
+    /// Rotations done by the last operation
+    pub fn last_rotations(&self) -> usize {
+        self.last_rotations
+    }
+
+    /// Rotations done since the tree was created
+    pub fn total_rotations(&self) -> usize {
+        self.total_rotations
+    }
+
+    /// Call `hook` after every operation with the number of rotations it did
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::{SplayOperation, SplayTree};
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    /// let sink = Rc::clone(&log);
+    ///
+    /// let mut tree: SplayTree<i32, ()> = (0..8).map(|k| (k, ())).collect();
+    /// tree.set_stats_hook(move |op, rotations| sink.borrow_mut().push((op, rotations)));
+    ///
+    /// tree.get(&0);
+    ///
+    /// assert_eq!(log.borrow()[0], (SplayOperation::Get, 3));
+    /// ```
+    pub fn set_stats_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(SplayOperation, usize) + 'static,
+    {
+        self.stats_hook = Some(Box::new(hook));
+    }
+
+    /// Stop reporting operations to the stats hook
+    pub fn clear_stats_hook(&mut self) {
+        self.stats_hook = None;
+    }
+
+    fn record(&mut self, operation: SplayOperation, rotations: usize) {
+        self.last_rotations = rotations;
+        self.total_rotations += rotations;
+        if let Some(hook) = self.stats_hook.as_mut() {
+            hook(operation, rotations);
+        }
+    }
+
+    // splay the root with `cmp`, returns the rotations and whether the root matches
+    fn splay_root<F>(&mut self, cmp: F) -> (usize, bool)
+    where
+        F: Fn(&K) -> Ordering,
+    {
+        match self.root.take() {
+            None => (0, false),
+            Some(root) => {
+                let (root, rotations) = splay(root, &cmp);
+                let found = cmp(&root.key) == Ordering::Equal;
+                self.root = Some(root);
+                (rotations, found)
+            }
+        }
+    }
+
+    /// Find the value stored under `key` and splay it to the root
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let (rotations, found) = self.splay_root(|k| key.cmp(k));
+        self.record(SplayOperation::Get, rotations);
+        if found {
+            self.root.as_ref().map(|node| &node.value)
+        } else {
+            None
+        }
+    }
+
+    /// Mutable reference to the value stored under `key`, splaying it to the root
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (rotations, found) = self.splay_root(|k| key.cmp(k));
+        self.record(SplayOperation::Get, rotations);
+        if found {
+            self.root.as_mut().map(|node| &mut node.value)
+        } else {
+            None
+        }
+    }
+
+    /// Determine if the tree contains `key`, splaying the search path
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Insert a key-value pair, returning the old value if `key` was present
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (rotations, found) = self.splay_root(|k| key.cmp(k));
+        self.record(SplayOperation::Insert, rotations);
+        if found {
+            let root = self.root.as_mut().expect("found key is at the root");
+            return Some(core::mem::replace(&mut root.value, value));
+        }
+
+        let mut node = Box::new(SplayNode {
+            key,
+            value,
+            left: None,
+            right: None,
+            size: 1,
+        });
+        if let Some(mut root) = self.root.take() {
+            // the root is the predecessor or the successor of the new key
+            if node.key < root.key {
+                node.left = root.left.take();
+                root.update_size();
+                node.right = Some(root);
+            } else {
+                node.right = root.right.take();
+                root.update_size();
+                node.left = Some(root);
+            }
+            node.update_size();
+        }
+        self.root = Some(node);
+        None
+    }
+
+    /// Remove `key` from the tree, returning its value
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (mut rotations, found) = self.splay_root(|k| key.cmp(k));
+        if !found {
+            self.record(SplayOperation::Remove, rotations);
+            return None;
+        }
+        let mut root = self.root.take().expect("found key is at the root");
+        self.root = match root.left.take() {
+            None => root.right.take(),
+            Some(left) => {
+                // every key on the left is smaller, so the maximum ends up at the root
+                let (mut left, more) = splay(left, |_| Ordering::Greater);
+                rotations += more;
+                left.right = root.right.take();
+                left.update_size();
+                Some(left)
+            }
+        };
+        self.record(SplayOperation::Remove, rotations);
+        Some(root.value)
+    }
+
+    /// Move the keys greater than or equal to `key` into a new tree
+    ///
+    /// Takes O(lg n) amortized time, the subtree sizes give both lengths.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::SplayTree;
+    ///
+    /// let mut tree: SplayTree<i32, ()> = (0..10).map(|k| (k, ())).collect();
+    /// let mut upper = tree.split(&6);
+    ///
+    /// assert_eq!(tree.len(), 6);
+    /// assert_eq!(upper.len(), 4);
+    ///
+    /// tree.join(upper);
+    ///
+    /// assert_eq!(tree.len(), 10);
+    /// ```
+    pub fn split(&mut self, key: &K) -> Self {
+        // splay the smallest key >= key, or the largest key below it
+        let (rotations, _) = self.splay_root(|k| match key.cmp(k) {
+            Ordering::Greater => Ordering::Greater,
+            _ => Ordering::Less,
+        });
+        self.record(SplayOperation::Split, rotations);
+
+        let mut other = Self::new();
+        let Some(mut root) = self.root.take() else {
+            return other;
+        };
+        if root.key < *key {
+            other.root = root.right.take();
+            root.update_size();
+            self.root = Some(root);
+        } else {
+            self.root = root.left.take();
+            root.update_size();
+            other.root = Some(root);
+        }
+        other
+    }
+
+    /// Append a tree whose keys are all greater than the keys of `self`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the largest key of `self` is not less than the smallest key of `other`.
+    pub fn join(&mut self, mut other: Self) {
+        let (rotations, _) = self.splay_root(|_| Ordering::Greater);
+        self.record(SplayOperation::Join, rotations);
+        let Some(mut root) = self.root.take() else {
+            self.root = other.root.take();
+            return;
+        };
+        if let Some(min) = other.minimum() {
+            assert!(root.key < *min, "joined splay trees have overlapping keys");
+        }
+        // the maximum is at the root, so its right subtree is empty
+        root.right = other.root.take();
+        root.update_size();
+        self.root = Some(root);
+    }
+
+    /// Smallest key in the tree, without splaying
+    pub fn minimum(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = node.left.as_ref() {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    /// Largest key in the tree, without splaying
+    pub fn maximum(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = node.right.as_ref() {
+            node = right;
+        }
+        Some(&node.key)
+    }
+
+    /// In-order walk of the tree, without splaying
+    pub fn inorder_tree_walk(&self) -> Vec<(&K, &V)> {
+        let mut result = Vec::with_capacity(self.len());
+        let mut stack = Vec::new();
+        let mut node = self.root.as_deref();
+        while node.is_some() || !stack.is_empty() {
+            while let Some(n) = node {
+                stack.push(n);
+                node = n.left.as_deref();
+            }
+            if let Some(n) = stack.pop() {
+                result.push((&n.key, &n.value));
+                node = n.right.as_deref();
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    // checks that every node holds the size of its subtree
+    fn check_sizes<K, V>(tree: &SplayTree<K, V>) {
+        let mut stack: Vec<&SplayNode<K, V>> = tree.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            assert_eq!(node.size, size(&node.left) + size(&node.right) + 1);
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+    }
+
+    #[test]
+    fn test_random_against_btree_map() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let mut tree = SplayTree::new();
+        let mut expected = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.gen_range(0..400);
+            match rng.gen_range(0..3) {
+                0 => assert_eq!(tree.insert(key, key * 3), expected.insert(key, key * 3)),
+                1 => assert_eq!(tree.remove(&key), expected.remove(&key)),
+                _ => {
+                    assert_eq!(tree.get(&key), expected.get(&key));
+                    if expected.contains_key(&key) {
+                        assert_eq!(tree.root_key(), Some(&key));
+                    }
+                }
+            }
+            assert_eq!(tree.len(), expected.len());
+            check_sizes(&tree);
+        }
+        assert_eq!(
+            tree.inorder_tree_walk(),
+            expected.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_skewed_access_needs_few_rotations() {
+        let mut tree: SplayTree<i32, ()> = (0..1000).map(|k| (k, ())).collect();
+        tree.get(&500);
+        let before = tree.total_rotations();
+        for _ in 0..100 {
+            tree.get(&500);
+            assert_eq!(tree.last_rotations(), 0);
+        }
+        assert_eq!(tree.total_rotations(), before);
+    }
+
+    #[test]
+    fn test_stats_hook_sees_every_operation() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let mut tree = SplayTree::new();
+        tree.set_stats_hook(move |_, _| counter.set(counter.get() + 1));
+        tree.insert(1, ());
+        tree.insert(2, ());
+        tree.get(&1);
+        tree.remove(&2);
+        assert_eq!(calls.get(), 4);
+        tree.clear_stats_hook();
+        tree.get(&1);
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        let mut tree: SplayTree<i32, i32> = (0..50).map(|k| (k * 2, k)).collect();
+        let upper = tree.split(&51);
+        check_sizes(&tree);
+        check_sizes(&upper);
+        assert_eq!(upper.len(), 24);
+        assert_eq!(tree.maximum(), Some(&50));
+        assert_eq!(upper.minimum(), Some(&52));
+        assert_eq!(tree.len() + upper.len(), 50);
+        tree.join(upper);
+        assert_eq!(tree.len(), 50);
+        assert_eq!(tree.get(&98), Some(&49));
+
+        let mut empty = SplayTree::<i32, i32>::new();
+        assert!(empty.split(&1).is_empty());
+        empty.join(tree);
+        assert_eq!(empty.len(), 50);
+    }
+
+    #[test]
+    fn test_degenerate_tree_drop() {
+        // sequential inserts build a path as long as the tree
+        let tree: SplayTree<u32, ()> = (0..200_000).map(|k| (k, ())).collect();
+        assert_eq!(tree.len(), 200_000);
+    }
+}
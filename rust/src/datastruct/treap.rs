@@ -0,0 +1,630 @@
+//! Treap (Introduction to Algorithms problem 13-4)
+//!
+//! A binary search tree in which every node also carries a random priority
+//! and the priorities obey the max-heap property. The shape of the tree is the
+//! one a BST gets when the keys are inserted in random order, so its expected
+//! height is O(lg n) whatever the order of the operations.
+//!
+//! Everything is built from two primitives: `split` cuts a treap into two and
+//! `merge` joins two treaps whose keys do not interleave. Ordering the nodes
+//! by their position instead of a key gives the implicit treap, a sequence
+//! with O(lg n) insertion, removal and reversal at any index.
+use super::BinaryTreeView;
+use core::fmt::Display;
+use core::ops::{Bound, RangeBounds};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+type Link<T> = Option<Box<TreapNode<T>>>;
+
+/// Node of a treap, only visible as a [`BinaryTreeView`] handle
+#[derive(Debug)]
+pub struct TreapNode<T> {
+    value: T,
+    priority: u64,
+    size: usize,
+    // the subtree still has to be reversed, only used by the implicit treap
+    reversed: bool,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> TreapNode<T> {
+    fn new(value: T, priority: u64) -> Box<Self> {
+        Box::new(Self {
+            value,
+            priority,
+            size: 1,
+            reversed: false,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    fn push_down(&mut self) {
+        if self.reversed {
+            core::mem::swap(&mut self.left, &mut self.right);
+            for child in [&mut self.left, &mut self.right].into_iter().flatten() {
+                child.reversed ^= true;
+            }
+            self.reversed = false;
+        }
+    }
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// Join two treaps, every node of `left` goes before every node of `right`
+fn merge<T>(left: Link<T>, right: Link<T>) -> Link<T> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.push_down();
+                left.right = merge(left.right.take(), Some(right));
+                left.update();
+                Some(left)
+            } else {
+                right.push_down();
+                right.left = merge(Some(left), right.left.take());
+                right.update();
+                Some(right)
+            }
+        }
+    }
+}
+
+/// Cut off the nodes with `value < key` into the first treap
+fn split_by_key<T: Ord>(link: Link<T>, key: &T) -> (Link<T>, Link<T>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            if node.value < *key {
+                let (left, right) = split_by_key(node.right.take(), key);
+                node.right = left;
+                node.update();
+                (Some(node), right)
+            } else {
+                let (left, right) = split_by_key(node.left.take(), key);
+                node.left = right;
+                node.update();
+                (left, Some(node))
+            }
+        }
+    }
+}
+
+/// Cut off the first `k` nodes into the first treap
+fn split_by_size<T>(link: Link<T>, k: usize) -> (Link<T>, Link<T>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            node.push_down();
+            let left_size = size(&node.left);
+            if left_size < k {
+                let (left, right) = split_by_size(node.right.take(), k - left_size - 1);
+                node.right = left;
+                node.update();
+                (Some(node), right)
+            } else {
+                let (left, right) = split_by_size(node.left.take(), k);
+                node.left = right;
+                node.update();
+                (left, Some(node))
+            }
+        }
+    }
+}
+
+// in-order walk honoring reversal flags that were not pushed down yet
+fn collect<'a, T>(link: &'a Link<T>, flip: bool, result: &mut Vec<&'a T>) {
+    if let Some(node) = link {
+        let flip = flip ^ node.reversed;
+        let (first, second) = if flip {
+            (&node.right, &node.left)
+        } else {
+            (&node.left, &node.right)
+        };
+        collect(first, flip, result);
+        result.push(&node.value);
+        collect(second, flip, result);
+    }
+}
+
+/// Treap ordered set
+///
+/// ```rust
+/// use algorithms_rs::datastruct::Treap;
+///
+/// let treap: Treap<i32> = (1..=6).collect();
+///
+/// let (small, large) = treap.split(&4);
+///
+/// assert_eq!(small.inorder_tree_walk(), vec![&1, &2, &3]);
+/// assert_eq!(large.inorder_tree_walk(), vec![&4, &5, &6]);
+///
+/// let treap = small.merge(large);
+///
+/// assert_eq!(treap.len(), 6);
+/// ```
+#[derive(Debug)]
+pub struct Treap<K> {
+    root: Link<K>,
+    rng: StdRng,
+}
+
+impl<K: Ord> Default for Treap<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> FromIterator<K> for Treap<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut treap = Self::new();
+        for key in iter {
+            treap.insert(key);
+        }
+        treap
+    }
+}
+
+impl<K: Ord> Treap<K> {
+    /// Creating an empty treap with priorities drawn from system entropy
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Creating an empty treap whose priorities are reproducible from `seed`
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::Treap;
+    ///
+    /// let mut treap = Treap::with_seed(7);
+    ///
+    /// treap.insert(1);
+    ///
+    /// assert_eq!(treap.contains(&1), true);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Number of keys in the treap
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Determine if the treap is empty
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Determine if the treap contains `key`
+    pub fn contains(&self, key: &K) -> bool {
+        let mut node = self.root.as_ref();
+        while let Some(n) = node {
+            if n.value == *key {
+                return true;
+            } else if n.value > *key {
+                node = n.left.as_ref();
+            } else {
+                node = n.right.as_ref();
+            }
+        }
+        false
+    }
+
+    /// Insert `key`, returning false if it was already present
+    pub fn insert(&mut self, key: K) -> bool {
+        if self.contains(&key) {
+            return false;
+        }
+        let (left, right) = split_by_key(self.root.take(), &key);
+        let node = TreapNode::new(key, self.rng.gen());
+        self.root = merge(merge(left, Some(node)), right);
+        true
+    }
+
+    /// Remove `key`, returning it if it was present
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::Treap;
+    ///
+    /// let mut treap: Treap<i32> = vec![3, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(treap.remove(&2), Some(2));
+    /// assert_eq!(treap.remove(&2), None);
+    /// ```
+    pub fn remove(&mut self, key: &K) -> Option<K> {
+        let (left, right) = split_by_key(self.root.take(), key);
+        // right starts with the smallest key >= key
+        let (first, rest) = split_by_size(right, 1);
+        match first {
+            Some(node) if node.value == *key => {
+                self.root = merge(left, rest);
+                Some(node.value)
+            }
+            first => {
+                self.root = merge(left, merge(first, rest));
+                None
+            }
+        }
+    }
+
+    /// Smallest key in the treap
+    pub fn minimum(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = node.left.as_ref() {
+            node = left;
+        }
+        Some(&node.value)
+    }
+
+    /// Largest key in the treap
+    pub fn maximum(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = node.right.as_ref() {
+            node = right;
+        }
+        Some(&node.value)
+    }
+
+    /// Split into the keys less than `key` and the keys greater than or equal to it
+    pub fn split(mut self, key: &K) -> (Self, Self) {
+        let (left, right) = split_by_key(self.root.take(), key);
+        let other = Self {
+            root: right,
+            rng: StdRng::seed_from_u64(self.rng.gen()),
+        };
+        self.root = left;
+        (self, other)
+    }
+
+    /// Concatenate with a treap whose keys are all greater than the keys of `self`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the largest key of `self` is not less than the smallest key of `other`.
+    pub fn merge(mut self, mut other: Self) -> Self {
+        if let (Some(max), Some(min)) = (self.maximum(), other.minimum()) {
+            assert!(max < min, "merged treaps have overlapping keys");
+        }
+        self.root = merge(self.root.take(), other.root.take());
+        self
+    }
+
+    /// In-order walk of the treap
+    pub fn inorder_tree_walk(&self) -> Vec<&K> {
+        let mut result = Vec::with_capacity(self.len());
+        collect(&self.root, false, &mut result);
+        result
+    }
+}
+
+/// Implicit treap, a sequence ordered by position instead of by key
+///
+/// ```rust
+/// use algorithms_rs::datastruct::ImplicitTreap;
+///
+/// let mut seq: ImplicitTreap<char> = "abcdef".chars().collect();
+///
+/// seq.reverse(1..4);
+/// seq.insert(0, 'z');
+///
+/// assert_eq!(seq.remove(6), 'f');
+/// assert_eq!(seq.inorder_tree_walk().into_iter().collect::<String>(), "zadcbe");
+/// ```
+#[derive(Debug)]
+pub struct ImplicitTreap<T> {
+    root: Link<T>,
+    rng: StdRng,
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for ImplicitTreap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut treap = Self::new();
+        for value in iter {
+            treap.push(value);
+        }
+        treap
+    }
+}
+
+impl<T> ImplicitTreap<T> {
+    /// Creating an empty sequence with priorities drawn from system entropy
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Creating an empty sequence whose priorities are reproducible from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: None,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Number of elements in the sequence
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Determine if the sequence is empty
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Element at `index`
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let mut node = self.root.as_ref();
+        let mut index = index;
+        let mut flip = false;
+        while let Some(n) = node {
+            flip ^= n.reversed;
+            let (first, second) = if flip {
+                (&n.right, &n.left)
+            } else {
+                (&n.left, &n.right)
+            };
+            let first_size = size(first);
+            if index < first_size {
+                node = first.as_ref();
+            } else if index == first_size {
+                return Some(&n.value);
+            } else {
+                index -= first_size + 1;
+                node = second.as_ref();
+            }
+        }
+        None
+    }
+
+    /// Append an element to the end of the sequence
+    pub fn push(&mut self, value: T) {
+        let node = TreapNode::new(value, self.rng.gen());
+        self.root = merge(self.root.take(), Some(node));
+    }
+
+    /// Insert an element at `index`, shifting every element after it to the right
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "insertion index is out of bounds");
+        let (left, right) = split_by_size(self.root.take(), index);
+        let node = TreapNode::new(value, self.rng.gen());
+        self.root = merge(merge(left, Some(node)), right);
+    }
+
+    /// Remove and return the element at `index`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len(), "removal index is out of bounds");
+        let (left, right) = split_by_size(self.root.take(), index);
+        let (middle, right) = split_by_size(right, 1);
+        self.root = merge(left, right);
+        middle.expect("index is in bounds").value
+    }
+
+    /// Reverse the elements in `range`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn reverse<R: RangeBounds<usize>>(&mut self, range: R) {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "reversed range is out of bounds"
+        );
+        let (left, right) = split_by_size(self.root.take(), end);
+        let (left, mut middle) = split_by_size(left, start);
+        if let Some(node) = middle.as_mut() {
+            node.reversed ^= true;
+        }
+        self.root = merge(merge(left, middle), right);
+    }
+
+    /// Split the sequence at `at`, returning the elements from `at` onwards
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "split index is out of bounds");
+        let (left, right) = split_by_size(self.root.take(), at);
+        self.root = left;
+        Self {
+            root: right,
+            rng: StdRng::seed_from_u64(self.rng.gen()),
+        }
+    }
+
+    /// Move every element of `other` to the end of the sequence
+    pub fn append(&mut self, other: &mut Self) {
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    /// Elements of the sequence in order
+    pub fn inorder_tree_walk(&self) -> Vec<&T> {
+        let mut result = Vec::with_capacity(self.len());
+        collect(&self.root, false, &mut result);
+        result
+    }
+}
+
+impl<K: Display> BinaryTreeView for Treap<K> {
+    type Handle<'a>
+        = &'a TreapNode<K>
+    where
+        K: 'a;
+
+    fn root(&self) -> Option<&TreapNode<K>> {
+        self.root.as_deref()
+    }
+
+    fn left<'a>(&'a self, node: &'a TreapNode<K>) -> Option<&'a TreapNode<K>> {
+        node.left.as_deref()
+    }
+
+    fn right<'a>(&'a self, node: &'a TreapNode<K>) -> Option<&'a TreapNode<K>> {
+        node.right.as_deref()
+    }
+
+    fn label(&self, node: &TreapNode<K>) -> String {
+        node.value.to_string()
+    }
+
+    fn annotations(&self, node: &TreapNode<K>) -> Vec<(&'static str, String)> {
+        vec![
+            ("priority", node.priority.to_string()),
+            ("size", node.size.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    // checks sizes and the heap property of the priorities
+    fn check<T>(link: &Link<T>) {
+        if let Some(node) = link {
+            assert_eq!(node.size, 1 + size(&node.left) + size(&node.right));
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                assert!(child.priority <= node.priority);
+            }
+            check(&node.left);
+            check(&node.right);
+        }
+    }
+
+    #[test]
+    fn test_treap_random_against_btree_set() {
+        let mut rng = StdRng::seed_from_u64(30);
+        let mut treap = Treap::with_seed(30);
+        let mut expected = BTreeSet::new();
+        for _ in 0..3000 {
+            let key = rng.gen_range(0..300);
+            if rng.gen_bool(0.6) {
+                assert_eq!(treap.insert(key), expected.insert(key));
+            } else {
+                assert_eq!(treap.remove(&key), expected.take(&key));
+            }
+            check(&treap.root);
+        }
+        assert_eq!(
+            treap.inorder_tree_walk(),
+            expected.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(treap.minimum(), expected.first());
+        assert_eq!(treap.maximum(), expected.last());
+    }
+
+    #[test]
+    fn test_treap_split_and_merge() {
+        let treap: Treap<i32> = (0..100).collect();
+        let (left, right) = treap.split(&40);
+        check(&left.root);
+        check(&right.root);
+        assert_eq!(left.len(), 40);
+        assert_eq!(right.minimum(), Some(&40));
+        let treap = left.merge(right);
+        assert_eq!(
+            treap.inorder_tree_walk(),
+            (0..100).collect::<Vec<_>>().iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_treap_merge_overlapping_panics() {
+        let left: Treap<i32> = vec![1, 5].into_iter().collect();
+        let right: Treap<i32> = vec![3, 7].into_iter().collect();
+        let _ = left.merge(right);
+    }
+
+    #[test]
+    fn test_implicit_treap_random_against_vec() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let mut seq = ImplicitTreap::with_seed(31);
+        let mut expected = Vec::new();
+        for step in 0..2000 {
+            match rng.gen_range(0..4) {
+                0 | 1 => {
+                    let index = rng.gen_range(0..=expected.len());
+                    seq.insert(index, step);
+                    expected.insert(index, step);
+                }
+                2 if !expected.is_empty() => {
+                    let index = rng.gen_range(0..expected.len());
+                    assert_eq!(seq.remove(index), expected.remove(index));
+                }
+                _ => {
+                    let start = rng.gen_range(0..=expected.len());
+                    let end = rng.gen_range(start..=expected.len());
+                    seq.reverse(start..end);
+                    expected[start..end].reverse();
+                }
+            }
+            check(&seq.root);
+        }
+        assert_eq!(seq.inorder_tree_walk(), expected.iter().collect::<Vec<_>>());
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(seq.get(index), Some(value));
+        }
+        assert_eq!(seq.get(expected.len()), None);
+    }
+
+    #[test]
+    fn test_implicit_treap_split_off_and_append() {
+        let mut seq: ImplicitTreap<i32> = (0..10).collect();
+        seq.reverse(..);
+        let mut tail = seq.split_off(4);
+        assert_eq!(seq.inorder_tree_walk(), vec![&9, &8, &7, &6]);
+        tail.reverse(..=1);
+        seq.append(&mut tail);
+        assert!(tail.is_empty());
+        assert_eq!(
+            seq.inorder_tree_walk(),
+            vec![&9, &8, &7, &6, &4, &5, &3, &2, &1, &0]
+        );
+    }
+}
@@ -0,0 +1,656 @@
+//! Tries for string keys
+//!
+//! A `Trie` has one node per byte of every key, so a lookup takes O(m) steps
+//! for a key of m bytes whatever the number of keys. A `RadixTree` compresses
+//! every chain of nodes with a single child into one edge labeled by several
+//! bytes, which saves most of the nodes when keys share long prefixes. Both
+//! keep their children sorted, so keys come out in lexicographic byte order.
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+struct TrieNode<V> {
+    value: Option<V>,
+    children: BTreeMap<u8, TrieNode<V>>,
+}
+
+impl<V> TrieNode<V> {
+    fn new() -> Self {
+        Self {
+            value: None,
+            children: BTreeMap::new(),
+        }
+    }
+}
+
+/// Trie with one node per key byte
+///
+/// ```rust
+/// use algorithms_rs::datastruct::Trie;
+///
+/// let mut trie = Trie::new();
+/// trie.insert("tea", 3);
+/// trie.insert("ten", 10);
+/// trie.insert("to", 7);
+///
+/// assert_eq!(trie.get("ten"), Some(&10));
+/// assert_eq!(trie.get("te"), None);
+/// assert_eq!(
+///     trie.prefix_iter("te").collect::<Vec<_>>(),
+///     vec![("tea".to_string(), &3), ("ten".to_string(), &10)]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Trie<V> {
+    root: TrieNode<V>,
+    len: usize,
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: AsRef<str>, V> FromIterator<(K, V)> for Trie<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut trie = Self::new();
+        for (key, value) in iter {
+            trie.insert(key.as_ref(), value);
+        }
+        trie
+    }
+}
+
+impl<V> Trie<V> {
+    /// Creating an empty trie
+    pub fn new() -> Self {
+        Self {
+            root: TrieNode::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of keys
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if the trie is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, key: &[u8]) -> Option<&TrieNode<V>> {
+        let mut node = &self.root;
+        for byte in key {
+            node = node.children.get(byte)?;
+        }
+        Some(node)
+    }
+
+    /// Value stored under `key`
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.node(key.as_bytes())?.value.as_ref()
+    }
+
+    /// Mutable reference to the value stored under `key`
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        for byte in key.as_bytes() {
+            node = node.children.get_mut(byte)?;
+        }
+        node.value.as_mut()
+    }
+
+    /// Determine if `key` is stored
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Insert a key-value pair, returning the old value if `key` was present
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for byte in key.as_bytes() {
+            node = node.children.entry(*byte).or_insert_with(TrieNode::new);
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Remove `key`, returning its value, and drop the nodes left without keys
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        fn remove<V>(node: &mut TrieNode<V>, key: &[u8]) -> Option<V> {
+            let Some((byte, rest)) = key.split_first() else {
+                return node.value.take();
+            };
+            let child = node.children.get_mut(byte)?;
+            let value = remove(child, rest)?;
+            if child.value.is_none() && child.children.is_empty() {
+                node.children.remove(byte);
+            }
+            Some(value)
+        }
+        let value = remove(&mut self.root, key.as_bytes())?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Longest stored key that is a prefix of `query`, with its value
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::Trie;
+    ///
+    /// let routes: Trie<&str> = [("/", "root"), ("/api", "api"), ("/api/v1", "v1")]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// assert_eq!(routes.longest_prefix_match("/api/v2/users"), Some(("/api", &"api")));
+    /// assert_eq!(routes.longest_prefix_match("static"), None);
+    /// ```
+    pub fn longest_prefix_match<'q>(&self, query: &'q str) -> Option<(&'q str, &V)> {
+        let mut node = &self.root;
+        let mut best = node.value.as_ref().map(|value| (0, value));
+        for (i, byte) in query.as_bytes().iter().enumerate() {
+            match node.children.get(byte) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(value) = &node.value {
+                best = Some((i + 1, value));
+            }
+        }
+        best.map(|(len, value)| (&query[..len], value))
+    }
+
+    /// All keys starting with `prefix` and their values, in lexicographic order
+    pub fn prefix_iter(&self, prefix: &str) -> impl Iterator<Item = (String, &V)> + '_ {
+        let mut stack = Vec::new();
+        if let Some(node) = self.node(prefix.as_bytes()) {
+            stack.push((prefix.as_bytes().to_vec(), node));
+        }
+        core::iter::from_fn(move || {
+            while let Some((key, node)) = stack.pop() {
+                for (byte, child) in node.children.iter().rev() {
+                    let mut child_key = key.clone();
+                    child_key.push(*byte);
+                    stack.push((child_key, child));
+                }
+                if let Some(value) = &node.value {
+                    return Some((into_string(key), value));
+                }
+            }
+            None
+        })
+    }
+
+    /// All keys and their values, in lexicographic order
+    pub fn iter(&self) -> impl Iterator<Item = (String, &V)> + '_ {
+        self.prefix_iter("")
+    }
+
+    /// Up to `limit` completions of `prefix`, highest `score` first
+    ///
+    /// Completions with equal scores are in lexicographic order.
+    ///
+    /// ```rust
+    /// use algorithms_rs::datastruct::Trie;
+    ///
+    /// let searches: Trie<u32> = [("rust", 90), ("ruby", 40), ("rust book", 90), ("go", 70)]
+    ///     .into_iter()
+    ///     .collect();
+    ///
+    /// let top: Vec<String> = searches
+    ///     .autocomplete("ru", 2, |count| *count)
+    ///     .into_iter()
+    ///     .map(|(key, _)| key)
+    ///     .collect();
+    ///
+    /// assert_eq!(top, vec!["rust", "rust book"]);
+    /// ```
+    pub fn autocomplete<S, F>(&self, prefix: &str, limit: usize, score: F) -> Vec<(String, &V)>
+    where
+        S: Ord,
+        F: Fn(&V) -> S,
+    {
+        rank(self.prefix_iter(prefix), limit, score)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RadixNode<V> {
+    // label of the edge from the parent, empty only for the root
+    label: Vec<u8>,
+    value: Option<V>,
+    // children by the first byte of their label
+    children: BTreeMap<u8, RadixNode<V>>,
+}
+
+impl<V> RadixNode<V> {
+    fn leaf(label: &[u8], value: Option<V>) -> Self {
+        Self {
+            label: label.to_vec(),
+            value,
+            children: BTreeMap::new(),
+        }
+    }
+
+    // fold a keyless node with a single child into that child
+    fn merge_with_only_child(&mut self) {
+        if self.value.is_some() || self.children.len() != 1 {
+            return;
+        }
+        let (_, child) = self.children.pop_first().expect("exactly one child");
+        self.label.extend_from_slice(&child.label);
+        self.value = child.value;
+        self.children = child.children;
+    }
+}
+
+/// Radix tree, a trie whose single-child chains are merged into one edge
+///
+/// ```rust
+/// use algorithms_rs::datastruct::RadixTree;
+///
+/// let mut tree = RadixTree::new();
+/// tree.insert("romane", 1);
+/// tree.insert("romanus", 2);
+/// tree.insert("romulus", 3);
+///
+/// assert_eq!(tree.get("romanus"), Some(&2));
+/// assert_eq!(tree.node_count(), 6);
+/// assert_eq!(tree.remove("romane"), Some(1));
+/// assert_eq!(tree.node_count(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RadixTree<V> {
+    root: RadixNode<V>,
+    len: usize,
+}
+
+impl<V> Default for RadixTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: AsRef<str>, V> FromIterator<(K, V)> for RadixTree<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (key, value) in iter {
+            tree.insert(key.as_ref(), value);
+        }
+        tree
+    }
+}
+
+impl<V> RadixTree<V> {
+    /// Creating an empty radix tree
+    pub fn new() -> Self {
+        Self {
+            root: RadixNode::leaf(&[], None),
+            len: 0,
+        }
+    }
+
+    /// Number of keys
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Determine if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of nodes including the root, O(n) for n keys
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            count += 1;
+            stack.extend(node.children.values());
+        }
+        count
+    }
+
+    fn node(&self, key: &[u8]) -> Option<&RadixNode<V>> {
+        let mut node = &self.root;
+        let mut rest = key;
+        while let Some(first) = rest.first() {
+            node = node.children.get(first)?;
+            rest = rest.strip_prefix(node.label.as_slice())?;
+        }
+        Some(node)
+    }
+
+    /// Value stored under `key`
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.node(key.as_bytes())?.value.as_ref()
+    }
+
+    /// Mutable reference to the value stored under `key`
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        let mut rest = key.as_bytes();
+        while let Some(first) = rest.first() {
+            node = node.children.get_mut(first)?;
+            rest = rest.strip_prefix(node.label.as_slice())?;
+        }
+        node.value.as_mut()
+    }
+
+    /// Determine if `key` is stored
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Insert a key-value pair, returning the old value if `key` was present
+    ///
+    /// ```no
+    /// INSERT(x, key, value)
+    ///     if key is empty
+    ///         x.value = value
+    ///     else if x has no child c whose label starts with key[0]
+    ///         add a leaf labeled key below x
+    ///     else if c.label is a prefix of key
+    ///         INSERT(c, key without c.label, value)
+    ///     else
+    ///         split c after the common prefix l of c.label and key
+    ///         INSERT(split node, key without l, value)
+    /// ```
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        let mut rest = key.as_bytes();
+        while let Some(&first) = rest.first() {
+            let Some(child) = node.children.get(&first) else {
+                node.children
+                    .insert(first, RadixNode::leaf(rest, Some(value)));
+                self.len += 1;
+                return None;
+            };
+            let common = child
+                .label
+                .iter()
+                .zip(rest)
+                .take_while(|(a, b)| a == b)
+                .count();
+            if common < child.label.len() {
+                let mut old = node.children.remove(&first).expect("child was found");
+                let mut split = RadixNode::leaf(&old.label[..common], None);
+                old.label.drain(..common);
+                split.children.insert(old.label[0], old);
+                node.children.insert(first, split);
+            }
+            node = node.children.get_mut(&first).expect("child was found");
+            rest = &rest[common..];
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Remove `key`, returning its value, and merge the nodes left with one child
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        fn remove<V>(node: &mut RadixNode<V>, key: &[u8]) -> Option<V> {
+            let Some(&first) = key.first() else {
+                return node.value.take();
+            };
+            let child = node.children.get_mut(&first)?;
+            let rest = key.strip_prefix(child.label.as_slice())?;
+            let value = remove(child, rest)?;
+            if child.value.is_none() && child.children.is_empty() {
+                node.children.remove(&first);
+            } else {
+                child.merge_with_only_child();
+            }
+            Some(value)
+        }
+        let value = remove(&mut self.root, key.as_bytes())?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Longest stored key that is a prefix of `query`, with its value
+    pub fn longest_prefix_match<'q>(&self, query: &'q str) -> Option<(&'q str, &V)> {
+        let mut node = &self.root;
+        let mut best = node.value.as_ref().map(|value| (0, value));
+        let mut matched = 0;
+        let bytes = query.as_bytes();
+        while let Some(first) = bytes.get(matched) {
+            let Some(child) = node.children.get(first) else {
+                break;
+            };
+            if !bytes[matched..].starts_with(&child.label) {
+                break;
+            }
+            matched += child.label.len();
+            node = child;
+            if let Some(value) = &node.value {
+                best = Some((matched, value));
+            }
+        }
+        best.map(|(len, value)| (&query[..len], value))
+    }
+
+    /// All keys starting with `prefix` and their values, in lexicographic order
+    pub fn prefix_iter(&self, prefix: &str) -> impl Iterator<Item = (String, &V)> + '_ {
+        let mut stack = Vec::new();
+        // the prefix may end in the middle of an edge
+        let mut node = &self.root;
+        let mut key = Vec::new();
+        let mut rest = prefix.as_bytes();
+        let found = loop {
+            let Some(first) = rest.first() else {
+                break true;
+            };
+            let Some(child) = node.children.get(first) else {
+                break false;
+            };
+            key.extend_from_slice(&child.label);
+            node = child;
+            if child.label.starts_with(rest) {
+                break true;
+            }
+            match rest.strip_prefix(child.label.as_slice()) {
+                Some(tail) => rest = tail,
+                None => break false,
+            }
+        };
+        if found {
+            stack.push((key, node));
+        }
+        core::iter::from_fn(move || {
+            while let Some((key, node)) = stack.pop() {
+                for child in node.children.values().rev() {
+                    let mut child_key = key.clone();
+                    child_key.extend_from_slice(&child.label);
+                    stack.push((child_key, child));
+                }
+                if let Some(value) = &node.value {
+                    return Some((into_string(key), value));
+                }
+            }
+            None
+        })
+    }
+
+    /// All keys and their values, in lexicographic order
+    pub fn iter(&self) -> impl Iterator<Item = (String, &V)> + '_ {
+        self.prefix_iter("")
+    }
+
+    /// Up to `limit` completions of `prefix`, highest `score` first
+    ///
+    /// Completions with equal scores are in lexicographic order.
+    pub fn autocomplete<S, F>(&self, prefix: &str, limit: usize, score: F) -> Vec<(String, &V)>
+    where
+        S: Ord,
+        F: Fn(&V) -> S,
+    {
+        rank(self.prefix_iter(prefix), limit, score)
+    }
+}
+
+// keys are only ever built from whole `&str` keys
+fn into_string(key: Vec<u8>) -> String {
+    String::from_utf8(key).expect("stored keys are valid UTF-8")
+}
+
+fn rank<'a, V, S, F>(
+    entries: impl Iterator<Item = (String, &'a V)>,
+    limit: usize,
+    score: F,
+) -> Vec<(String, &'a V)>
+where
+    S: Ord,
+    F: Fn(&V) -> S,
+{
+    if limit == 0 {
+        return Vec::new();
+    }
+    let mut scored: Vec<(S, String, &V)> = entries
+        .map(|(key, value)| (score(value), key, value))
+        .collect();
+    // keys are distinct, so this is a total order with ties broken by key
+    let order =
+        |a: &(S, String, &V), b: &(S, String, &V)| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1));
+    // only the best `limit` entries are sorted, in O(n + limit log limit) time
+    if limit < scored.len() {
+        scored.select_nth_unstable_by(limit - 1, order);
+        scored.truncate(limit);
+    }
+    scored.sort_unstable_by(order);
+    scored
+        .into_iter()
+        .map(|(_, key, value)| (key, value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_key(rng: &mut StdRng) -> String {
+        let len = rng.gen_range(0..6);
+        (0..len)
+            .map(|_| ['a', 'b', 'é', 'c'][rng.gen_range(0..4)])
+            .collect()
+    }
+
+    fn check_radix_shape<V>(node: &RadixNode<V>, is_root: bool) {
+        if !is_root {
+            assert!(!node.label.is_empty());
+            assert!(node.value.is_some() || node.children.len() >= 2);
+        }
+        for (first, child) in &node.children {
+            assert_eq!(child.label[0], *first);
+            check_radix_shape(child, false);
+        }
+    }
+
+    #[test]
+    fn test_random_against_btree_map() {
+        let mut rng = StdRng::seed_from_u64(38);
+        let mut trie = Trie::new();
+        let mut radix = RadixTree::new();
+        let mut expected = BTreeMap::new();
+        for i in 0..3000 {
+            let key = random_key(&mut rng);
+            if rng.gen_bool(0.6) {
+                let old = expected.insert(key.clone(), i);
+                assert_eq!(trie.insert(&key, i), old);
+                assert_eq!(radix.insert(&key, i), old);
+            } else {
+                let old = expected.remove(&key);
+                assert_eq!(trie.remove(&key), old);
+                assert_eq!(radix.remove(&key), old);
+            }
+            assert_eq!(trie.len(), expected.len());
+            assert_eq!(radix.len(), expected.len());
+        }
+        check_radix_shape(&radix.root, true);
+
+        let all: Vec<(String, &i32)> = expected.iter().map(|(k, v)| (k.clone(), v)).collect();
+        assert_eq!(trie.iter().collect::<Vec<_>>(), all);
+        assert_eq!(radix.iter().collect::<Vec<_>>(), all);
+        for _ in 0..200 {
+            let query = random_key(&mut rng);
+            let prefixed: Vec<(String, &i32)> = expected
+                .iter()
+                .filter(|(k, _)| k.starts_with(&query))
+                .map(|(k, v)| (k.clone(), v))
+                .collect();
+            assert_eq!(trie.prefix_iter(&query).collect::<Vec<_>>(), prefixed);
+            assert_eq!(radix.prefix_iter(&query).collect::<Vec<_>>(), prefixed);
+
+            let longest = expected
+                .iter()
+                .filter(|(k, _)| query.starts_with(k.as_str()))
+                .max_by_key(|(k, _)| k.len())
+                .map(|(k, v)| (k.as_str(), v));
+            let found = trie.longest_prefix_match(&query);
+            assert_eq!(found.map(|(k, v)| (k, *v)), longest.map(|(k, v)| (k, *v)));
+            let found = radix.longest_prefix_match(&query);
+            assert_eq!(found.map(|(k, v)| (k, *v)), longest.map(|(k, v)| (k, *v)));
+            assert_eq!(trie.get(&query), expected.get(&query));
+            assert_eq!(radix.get(&query), expected.get(&query));
+        }
+    }
+
+    #[test]
+    fn test_radix_split_and_merge() {
+        let mut tree = RadixTree::new();
+        tree.insert("test", 1);
+        tree.insert("team", 2);
+        tree.insert("te", 3);
+        assert_eq!(tree.node_count(), 4);
+        assert_eq!(tree.root.children[&b't'].label, b"te");
+        assert_eq!(tree.remove("te"), Some(3));
+        assert_eq!(tree.node_count(), 4);
+        assert_eq!(tree.remove("team"), Some(2));
+        assert_eq!(tree.node_count(), 2);
+        assert_eq!(tree.root.children[&b't'].label, b"test");
+        assert_eq!(tree.prefix_iter("tes").count(), 1);
+        assert_eq!(tree.prefix_iter("tex").count(), 0);
+        assert_eq!(tree.remove("tes"), None);
+    }
+
+    #[test]
+    fn test_autocomplete_and_empty_key() {
+        let mut trie: Trie<u32> = [("apple", 5), ("app", 5), ("apply", 9), ("ape", 1)]
+            .into_iter()
+            .collect();
+        let radix: RadixTree<u32> = trie.iter().map(|(k, v)| (k, *v)).collect();
+        let expected = vec![("apply".to_string(), &9), ("app".to_string(), &5)];
+        assert_eq!(trie.autocomplete("app", 2, |v| *v), expected);
+        assert_eq!(radix.autocomplete("app", 2, |v| *v), expected);
+        assert_eq!(radix.autocomplete("b", 2, |v| *v), vec![]);
+
+        assert_eq!(trie.longest_prefix_match("banana"), None);
+        trie.insert("", 0);
+        assert_eq!(trie.longest_prefix_match("banana"), Some(("", &0)));
+        *trie.get_mut("ape").unwrap() += 1;
+        assert_eq!(trie.get("ape"), Some(&2));
+    }
+
+    #[test]
+    fn test_autocomplete_against_full_sort() {
+        let mut rng = StdRng::seed_from_u64(38);
+        let trie: Trie<u32> = (0..300)
+            .map(|_| (random_key(&mut rng), rng.gen_range(0..5)))
+            .collect();
+        let mut all: Vec<_> = trie.prefix_iter("").collect();
+        all.sort_by(|a, b| b.1.cmp(a.1));
+        for limit in [0, 1, 7, 50, all.len(), all.len() + 1] {
+            let expected: Vec<_> = all.iter().take(limit).cloned().collect();
+            assert_eq!(trie.autocomplete("", limit, |v| *v), expected);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use core::cmp::Ordering;
 use std::fmt::{Debug, Display};
 use std::vec::Vec;
 
@@ -397,6 +398,52 @@ impl<T: Clone + PartialOrd + Default + Display + Debug> Heap<T> {
     }
 }
 
+/// Ascending heap sort of a slice in place with a comparator
+///
+/// The slice counterpart of `Heap::asc_sort_with_max_sift`, it takes O(n lg n)
+/// time in the worst case and O(1) extra space.
+///
+/// ```rust
+/// use algorithms_rs::heap::heap_sort_by;
+///
+/// let mut arr = [3, 1, 4, 1, 5, 9, 2, 6];
+/// heap_sort_by(&mut arr, |a, b| b.cmp(a));
+/// assert_eq!(arr, [9, 6, 5, 4, 3, 2, 1, 1]);
+/// ```
+pub fn heap_sort_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // build a max heap bottom up
+    for index in (0..arr.len() / 2).rev() {
+        sift_down_by(arr, index, &mut compare);
+    }
+    // move the maximum behind the shrinking heap
+    for end in (1..arr.len()).rev() {
+        arr.swap(0, end);
+        sift_down_by(&mut arr[..end], 0, &mut compare);
+    }
+}
+
+fn sift_down_by<T, F>(heap: &mut [T], mut index: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        let mut largest = index;
+        for child in [left(index), right(index)] {
+            if child < heap.len() && compare(&heap[largest], &heap[child]) == Ordering::Less {
+                largest = child;
+            }
+        }
+        if largest == index {
+            break;
+        }
+        heap.swap(index, largest);
+        index = largest;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -457,4 +504,14 @@ mod tests {
         max_heap.asc_sort_with_max_sift();
         assert_eq!(max_heap.data, vec![0, 1, 2, 3, 4, 5, 5, 6, 7, 8, 9]);
     }
+
+    #[test]
+    fn test_heap_sort_by_slice() {
+        let mut arr = [5, 3, 7, 9, 10, 23, 45, 23, 12, 23, 0, 12, 32];
+        heap_sort_by(&mut arr, i32::cmp);
+        assert_eq!(arr, [0, 3, 5, 7, 9, 10, 12, 12, 23, 23, 23, 32, 45]);
+
+        let mut empty: [i32; 0] = [];
+        heap_sort_by(&mut empty, i32::cmp);
+    }
 }
@@ -2,8 +2,8 @@
 // bubble sort is to look for adjacent indexes which
 // are out of place and interchange their elements
 // until the entire array is sorted.
-use super::Sort;
-use core::cmp::PartialOrd;
+use super::{sort_by_cached_key_with, Sort};
+use core::cmp::{Ordering, PartialOrd};
 
 /// bubble sort
 #[derive(Debug)]
@@ -30,36 +30,55 @@ impl<T: Clone + PartialOrd> Sort<T> for BubbleSort<T> {
 
     fn sort_by<F>(&mut self, f: F)
     where
-        F: FnOnce(&T, &T) -> bool + core::marker::Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
         let _ = self.bubble_sort_by(f);
     }
+
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        sort_by_cached_key_with(&mut self.arr, f, |order, compare| {
+            bubble_sort_by(order, compare)
+        });
+    }
 }
 
-impl<T: PartialOrd> BubbleSort<T> {
+impl<T> BubbleSort<T> {
     /// bubble sort can customize the comparison logic based on the comparison function passed in
     pub fn bubble_sort_by<F>(&mut self, cmp_fun: F) -> bool
     where
-        F: FnOnce(&T, &T) -> bool + Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
-        if self.arr.is_empty() {
-            return true;
-        }
+        bubble_sort_by(&mut self.arr, cmp_fun);
+        true
+    }
+}
 
-        let mut sorted = false;
-        let len = self.arr.len();
+/// Bubble sort a slice in place in ascending order
+pub fn bubble_sort<T: Ord>(arr: &mut [T]) {
+    bubble_sort_by(arr, T::cmp)
+}
 
-        while !sorted {
-            sorted = true;
-            (0..(len - 1)).for_each(|idx| {
-                if cmp_fun(&self.arr[idx + 1], &self.arr[idx]) {
-                    self.arr.swap(idx, idx + 1);
-                    sorted = false;
-                }
-            })
-        }
+/// Stable bubble sort of a slice in place with a comparator
+pub fn bubble_sort_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // only strictly smaller elements move forward, so the sort is stable
+    let mut sorted = false;
+    let len = arr.len();
 
-        sorted
+    while !sorted {
+        sorted = true;
+        (1..len).for_each(|idx| {
+            if compare(&arr[idx], &arr[idx - 1]) == Ordering::Less {
+                arr.swap(idx - 1, idx);
+                sorted = false;
+            }
+        })
     }
 }
 
@@ -81,4 +100,11 @@ mod tests {
         bubble.sort();
         assert!(bubble.is_sort());
     }
+
+    #[test]
+    fn test_bubble_sort_descending() {
+        let mut bubble = BubbleSort::from(vec![10, 4, 6, 8, 13, 2, 3]);
+        assert!(bubble.bubble_sort_by(|a, b| b.cmp(a)));
+        assert_eq!(bubble.inner(), vec![13, 10, 8, 6, 4, 3, 2]);
+    }
 }
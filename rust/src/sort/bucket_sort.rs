@@ -0,0 +1,82 @@
+//! Bucket sort, CLRS 8.4
+
+use super::insertion_sort_by;
+
+/// Bucket sort of floats, in expected O(n) time for uniformly distributed values
+///
+/// The range between the smallest and the largest value is split into n
+/// equal buckets, every bucket is insertion sorted and the buckets are
+/// concatenated. Values clustered in a few buckets take up to O(n^2) time.
+///
+/// ```no
+/// BUCKET-SORT(A)
+///     n = A.length
+///     let B[0..n - 1] be a new array of empty lists
+///     for i = 1 to n
+///         insert A[i] into list B[floor(n A[i])]
+///     for i = 0 to n - 1
+///         sort list B[i] with insertion sort
+///     concatenate the lists B[0], B[1], ..., B[n - 1] together in order
+/// ```
+///
+/// ```rust
+/// use algorithms_rs::sort::bucket_sort;
+///
+/// let mut arr = [0.78, 0.17, 0.39, 0.26, 0.72, 0.94, 0.21, 0.12, 0.23, 0.68];
+/// bucket_sort(&mut arr);
+/// assert_eq!(arr, [0.12, 0.17, 0.21, 0.23, 0.26, 0.39, 0.68, 0.72, 0.78, 0.94]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if a value is infinite or NaN.
+pub fn bucket_sort(arr: &mut [f64]) {
+    assert!(
+        arr.iter().all(|v| v.is_finite()),
+        "bucket sort needs finite values"
+    );
+    let n = arr.len();
+    let min = arr.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = arr.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if n < 2 || min == max {
+        return;
+    }
+
+    let mut buckets: Vec<Vec<f64>> = vec![Vec::new(); n];
+    let width = max - min;
+    for &v in arr.iter() {
+        let b = ((v - min) / width * n as f64) as usize;
+        buckets[b.min(n - 1)].push(v);
+    }
+    let mut i = 0;
+    for mut bucket in buckets {
+        insertion_sort_by(&mut bucket, f64::total_cmp);
+        arr[i..i + bucket.len()].copy_from_slice(&bucket);
+        i += bucket.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_bucket_sort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(4747);
+        for len in [0, 1, 2, 10, 1000] {
+            for (lo, hi) in [(0.0, 1.0), (-1e9, 1e9), (5.0, 5.001)] {
+                let values: Vec<f64> = (0..len).map(|_| rng.gen_range(lo..hi)).collect();
+                let mut expected = values.clone();
+                expected.sort_by(f64::total_cmp);
+                let mut actual = values;
+                bucket_sort(&mut actual);
+                assert_eq!(actual, expected);
+            }
+        }
+        let mut constant = [2.5; 10];
+        bucket_sort(&mut constant);
+        assert_eq!(constant, [2.5; 10]);
+    }
+}
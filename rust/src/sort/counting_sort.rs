@@ -0,0 +1,92 @@
+//! Counting sort, CLRS 8.2
+
+/// Stable counting sort by a key in `0..key_range`, in O(n + k) time
+///
+/// Each key is computed once. The elements are moved into place by
+/// following the cycles of the sorted permutation, so they need not be
+/// `Clone`.
+///
+/// ```no
+/// COUNTING-SORT(A, B, k)
+///     let C[0..k] be a new array of zeros
+///     for j = 1 to A.length
+///         C[A[j]] = C[A[j]] + 1
+///     for i = 1 to k
+///         C[i] = C[i] + C[i - 1]
+///     for j = A.length downto 1
+///         B[C[A[j]]] = A[j]
+///         C[A[j]] = C[A[j]] - 1
+/// ```
+///
+/// ```rust
+/// use algorithms_rs::sort::counting_sort;
+///
+/// let mut grades = [("ann", 3), ("bob", 1), ("cid", 3), ("dee", 0)];
+/// counting_sort(&mut grades, 4, |grade| grade.1);
+///
+/// assert_eq!(grades, [("dee", 0), ("bob", 1), ("ann", 3), ("cid", 3)]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if a key is not less than `key_range`.
+pub fn counting_sort<T, F>(arr: &mut [T], key_range: usize, mut key: F)
+where
+    F: FnMut(&T) -> usize,
+{
+    let keys: Vec<usize> = arr.iter().map(&mut key).collect();
+    let mut starts = vec![0usize; key_range + 1];
+    for &k in &keys {
+        assert!(k < key_range, "key {k} out of range for {key_range} keys");
+        starts[k + 1] += 1;
+    }
+    for i in 1..starts.len() {
+        starts[i] += starts[i - 1];
+    }
+    // the final position of every element, in input order for equal keys
+    let mut destinations: Vec<usize> = keys
+        .iter()
+        .map(|&k| {
+            starts[k] += 1;
+            starts[k] - 1
+        })
+        .collect();
+    permute(arr, &mut destinations);
+}
+
+// Move arr[i] to destinations[i], following each cycle of the permutation
+fn permute<T>(arr: &mut [T], destinations: &mut [usize]) {
+    for i in 0..arr.len() {
+        while destinations[i] != i {
+            let d = destinations[i];
+            arr.swap(i, d);
+            destinations.swap(i, d);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_counting_sort_is_stable() {
+        let mut rng = StdRng::seed_from_u64(47);
+        for len in 0..100 {
+            let values: Vec<(usize, usize)> = (0..len).map(|i| (rng.gen_range(0..7), i)).collect();
+            let mut expected = values.clone();
+            expected.sort_by_key(|v| v.0);
+            let mut actual = values;
+            counting_sort(&mut actual, 7, |v| v.0);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "key 3 out of range")]
+    fn test_counting_sort_key_out_of_range() {
+        counting_sort(&mut [1, 3], 3, |v| *v);
+    }
+}
@@ -0,0 +1,457 @@
+//! External merge sort for inputs larger than memory
+//!
+//! The input is read in runs of a fixed number of records, each run is
+//! sorted in memory with `merge_sort_by` and written to a temporary file,
+//! and the runs are merged k at a time through a min-heap of their first
+//! records. When there are more runs than the fan-in, consecutive groups
+//! are merged into longer runs first, so at most `fan_in` files are open at
+//! once. Ties between runs go to the earlier run, which keeps the sort stable.
+use super::merge_sort_by;
+use core::cmp::Ordering;
+use core::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// How records are laid out in a byte stream
+pub trait RecordCodec {
+    /// Decoded record
+    type Record;
+
+    /// Read the next record, None at the end of the input
+    fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Self::Record>>;
+
+    /// Write a record
+    fn write_record<W: Write>(&self, writer: &mut W, record: &Self::Record) -> io::Result<()>;
+}
+
+/// Records separated by `\n`, the last one may lack its newline
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineCodec;
+
+impl RecordCodec for LineCodec {
+    type Record = Vec<u8>;
+
+    fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn write_record<W: Write>(&self, writer: &mut W, record: &Vec<u8>) -> io::Result<()> {
+        writer.write_all(record)?;
+        writer.write_all(b"\n")
+    }
+}
+
+/// Records of exactly `width` bytes, back to back
+#[derive(Debug, Clone, Copy)]
+pub struct FixedWidthCodec {
+    width: usize,
+}
+
+impl FixedWidthCodec {
+    /// Creating a codec for records of `width` bytes
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
+    pub fn new(width: usize) -> Self {
+        assert!(width > 0, "records need at least one byte");
+        Self { width }
+    }
+}
+
+impl RecordCodec for FixedWidthCodec {
+    type Record = Vec<u8>;
+
+    fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+        let mut record = Vec::with_capacity(self.width);
+        reader.take(self.width as u64).read_to_end(&mut record)?;
+        match record.len() {
+            0 => Ok(None),
+            n if n == self.width => Ok(Some(record)),
+            n => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("record of {n} bytes, expected {}", self.width),
+            )),
+        }
+    }
+
+    fn write_record<W: Write>(&self, writer: &mut W, record: &Vec<u8>) -> io::Result<()> {
+        if record.len() != self.width {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("record of {} bytes, expected {}", record.len(), self.width),
+            ));
+        }
+        writer.write_all(record)
+    }
+}
+
+/// Records preceded by their length as a big-endian `u32`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LengthPrefixedCodec;
+
+impl RecordCodec for LengthPrefixedCodec {
+    type Record = Vec<u8>;
+
+    fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+        if reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut prefix = [0u8; 4];
+        reader.read_exact(&mut prefix)?;
+        // read rather than allocate up front, a corrupt prefix must not
+        // reserve gigabytes before the body turns out to be short
+        let len = u32::from_be_bytes(prefix) as usize;
+        let mut record = Vec::new();
+        reader.take(len as u64).read_to_end(&mut record)?;
+        if record.len() != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("record of {} bytes, expected {len}", record.len()),
+            ));
+        }
+        Ok(Some(record))
+    }
+
+    fn write_record<W: Write>(&self, writer: &mut W, record: &Vec<u8>) -> io::Result<()> {
+        let len = u32::try_from(record.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("record of {} bytes is too long", record.len()),
+            )
+        })?;
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(record)
+    }
+}
+
+/// External merge sort of records from a `Read` into a `Write`
+///
+/// ```rust
+/// use algorithms_rs::sort::{ExternalSort, LineCodec};
+///
+/// let input = b"pear\nfig\napple\nkiwi\nbanana\n";
+/// let mut output = Vec::new();
+///
+/// ExternalSort::new(LineCodec)
+///     .with_run_len(2)
+///     .sort(&input[..], &mut output)
+///     .unwrap();
+///
+/// assert_eq!(output, b"apple\nbanana\nfig\nkiwi\npear\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExternalSort<C> {
+    codec: C,
+    run_len: usize,
+    fan_in: usize,
+    temp_dir: PathBuf,
+}
+
+impl<C: RecordCodec> ExternalSort<C> {
+    /// Creating a sorter with runs of 2^20 records, merged 64 at a time in
+    /// the system temporary directory
+    pub fn new(codec: C) -> Self {
+        Self {
+            codec,
+            run_len: 1 << 20,
+            fan_in: 64,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+
+    /// Sorting `run_len` records at a time in memory
+    pub fn with_run_len(mut self, run_len: usize) -> Self {
+        self.run_len = run_len.max(1);
+        self
+    }
+
+    /// Merging at most `fan_in` runs at a time
+    pub fn with_fan_in(mut self, fan_in: usize) -> Self {
+        self.fan_in = fan_in.max(2);
+        self
+    }
+
+    /// Writing the runs to files in `dir`
+    pub fn with_temp_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.temp_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Sort the records of `input` in ascending order into `output`
+    pub fn sort<R: Read, W: Write>(&self, input: R, output: W) -> io::Result<()>
+    where
+        C::Record: Ord + Clone,
+    {
+        self.sort_by(input, output, C::Record::cmp)
+    }
+
+    /// Stable sort of the records of `input` with a comparator into `output`
+    pub fn sort_by<R, W, F>(&self, input: R, output: W, mut compare: F) -> io::Result<()>
+    where
+        R: Read,
+        W: Write,
+        C::Record: Clone,
+        F: FnMut(&C::Record, &C::Record) -> Ordering,
+    {
+        let mut reader = BufReader::new(input);
+        let mut writer = BufWriter::new(output);
+        let mut runs = Vec::new();
+        loop {
+            let mut run = Vec::with_capacity(self.run_len.min(1 << 16));
+            while run.len() < self.run_len {
+                match self.codec.read_record(&mut reader)? {
+                    Some(record) => run.push(record),
+                    None => break,
+                }
+            }
+            let last = run.len() < self.run_len;
+            merge_sort_by(&mut run, &mut compare);
+            if last && runs.is_empty() {
+                // everything fit in memory
+                for record in &run {
+                    self.codec.write_record(&mut writer, record)?;
+                }
+                return writer.flush();
+            }
+            if !run.is_empty() {
+                let (file, mut run_writer) = RunFile::create(&self.temp_dir)?;
+                for record in &run {
+                    self.codec.write_record(&mut run_writer, record)?;
+                }
+                run_writer.flush()?;
+                runs.push(file);
+            }
+            if last {
+                break;
+            }
+        }
+
+        // merge consecutive groups until one pass can finish the job
+        while runs.len() > self.fan_in {
+            let mut merged = Vec::with_capacity(runs.len().div_ceil(self.fan_in));
+            for group in runs.chunks(self.fan_in) {
+                let (file, mut run_writer) = RunFile::create(&self.temp_dir)?;
+                self.merge(group, &mut run_writer, &mut compare)?;
+                run_writer.flush()?;
+                merged.push(file);
+            }
+            runs = merged;
+        }
+        self.merge(&runs, &mut writer, &mut compare)?;
+        writer.flush()
+    }
+
+    // k-way merge of the runs into writer through a min-heap of run heads
+    fn merge<W, F>(&self, runs: &[RunFile], writer: &mut W, compare: &mut F) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&C::Record, &C::Record) -> Ordering,
+    {
+        let mut readers = runs
+            .iter()
+            .map(|run| Ok(BufReader::new(File::open(&run.path)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut heap: Vec<(C::Record, usize)> = Vec::with_capacity(readers.len());
+        for (i, reader) in readers.iter_mut().enumerate() {
+            if let Some(record) = self.codec.read_record(reader)? {
+                heap.push((record, i));
+            }
+        }
+        // the earlier run wins ties, which keeps the merge stable
+        let mut less = |a: &(C::Record, usize), b: &(C::Record, usize)| {
+            compare(&a.0, &b.0).then(a.1.cmp(&b.1)) == Ordering::Less
+        };
+        for i in (0..heap.len() / 2).rev() {
+            sift_down(&mut heap, i, &mut less);
+        }
+        while !heap.is_empty() {
+            let run = heap[0].1;
+            self.codec.write_record(writer, &heap[0].0)?;
+            match self.codec.read_record(&mut readers[run])? {
+                Some(record) => heap[0].0 = record,
+                None => {
+                    heap.swap_remove(0);
+                }
+            }
+            sift_down(&mut heap, 0, &mut less);
+        }
+        Ok(())
+    }
+}
+
+fn sift_down<T, F>(heap: &mut [T], mut index: usize, less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        let mut smallest = index;
+        for child in [2 * index + 1, 2 * index + 2] {
+            if child < heap.len() && less(&heap[child], &heap[smallest]) {
+                smallest = child;
+            }
+        }
+        if smallest == index {
+            break;
+        }
+        heap.swap(index, smallest);
+        index = smallest;
+    }
+}
+
+// distinguishes the run files of sorts running at the same time
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// a temporary run file, removed when dropped
+#[derive(Debug)]
+struct RunFile {
+    path: PathBuf,
+}
+
+impl RunFile {
+    // the new run and a buffered writer for it
+    fn create(dir: &Path) -> io::Result<(Self, BufWriter<File>)> {
+        let id = RUN_COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = dir.join(format!("external-sort-{}-{id}.run", std::process::id()));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok((Self { path }, BufWriter::new(file)))
+    }
+}
+
+impl Drop for RunFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // a fresh directory, so the test can check that no run files are left
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("external-sort-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_lines_over_many_passes() {
+        let dir = temp_dir("lines");
+        let mut rng = StdRng::seed_from_u64(49);
+        for len in [0, 1, 7, 8, 100, 1000] {
+            let mut lines: Vec<String> = (0..len)
+                .map(|_| {
+                    let n = rng.gen_range(0..6);
+                    (0..n).map(|_| rng.gen_range('a'..'e')).collect()
+                })
+                .collect();
+            let input = lines.iter().map(|l| format!("{l}\n")).collect::<String>();
+            let mut output = Vec::new();
+            ExternalSort::new(LineCodec)
+                .with_run_len(7)
+                .with_fan_in(3)
+                .with_temp_dir(&dir)
+                .sort(input.as_bytes(), &mut output)
+                .unwrap();
+            lines.sort();
+            let expected = lines.iter().map(|l| format!("{l}\n")).collect::<String>();
+            assert_eq!(String::from_utf8(output).unwrap(), expected);
+        }
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fixed_width_is_stable() {
+        let dir = temp_dir("fixed");
+        let mut rng = StdRng::seed_from_u64(490);
+        // a key byte followed by the big-endian position
+        let records: Vec<Vec<u8>> = (0..500u32)
+            .map(|i| {
+                let mut record = vec![rng.gen_range(0..8)];
+                record.extend(i.to_be_bytes());
+                record
+            })
+            .collect();
+        let mut output = Vec::new();
+        ExternalSort::new(FixedWidthCodec::new(5))
+            .with_run_len(16)
+            .with_fan_in(4)
+            .with_temp_dir(&dir)
+            .sort_by(records.concat().as_slice(), &mut output, |a, b| {
+                a[0].cmp(&b[0])
+            })
+            .unwrap();
+        let mut expected = records;
+        expected.sort_by_key(|r| r[0]);
+        assert_eq!(output, expected.concat());
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_length_prefixed_round_trip() {
+        let dir = temp_dir("prefixed");
+        let mut rng = StdRng::seed_from_u64(4900);
+        let codec = LengthPrefixedCodec;
+        let mut records: Vec<Vec<u8>> = (0..300)
+            .map(|_| (0..rng.gen_range(0..20)).map(|_| rng.gen()).collect())
+            .collect();
+        let mut input = Vec::new();
+        for record in &records {
+            codec.write_record(&mut input, record).unwrap();
+        }
+        let mut output = Vec::new();
+        ExternalSort::new(codec)
+            .with_run_len(10)
+            .with_temp_dir(&dir)
+            .sort(input.as_slice(), &mut output)
+            .unwrap();
+        records.sort();
+        let mut reader = output.as_slice();
+        for record in &records {
+            assert_eq!(
+                codec.read_record(&mut reader).unwrap().as_ref(),
+                Some(record)
+            );
+        }
+        assert!(codec.read_record(&mut reader).unwrap().is_none());
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_input_is_an_error() {
+        let dir = temp_dir("truncated");
+        let sorter = ExternalSort::new(FixedWidthCodec::new(4)).with_temp_dir(&dir);
+        let error = sorter.sort(&b"abcdefg"[..], Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let error = ExternalSort::new(LengthPrefixedCodec)
+            .sort(&[0u8, 0, 0, 9, 1, 2][..], Vec::new())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_huge_prefix_with_short_body() {
+        let mut input: &[u8] = &[0xff, 0xff, 0xff, 0xff, 1, 2, 3];
+        let error = LengthPrefixedCodec.read_record(&mut input).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -1,6 +1,6 @@
-use super::Sort;
+use super::{sort_by_cached_key_with, Sort};
 use core::clone::Clone;
-use core::cmp::PartialOrd;
+use core::cmp::{Ordering, PartialOrd};
 
 /// Insert sort
 #[derive(Debug)]
@@ -8,23 +8,27 @@ pub struct InsertSort<T> {
     arr: Vec<T>,
 }
 
-impl<T> InsertSort<T> {
-    fn insert_sort<F>(&mut self, f: F)
-    where
-        F: FnOnce(&T, &T) -> bool + Copy,
-    {
-        let len = self.arr.len();
+/// Insertion sort a slice in place in ascending order
+pub fn insertion_sort<T: Ord>(arr: &mut [T]) {
+    insertion_sort_by(arr, T::cmp)
+}
 
-        for i in 1..len {
-            // do a[i] insert to a[i - 1], a[i - 2], a[i - 3]... among
-            let mut j = i;
-            while j > 0 && f(&self.arr[j], &self.arr[j - 1]) {
-                self.arr.swap(j, j - 1);
-                j -= 1;
-            }
+/// Stable insertion sort of a slice in place with a comparator
+pub fn insertion_sort_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // elements only move past strictly greater ones, so the sort is stable
+    for i in 1..arr.len() {
+        // do a[i] insert to a[i - 1], a[i - 2], a[i - 3]... among
+        let mut j = i;
+        while j > 0 && compare(&arr[j], &arr[j - 1]) == Ordering::Less {
+            arr.swap(j, j - 1);
+            j -= 1;
         }
     }
 }
+
 impl<T> From<Vec<T>> for InsertSort<T> {
     fn from(arr: Vec<T>) -> Self {
         Self { arr }
@@ -44,9 +48,19 @@ impl<T: PartialOrd + Clone> Sort<T> for InsertSort<T> {
 
     fn sort_by<F>(&mut self, f: F)
     where
-        F: FnOnce(&T, &T) -> bool + core::marker::Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
-        self.insert_sort(f)
+        insertion_sort_by(&mut self.arr, f)
+    }
+
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        sort_by_cached_key_with(&mut self.arr, f, |order, compare| {
+            insertion_sort_by(order, compare)
+        });
     }
 }
 
@@ -69,4 +83,11 @@ mod tests {
         insert.sort();
         assert!(insert.is_sort());
     }
+
+    #[test]
+    fn test_insert_sort_is_stable() {
+        let mut insert = InsertSort::from(vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')]);
+        insert.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(insert.inner(), vec![(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
 }
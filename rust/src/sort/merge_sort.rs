@@ -1,26 +1,32 @@
-use super::Infite;
-use super::Sort;
+use super::{sort_by_cached_key_with, Sort};
 use core::clone::Clone;
-use core::cmp::PartialOrd;
-use core::fmt::Debug;
+use core::cmp::{Ordering, PartialOrd};
 
 /// Merge Sort
+///
+/// ```rust
+/// use algorithms_rs::sort::{MergeSort, Sort};
+///
+/// let mut names = MergeSort::from(vec!["carol".to_string(), "al".to_string(), "bo".to_string()]);
+/// names.sort();
+/// assert_eq!(names.inner(), ["al", "bo", "carol"]);
+///
+/// let mut floats = MergeSort::from(vec![2.5, -1.0, f64::MAX, 0.0]);
+/// floats.bottom_up_sort_by(f64::total_cmp);
+/// assert_eq!(floats.inner(), [-1.0, 0.0, 2.5, f64::MAX]);
+/// ```
 #[derive(Debug)]
 pub struct MergeSort<T> {
     arr: Vec<T>,
 }
 
-impl<T> MergeSort<T>
-where
-    T: Copy + Default + Infite + Debug,
-{
-    fn merge_sort_by<F>(&mut self, f: F)
+impl<T: Clone> MergeSort<T> {
+    /// Stable bottom-up merge sort with a comparator
+    pub fn bottom_up_sort_by<F>(&mut self, f: F)
     where
-        F: FnOnce(&T, &T) -> bool + core::marker::Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
-        let start = 0;
-        let end = self.arr.len() - 1;
-        inner_merge_sort(&mut self.arr, start, end, f);
+        bottom_up_merge_sort_by(&mut self.arr, f)
     }
 }
 
@@ -36,84 +42,123 @@ impl<T: Clone> From<&[T]> for MergeSort<T> {
     }
 }
 
-impl<T> Sort<T> for MergeSort<T>
-where
-    T: PartialOrd + Default + Copy + Infite + Debug,
-{
+impl<T: PartialOrd + Clone> Sort<T> for MergeSort<T> {
     fn inner(&self) -> Vec<T> {
         self.arr.clone()
     }
 
     fn sort_by<F>(&mut self, f: F)
     where
-        F: FnOnce(&T, &T) -> bool + core::marker::Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
-        self.merge_sort_by(f)
+        merge_sort_by(&mut self.arr, f)
+    }
+
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        sort_by_cached_key_with(&mut self.arr, f, |order, compare| {
+            merge_sort_by(order, compare)
+        });
     }
 }
 
-fn inner_merge_sort<T, F>(array: &mut Vec<T>, p: usize, r: usize, f: F)
+/// Merge sort a slice in place in ascending order
+pub fn merge_sort<T: Ord + Clone>(arr: &mut [T]) {
+    merge_sort_by(arr, T::cmp)
+}
+
+/// Stable top-down merge sort of a slice in place with a comparator
+///
+/// ```no
+/// MERGE-SORT(A, p, r)
+///     if p < r
+///         q = floor((p + r) / 2)
+///         MERGE-SORT(A, p, q)
+///         MERGE-SORT(A, q + 1, r)
+///         MERGE(A, p, q, r)
+/// ```
+pub fn merge_sort_by<T, F>(arr: &mut [T], mut compare: F)
 where
-    T: Default + Copy + Infite + Debug,
-    F: FnOnce(&T, &T) -> bool + Copy,
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    if p < r {
-        let q = (p + r) / 2;
-        inner_merge_sort(array, p, q, f);
-        inner_merge_sort(array, q + 1, r, f);
-        inner_merge(array, p, q + 1, r + 1, f); // this is sick
-    }
+    // the left half of every merge has at most len / 2 elements
+    let mut buf = arr[..arr.len() / 2].to_vec();
+    inner_merge_sort(arr, &mut buf, &mut compare);
 }
 
-fn inner_merge<T, F>(arr: &mut [T], p: usize, q: usize, r: usize, f: F)
+/// Bottom-up merge sort a slice in place in ascending order
+pub fn bottom_up_merge_sort<T: Ord + Clone>(arr: &mut [T]) {
+    bottom_up_merge_sort_by(arr, T::cmp)
+}
+
+/// Stable bottom-up merge sort of a slice in place with a comparator
+///
+/// Merges neighbouring runs of width 1, 2, 4, ... without recursion.
+pub fn bottom_up_merge_sort_by<T, F>(arr: &mut [T], mut compare: F)
 where
-    T: Default + Copy + Infite + Debug,
-    F: FnOnce(&T, &T) -> bool + Copy,
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    log::info!("p = {}, q = {}, r = {}", p, q, r);
-    let n1 = q - p;
-    let n2 = r - q;
-
-    // let L[1..n1 + 1] and R[1..n2+ 1] be new arrayss
-    let max_value = T::max_value();
-    let mut l_arr = vec![max_value; n1 + 1];
-    let mut r_arr = vec![max_value; n2 + 1];
-
-    for i in 0..n1 {
-        if let Some(v) = arr.get(p + i) {
-            if let Some(t) = l_arr.get_mut(i) {
-                *t = *v;
-            }
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+    // the widest left run is the largest power of two below len
+    let mut buf = arr[..1 << (len - 1).ilog2()].to_vec();
+    let mut width = 1;
+    while width < len {
+        for start in (0..len - width).step_by(2 * width) {
+            let end = len.min(start + 2 * width);
+            inner_merge(&mut arr[start..end], width, &mut buf, &mut compare);
         }
+        width *= 2;
     }
+}
 
-    log::info!("l_arr = {:?}", l_arr);
-
-    for j in 0..n2 {
-        if let Some(v) = arr.get(q + j) {
-            if let Some(t) = r_arr.get_mut(j) {
-                *t = *v;
-            }
-        }
+fn inner_merge_sort<T, F>(arr: &mut [T], buf: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if arr.len() > 1 {
+        let mid = arr.len() / 2;
+        inner_merge_sort(&mut arr[..mid], buf, compare);
+        inner_merge_sort(&mut arr[mid..], buf, compare);
+        inner_merge(arr, mid, buf, compare);
     }
-    log::info!("r_arr = {:?}", r_arr);
+}
 
-    let mut i = 0usize;
-    let mut j = 0usize;
+// Merge the sorted runs arr[..mid] and arr[mid..]. The left run is swapped
+// out into the scratch buffer, so elements are moved rather than cloned and
+// the buffer just holds whatever the output positions held before.
+fn inner_merge<T, F>(arr: &mut [T], mid: usize, buf: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // already in order, as for presorted input
+    if compare(&arr[mid], &arr[mid - 1]) != Ordering::Less {
+        return;
+    }
+    let left = &mut buf[..mid];
+    arr[..mid].swap_with_slice(left);
 
-    for k in p..r {
-        if f(&l_arr[i], &r_arr[j]) {
-            if let Some(v) = arr.get_mut(k) {
-                *v = l_arr[i];
-            }
-            i += 1;
-        } else {
-            if let Some(v) = arr.get_mut(k) {
-                *v = r_arr[j];
-            }
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    while i < mid && j < arr.len() {
+        // ties take from the left run to keep the sort stable
+        if compare(&arr[j], &left[i]) == Ordering::Less {
+            arr.swap(k, j);
             j += 1;
+        } else {
+            core::mem::swap(&mut arr[k], &mut left[i]);
+            i += 1;
         }
+        k += 1;
     }
+    // the rest of the right run is already in place
+    arr[k..k + mid - i].swap_with_slice(&mut left[i..]);
 }
 
 #[test]
@@ -127,6 +172,17 @@ fn test_merge_sort() {
     println!("merge_sort: {merge_sort:?}");
 }
 
+#[test]
+fn test_merge_sort_descending_and_empty() {
+    let mut merge_sort = MergeSort::from(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+    merge_sort.sort_by(|a, b| b.cmp(a));
+    assert_eq!(merge_sort.inner(), vec![9, 6, 5, 4, 3, 2, 1, 1]);
+
+    let mut empty = MergeSort::from(Vec::<i32>::new());
+    empty.sort();
+    assert!(empty.is_sort());
+}
+
 type Link<T> = Option<Box<ListNode<T>>>;
 /// link node for sort list merge sort
 #[derive(Debug, PartialEq)]
@@ -234,6 +290,36 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_merge_sort_without_sentinels() {
+        let mut extremes = vec![i32::MAX, 0, i32::MIN, i32::MAX, -1];
+        merge_sort(&mut extremes);
+        assert_eq!(extremes, [i32::MIN, -1, 0, i32::MAX, i32::MAX]);
+
+        let mut words: Vec<String> = ["pear", "fig", "apple", "fig"].map(String::from).to_vec();
+        bottom_up_merge_sort(&mut words);
+        assert_eq!(words, ["apple", "fig", "fig", "pear"]);
+    }
+
+    #[test]
+    fn test_top_down_and_bottom_up_match_std() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(43);
+        for len in 0..130 {
+            let values: Vec<(u64, usize)> = (0..len).map(|i| (rng.gen_range(0..8), i)).collect();
+            let mut expected = values.clone();
+            expected.sort_by_key(|v| v.0);
+            let mut top_down = values.clone();
+            merge_sort_by(&mut top_down, |a, b| a.0.cmp(&b.0));
+            assert_eq!(top_down, expected);
+            let mut bottom_up = values;
+            bottom_up_merge_sort_by(&mut bottom_up, |a, b| a.0.cmp(&b.0));
+            assert_eq!(bottom_up, expected);
+        }
+    }
+
     #[test]
     fn test_merge_list() {
         let list1 = list!(1, 3);
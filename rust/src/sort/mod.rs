@@ -8,58 +8,195 @@ mod merge_sort;
 pub use merge_sort::*;
 mod quick_sort;
 pub use quick_sort::*;
+mod randomized_quick_sort;
+pub use randomized_quick_sort::*;
+mod select;
+pub use select::*;
+mod counting_sort;
+pub use counting_sort::*;
+mod radix_sort;
+pub use radix_sort::*;
+mod bucket_sort;
+pub use bucket_sort::*;
+mod parallel_sort;
+pub use parallel_sort::*;
+mod external_sort;
+pub use external_sort::*;
+mod tim_sort;
+pub use tim_sort::*;
+mod slice_sort;
+pub use slice_sort::*;
+
+use core::cmp::Ordering;
 
 /// Generic interface to sorting algorithms
+///
+/// Comparators return the `Ordering` of their first argument relative to the
+/// second. `sort_by` is stable, elements that compare `Equal` keep their
+/// relative order; `sort_unstable_by` may reorder them.
+///
+/// ```rust
+/// use algorithms_rs::sort::{InsertSort, Sort};
+///
+/// let mut words = InsertSort::from(vec!["pear", "fig", "apple", "kiwi"]);
+///
+/// words.sort_by_key(|word| word.len());
+///
+/// assert_eq!(words.inner(), vec!["fig", "pear", "kiwi", "apple"]);
+/// ```
 pub trait Sort<T: core::cmp::PartialOrd + Clone> {
     /// Get the internal data
     fn inner(&self) -> Vec<T>;
 
-    /// Customizable comparison logic based on closures
-    fn sort_by<F>(&mut self, f: F)
+    /// Stable sort with a comparator
+    fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Sort with a comparator, equal elements may be reordered
+    fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_by(compare)
+    }
+
+    /// Stable sort by a key, which is computed on every comparison
+    fn sort_by_key<K, F>(&mut self, mut f: F)
     where
-        F: FnOnce(&T, &T) -> bool + core::marker::Copy;
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Stable sort by a key, which is computed once per element
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K;
 
     /// Sort by ascending order
+    ///
+    /// # Panics
+    ///
+    /// Panics if two elements are not comparable, such as a NaN float.
     fn sort(&mut self) {
-        self.sort_by(|v1, v2| v1 <= v2);
+        self.sort_by(|v1, v2| {
+            v1.partial_cmp(v2)
+                .expect("sort needs comparable elements, use sort_by instead")
+        });
     }
 
     /// Determine if the sort is ascending
     fn is_sort(&self) -> bool {
-        self.is_sort_by(|v1, v2| v1 <= v2)
+        self.is_sort_by(|v1, v2| v1.partial_cmp(v2).unwrap_or(Ordering::Greater))
     }
 
-    /// Customized judgments are ordered
-    fn is_sort_by<F>(&self, f: F) -> bool
+    /// Determine if no element is ordered after its successor by `compare`
+    fn is_sort_by<F>(&self, mut compare: F) -> bool
     where
-        F: FnOnce(&T, &T) -> bool + core::marker::Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
-        let array = self.inner();
-
-        if array.is_empty() {
-            return true;
-        }
-
-        for idx in 0..array.len() - 1 {
-            if !f(&array[idx], &array[idx + 1]) {
-                return false;
-            }
-        }
-        true
+        self.inner()
+            .windows(2)
+            .all(|pair| compare(&pair[0], &pair[1]) != Ordering::Greater)
     }
 }
 
-pub trait Infite {
-    fn max_value() -> Self;
+// Order `arr` by keys computed once per element. `sort_positions` sorts the
+// positions of the elements with the caller's algorithm; ties are broken by
+// position, so even an unstable algorithm gives a stable result.
+fn sort_by_cached_key_with<T, K, F, S>(arr: &mut Vec<T>, f: F, sort_positions: S)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+    S: FnOnce(&mut [usize], &mut dyn FnMut(&usize, &usize) -> Ordering),
+{
+    let keys: Vec<K> = arr.iter().map(f).collect();
+    let mut order: Vec<usize> = (0..arr.len()).collect();
+    sort_positions(&mut order, &mut |a, b| {
+        keys[*a].cmp(&keys[*b]).then(a.cmp(b))
+    });
+    apply_order(arr, &order);
+}
+
+// Stable sort of `arr` with an unstable algorithm, by sorting the positions
+// of the elements and breaking ties by position.
+fn stable_sort_by_positions<T, F, S>(arr: &mut Vec<T>, mut compare: F, sort_positions: S)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    S: FnOnce(&mut [usize], &mut dyn FnMut(&usize, &usize) -> Ordering),
+{
+    let mut order: Vec<usize> = (0..arr.len()).collect();
+    sort_positions(&mut order, &mut |a, b| {
+        compare(&arr[*a], &arr[*b]).then(a.cmp(b))
+    });
+    apply_order(arr, &order);
+}
 
-    fn min_value() -> Self;
+// Rearrange `arr` so that position i holds the element that was at order[i]
+fn apply_order<T>(arr: &mut Vec<T>, order: &[usize]) {
+    let mut slots: Vec<Option<T>> = arr.drain(..).map(Some).collect();
+    arr.extend(
+        order
+            .iter()
+            .map(|&i| slots[i].take().expect("every position appears once")),
+    );
 }
 
-impl Infite for i32 {
-    fn max_value() -> Self {
-        i32::MAX
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn check_sorter<S>(make: impl Fn(Vec<(i32, usize)>) -> S)
+    where
+        S: Sort<(i32, usize)>,
+    {
+        let mut rng = StdRng::seed_from_u64(41);
+        for len in 0..40 {
+            // few distinct keys, tagged with their position to expose instability
+            let values: Vec<(i32, usize)> = (0..len).map(|i| (rng.gen_range(0..5), i)).collect();
+            let mut expected = values.clone();
+            expected.sort_by_key(|v| v.0);
+
+            let mut sorter = make(values.clone());
+            sorter.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(sorter.inner(), expected);
+
+            let mut sorter = make(values.clone());
+            sorter.sort_by_key(|v| v.0);
+            assert_eq!(sorter.inner(), expected);
+
+            let mut calls = 0;
+            let mut sorter = make(values.clone());
+            sorter.sort_by_cached_key(|v| {
+                calls += 1;
+                v.0
+            });
+            assert_eq!(sorter.inner(), expected);
+            assert_eq!(calls, len);
+
+            let mut sorter = make(values.clone());
+            sorter.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+            assert!(sorter.is_sort_by(|a, b| b.0.cmp(&a.0)));
+
+            let mut sorter = make(values);
+            sorter.sort();
+            assert!(sorter.is_sort());
+        }
     }
-    fn min_value() -> Self {
-        i32::MIN
+
+    #[test]
+    fn test_sorters_match_std() {
+        check_sorter(BubbleSort::from);
+        check_sorter(InsertSort::from);
+        check_sorter(SelectSort::from);
+        check_sorter(MergeSort::from);
+        check_sorter(IntroSort::from);
+        check_sorter(RandomizedQuickSort::from);
+        check_sorter(TimSort::from);
     }
 }
@@ -0,0 +1,339 @@
+//! Multithreaded merge sort and quicksort, CLRS 27.3
+//!
+//! Both sorts split the work with `std::thread::scope`, running one half on a
+//! new thread and the other on the current one. Slices shorter than the
+//! threshold, and anything below the depth that already uses every thread,
+//! are sorted sequentially with `merge_sort_by` or `introsort_by`.
+//!
+//! P-MERGE-SORT also merges in parallel: the middle element of the longer run
+//! is found in the shorter one by binary search, which splits the merge into
+//! two independent merges on either side of it.
+use super::{choose_pivot, hoare_partition_by, introsort_by, merge_sort_by};
+use core::cmp::Ordering;
+use std::thread;
+
+/// Settings for the parallel sorts
+///
+/// Comparators are shared between threads, so they are `Fn + Sync` rather
+/// than `FnMut`.
+///
+/// ```rust
+/// use algorithms_rs::sort::ParallelSort;
+///
+/// let mut arr: Vec<u64> = (0..100_000).map(|i| i * 7919 % 100_003).collect();
+/// let sorter = ParallelSort::new().with_threads(4).with_threshold(1 << 12);
+///
+/// sorter.merge_sort_by(&mut arr, |a, b| b.cmp(a));
+/// assert!(arr.windows(2).all(|w| w[0] >= w[1]));
+///
+/// sorter.quicksort_by(&mut arr, u64::cmp);
+/// assert!(arr.windows(2).all(|w| w[0] <= w[1]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelSort {
+    threshold: usize,
+    depth: u32,
+}
+
+impl Default for ParallelSort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParallelSort {
+    /// Using every available thread and a threshold of 8192 elements
+    pub fn new() -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            threshold: 1 << 13,
+            depth: 0,
+        }
+        .with_threads(threads)
+    }
+
+    /// Sorting slices shorter than `threshold` on a single thread
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold.max(2);
+        self
+    }
+
+    /// Splitting the work until there is a task for each of `threads` threads
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.depth = threads.max(1).next_power_of_two().ilog2();
+        self
+    }
+
+    /// Stable parallel merge sort with a comparator
+    ///
+    /// ```no
+    /// P-MERGE-SORT(A, p, r, B, s)
+    ///     n = r - p + 1
+    ///     if n == 1
+    ///         B[s] = A[p]
+    ///     else let T[1..n] be a new array
+    ///         q = floor((p + r) / 2)
+    ///         q' = q - p + 1
+    ///         spawn P-MERGE-SORT(A, p, q, T, 1)
+    ///         P-MERGE-SORT(A, q + 1, r, T, q' + 1)
+    ///         sync
+    ///         P-MERGE(T, 1, q', q' + 1, n, B, s)
+    /// ```
+    pub fn merge_sort_by<T, F>(&self, arr: &mut [T], compare: F)
+    where
+        T: Clone + Send + Sync,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if arr.len() < self.threshold || self.depth == 0 {
+            merge_sort_by(arr, compare);
+            return;
+        }
+        let mut buf = arr.to_vec();
+        self.p_merge_sort(arr, &mut buf, false, self.depth, &compare);
+    }
+
+    /// Unstable parallel quicksort with a comparator
+    pub fn quicksort_by<T, F>(&self, arr: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        self.p_quicksort(arr, self.depth, &compare);
+    }
+
+    // Sort src, leaving the result in buf if into_buf and in src otherwise.
+    // The halves are sorted into the other slice and merged back.
+    fn p_merge_sort<T, F>(
+        &self,
+        src: &mut [T],
+        buf: &mut [T],
+        into_buf: bool,
+        depth: u32,
+        compare: &F,
+    ) where
+        T: Clone + Send + Sync,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if src.len() < self.threshold || depth == 0 {
+            merge_sort_by(src, compare);
+            if into_buf {
+                buf.clone_from_slice(src);
+            }
+            return;
+        }
+        let mid = src.len() / 2;
+        let (src_left, src_right) = src.split_at_mut(mid);
+        let (buf_left, buf_right) = buf.split_at_mut(mid);
+        join(
+            || self.p_merge_sort(src_left, buf_left, !into_buf, depth - 1, compare),
+            || self.p_merge_sort(src_right, buf_right, !into_buf, depth - 1, compare),
+        );
+        if into_buf {
+            self.p_merge(src_left, src_right, buf, depth, compare);
+        } else {
+            self.p_merge(buf_left, buf_right, src, depth, compare);
+        }
+    }
+
+    // Merge the sorted runs left and right into dst, taking equal elements
+    // from left first
+    fn p_merge<T, F>(&self, left: &[T], right: &[T], dst: &mut [T], depth: u32, compare: &F)
+    where
+        T: Clone + Send + Sync,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if dst.len() < self.threshold || depth == 0 || left.is_empty() || right.is_empty() {
+            merge_into(left, right, dst, compare);
+            return;
+        }
+        // split around the middle of the longer run; elements equal to the
+        // splitter stay on the side that keeps the merge stable
+        let (q1, q2, x) = if left.len() >= right.len() {
+            let q1 = left.len() / 2;
+            let x = &left[q1];
+            let q2 = right.partition_point(|v| compare(v, x) == Ordering::Less);
+            (q1, q2, x)
+        } else {
+            let q2 = right.len() / 2;
+            let x = &right[q2];
+            let q1 = left.partition_point(|v| compare(v, x) != Ordering::Greater);
+            (q1, q2, x)
+        };
+        let (dst_low, rest) = dst.split_at_mut(q1 + q2);
+        let (dst_x, dst_high) = rest.split_first_mut().expect("the splitter has a slot");
+        dst_x.clone_from(x);
+        let (left_high, right_high) = if left.len() >= right.len() {
+            (&left[q1 + 1..], &right[q2..])
+        } else {
+            (&left[q1..], &right[q2 + 1..])
+        };
+        join(
+            || self.p_merge(&left[..q1], &right[..q2], dst_low, depth - 1, compare),
+            || self.p_merge(left_high, right_high, dst_high, depth - 1, compare),
+        );
+    }
+
+    fn p_quicksort<T, F>(&self, arr: &mut [T], depth: u32, compare: &F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        if arr.len() < self.threshold || depth == 0 {
+            introsort_by(arr, compare);
+            return;
+        }
+        let pivot = choose_pivot(arr, &mut &compare);
+        arr.swap(0, pivot);
+        let mid = hoare_partition_by(arr, compare);
+        let (left, rest) = arr.split_at_mut(mid);
+        let right = &mut rest[1..];
+        join(
+            || self.p_quicksort(left, depth - 1, compare),
+            || self.p_quicksort(right, depth - 1, compare),
+        );
+    }
+}
+
+/// Stable parallel merge sort in ascending order, with the default settings
+///
+/// ```rust
+/// use algorithms_rs::sort::par_merge_sort;
+///
+/// let mut arr: Vec<i32> = (0..50_000).rev().collect();
+/// par_merge_sort(&mut arr);
+/// assert!(arr.windows(2).all(|w| w[0] <= w[1]));
+/// ```
+pub fn par_merge_sort<T: Ord + Clone + Send + Sync>(arr: &mut [T]) {
+    ParallelSort::new().merge_sort_by(arr, T::cmp)
+}
+
+/// Stable parallel merge sort with a comparator, with the default settings
+pub fn par_merge_sort_by<T, F>(arr: &mut [T], compare: F)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    ParallelSort::new().merge_sort_by(arr, compare)
+}
+
+/// Unstable parallel quicksort in ascending order, with the default settings
+pub fn par_quicksort<T: Ord + Send>(arr: &mut [T]) {
+    ParallelSort::new().quicksort_by(arr, T::cmp)
+}
+
+/// Unstable parallel quicksort with a comparator, with the default settings
+pub fn par_quicksort_by<T, F>(arr: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    ParallelSort::new().quicksort_by(arr, compare)
+}
+
+// run a on a new thread and b on this one, passing on a panic from either
+fn join<A, B>(a: A, b: B)
+where
+    A: FnOnce() + Send,
+    B: FnOnce(),
+{
+    thread::scope(|scope| {
+        let handle = scope.spawn(a);
+        b();
+        if let Err(panic) = handle.join() {
+            std::panic::resume_unwind(panic);
+        }
+    });
+}
+
+// sequential merge, cloning the elements into dst
+fn merge_into<T, F>(left: &[T], right: &[T], dst: &mut [T], compare: &F)
+where
+    T: Clone,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let (mut i, mut j) = (0, 0);
+    for slot in dst.iter_mut() {
+        let take_left =
+            j == right.len() || (i < left.len() && compare(&right[j], &left[i]) != Ordering::Less);
+        if take_left {
+            slot.clone_from(&left[i]);
+            i += 1;
+        } else {
+            slot.clone_from(&right[j]);
+            j += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // a small threshold and many threads exercise the parallel paths
+    fn sorter() -> ParallelSort {
+        ParallelSort::new().with_threads(8).with_threshold(64)
+    }
+
+    #[test]
+    fn test_parallel_merge_sort_is_stable() {
+        let mut rng = StdRng::seed_from_u64(48);
+        for len in [0, 1, 63, 64, 65, 1000, 20_000] {
+            let values: Vec<(u8, usize)> = (0..len).map(|i| (rng.gen_range(0..10), i)).collect();
+            let mut expected = values.clone();
+            expected.sort_by_key(|v| v.0);
+            let mut actual = values;
+            sorter().merge_sort_by(&mut actual, |a, b| a.0.cmp(&b.0));
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_parallel_quicksort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(480);
+        for len in [0, 1, 63, 64, 65, 1000, 20_000] {
+            let n = len as i64;
+            let inputs: Vec<Vec<i64>> = vec![
+                (0..len).map(|_| rng.gen_range(-1000..1000)).collect(),
+                (0..n).collect(),
+                vec![1; len],
+            ];
+            for values in inputs {
+                let mut expected = values.clone();
+                expected.sort();
+                let mut actual = values;
+                sorter().quicksort_by(&mut actual, i64::cmp);
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_merge_runs_of_unequal_length() {
+        let mut rng = StdRng::seed_from_u64(4800);
+        for (n1, n2) in [(0, 500), (500, 0), (1, 999), (999, 1), (300, 700)] {
+            let mut left: Vec<(u8, bool)> =
+                (0..n1).map(|_| (rng.gen_range(0..20), false)).collect();
+            let mut right: Vec<(u8, bool)> =
+                (0..n2).map(|_| (rng.gen_range(0..20), true)).collect();
+            left.sort();
+            right.sort();
+            let mut expected = [left.clone(), right.clone()].concat();
+            expected.sort_by_key(|v| v.0);
+            let mut actual = vec![(0, false); n1 + n2];
+            sorter().p_merge(&left, &right, &mut actual, 8, &|a, b| a.0.cmp(&b.0));
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "comparator failed")]
+    fn test_panics_reach_the_caller() {
+        let mut arr: Vec<u32> = (0..10_000).rev().collect();
+        sorter().quicksort_by(&mut arr, |a, b| {
+            assert!(*a != 5000 && *b != 5000, "comparator failed");
+            a.cmp(b)
+        });
+    }
+}
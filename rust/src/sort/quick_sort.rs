@@ -1,20 +1,24 @@
+use super::{insertion_sort_by, sort_by_cached_key_with, stable_sort_by_positions, Sort};
+use crate::heap::heap_sort_by;
+use core::cmp::Ordering;
+
 /// 这段代码是使用 Rust 编写的快速排序算法的实现。具体来说：
 ///
 /// 1. `quickify` 函数是排序的主函数。如果给定数组（`arr`）的长度小于或等于 1，函数就直接返回，
-///   因为长度小于或等于 1 的数组已经被认为是有序的。然后，它会选择一个 "pivot" （即基准点或枢轴），
-///   然后将数组分为两部分。一部分包含所有小于或等于 pivot 的元素，另一部分包含所有大于 pivot 的元素。然后，
-///   它会递归地调用自己来对这两部分数组进行排序。
+///    因为长度小于或等于 1 的数组已经被认为是有序的。然后，它会选择一个 "pivot" （即基准点或枢轴），
+///    然后将数组分为两部分。一部分包含所有小于或等于 pivot 的元素，另一部分包含所有大于 pivot 的元素。然后，
+///    它会递归地调用自己来对这两部分数组进行排序。
 ///
 /// 2. `partition` 函数是为了划分数组。它的目标是调整数组的元素，使得所有小于或等于 pivot 的元素都在 pivot 的左边，
-///   而所有大于 pivot 的元素都在其右边。这个函数首先将最后一个元素设为 pivot。然后，它会遍历数组的其他元素。
-///   如果当前元素小于或等于 pivot，那么它就会将该元素与数组的第一个未处理的元素交换位置，然后增加这个未处理元素的计数器。
-///   最后，它会将 pivot 与第一个大于 pivot 的元素交换位置，从而确保 pivot 左边的所有元素都小于或等于它，
-///   而它右边的所有元素都大于它。函数最后返回 pivot 的位置。
+///    而所有大于 pivot 的元素都在其右边。这个函数首先将最后一个元素设为 pivot。然后，它会遍历数组的其他元素。
+///    如果当前元素小于或等于 pivot，那么它就会将该元素与数组的第一个未处理的元素交换位置，然后增加这个未处理元素的计数器。
+///    最后，它会将 pivot 与第一个大于 pivot 的元素交换位置，从而确保 pivot 左边的所有元素都小于或等于它，
+///    而它右边的所有元素都大于它。函数最后返回 pivot 的位置。
 ///
 /// 在整个过程中，`quickify`函数通过在每次调用`partition`函数后递归地对 pivot 左边和右边的部分进行排序，
 /// 以对整个数组进行排序。快速排序的平均时间复杂度为 O(n log n)，其中 n 是数组的大小。
 ///
-pub fn quickify<T: Ord + std::fmt::Debug>(arr: &mut [T]) {
+pub fn quickify<T: Ord>(arr: &mut [T]) {
     if arr.len() <= 1 {
         return;
     }
@@ -23,18 +27,8 @@ pub fn quickify<T: Ord + std::fmt::Debug>(arr: &mut [T]) {
     quickify(&mut arr[pivot + 1..]);
 }
 
-pub fn partition<T: Ord + std::fmt::Debug>(arr: &mut [T]) -> usize {
-    let pivot = arr.len() - 1;
-    let mut i = 0;
-    for j in 0..pivot {
-        if arr[j] <= arr[pivot] {
-            arr.swap(i, j);
-            i += 1;
-        }
-    }
-    arr.swap(i, pivot);
-    println!("arr: {:?}", arr);
-    i
+pub fn partition<T: Ord>(arr: &mut [T]) -> usize {
+    lomuto_partition_by(arr, T::cmp)
 }
 
 /// 当然可以，我来给你解释一下。
@@ -46,13 +40,13 @@ pub fn partition<T: Ord + std::fmt::Debug>(arr: &mut [T]) -> usize {
 /// 2. 初始化 `i = 0`，这是一个“指针”，它指向我们要交换的下一个元素。
 ///
 /// 3. 我们遍历数组中除 `pivot` 的其他元素。对于每个元素 `j`，如果 `arr[j] <= pivot`，
-///   那么我们就交换 `arr[i]` 和 `arr[j]`，然后 `i` 自增 1。这样做的目的是将所有小于或等于 `pivot` 的元素移动到数组的左侧。
+///    那么我们就交换 `arr[i]` 和 `arr[j]`，然后 `i` 自增 1。这样做的目的是将所有小于或等于 `pivot` 的元素移动到数组的左侧。
 ///
 /// 4. 当我们完成遍历时，`arr` 可能看起来像这样：`[5, 2, 3, 11, 12, 9, 14, 7, 10, 6]`。
-///   现在，`i = 3`，也就是 `arr[i] = 11`。注意到所有小于等于 `pivot`（6）的元素都在左侧，而所有大于 `pivot` 的元素都在右侧。但是，`pivot` 本身还在数组的最右侧。
+///    现在，`i = 3`，也就是 `arr[i] = 11`。注意到所有小于等于 `pivot`（6）的元素都在左侧，而所有大于 `pivot` 的元素都在右侧。但是，`pivot` 本身还在数组的最右侧。
 ///
 /// 5. 我们将 `pivot` 与 `arr[i]` 交换，得到：`[5, 2, 3, 6, 12, 9, 14, 7, 10, 11]`。
-///   这样就确保了 `pivot` 的左侧都是小于或等于它的元素，右侧都是大于它的元素。
+///    这样就确保了 `pivot` 的左侧都是小于或等于它的元素，右侧都是大于它的元素。
 ///
 /// 6. 最后，`partition` 函数返回 `pivot` 的位置，也就是 `i` 的值，它是 3。
 ///
@@ -77,3 +71,411 @@ fn test_quickify() {
     quickify(&mut arr);
     assert_eq!(arr, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
 }
+
+/// How `IntroSort` partitions around the pivot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartitionScheme {
+    /// Single scan with the pivot last, CLRS `PARTITION`
+    Lomuto,
+    /// Two scans towards each other, which split runs of equal elements evenly
+    #[default]
+    Hoare,
+    /// Dutch national flag, which gathers the elements equal to the pivot
+    ThreeWay,
+}
+
+// slices this short are finished by insertion sort
+const INSERTION_CUTOFF: usize = 16;
+// slices this long take the pivot from a ninther instead of median of three
+const NINTHER_THRESHOLD: usize = 128;
+
+/// Introsort, quicksort that falls back to heapsort on bad pivots
+///
+/// Pivots are the median of three, or Tukey's ninther for long slices.
+/// The recursion goes into the smaller side and loops on the larger one, so
+/// the stack stays O(lg n) deep, and after 2 lg n levels the rest of the slice
+/// is heapsorted, so the worst case is O(n lg n).
+///
+/// Introsort is not stable, `sort_by` sorts the element positions with ties
+/// broken by position, which costs O(n) extra space.
+///
+/// ```rust
+/// use algorithms_rs::sort::{IntroSort, PartitionScheme, Sort};
+///
+/// let mut intro = IntroSort::from(vec![5, 1, 4, 1, 5, 9, 2, 6]).with_scheme(PartitionScheme::ThreeWay);
+///
+/// intro.sort_unstable_by(|a, b| b.cmp(a));
+///
+/// assert_eq!(intro.inner(), vec![9, 6, 5, 5, 4, 2, 1, 1]);
+/// ```
+#[derive(Debug)]
+pub struct IntroSort<T> {
+    arr: Vec<T>,
+    scheme: PartitionScheme,
+}
+
+impl<T> IntroSort<T> {
+    /// Choosing how to partition, Hoare by default
+    pub fn with_scheme(mut self, scheme: PartitionScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+}
+
+impl<T> From<Vec<T>> for IntroSort<T> {
+    fn from(arr: Vec<T>) -> Self {
+        Self {
+            arr,
+            scheme: PartitionScheme::default(),
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for IntroSort<T> {
+    fn from(arr: &[T]) -> Self {
+        Self::from(arr.to_vec())
+    }
+}
+
+impl<T: PartialOrd + Clone> Sort<T> for IntroSort<T> {
+    fn inner(&self) -> Vec<T> {
+        self.arr.clone()
+    }
+
+    fn sort_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let scheme = self.scheme;
+        stable_sort_by_positions(&mut self.arr, f, |order, compare| {
+            introsort_with(order, scheme, compare)
+        });
+    }
+
+    fn sort_unstable_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        introsort_with(&mut self.arr, self.scheme, f)
+    }
+
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let scheme = self.scheme;
+        sort_by_cached_key_with(&mut self.arr, f, |order, compare| {
+            introsort_with(order, scheme, compare)
+        });
+    }
+}
+
+/// Introsort a slice in place in ascending order
+pub fn introsort<T: Ord>(arr: &mut [T]) {
+    introsort_by(arr, T::cmp)
+}
+
+/// Unstable introsort of a slice in place with a comparator
+pub fn introsort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    introsort_with(arr, PartitionScheme::default(), compare)
+}
+
+/// Unstable introsort of a slice in place with a partition scheme and a comparator
+pub fn introsort_with<T, F>(arr: &mut [T], scheme: PartitionScheme, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let depth_limit = 2 * arr.len().max(1).ilog2();
+    inner_introsort(arr, scheme, depth_limit, &mut compare);
+}
+
+fn inner_introsort<T, F>(
+    mut arr: &mut [T],
+    scheme: PartitionScheme,
+    mut depth: u32,
+    compare: &mut F,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        if arr.len() <= INSERTION_CUTOFF {
+            insertion_sort_by(arr, &mut *compare);
+            return;
+        }
+        if depth == 0 {
+            heap_sort_by(arr, &mut *compare);
+            return;
+        }
+        depth -= 1;
+
+        let pivot = choose_pivot(arr, compare);
+        let (left_end, right_start) = match scheme {
+            PartitionScheme::Lomuto => {
+                let last = arr.len() - 1;
+                arr.swap(pivot, last);
+                let mid = lomuto_partition_by(arr, &mut *compare);
+                (mid, mid + 1)
+            }
+            PartitionScheme::Hoare => {
+                arr.swap(0, pivot);
+                let mid = hoare_partition_by(arr, &mut *compare);
+                (mid, mid + 1)
+            }
+            PartitionScheme::ThreeWay => {
+                arr.swap(0, pivot);
+                three_way_partition_by(arr, &mut *compare)
+            }
+        };
+
+        let (left, rest) = core::mem::take(&mut arr).split_at_mut(left_end);
+        let right = &mut rest[right_start - left_end..];
+        // recurse into the smaller side and loop on the larger one
+        if left.len() < right.len() {
+            inner_introsort(left, scheme, depth, compare);
+            arr = right;
+        } else {
+            inner_introsort(right, scheme, depth, compare);
+            arr = left;
+        }
+    }
+}
+
+// Index of a pivot candidate, the median of the first, middle and last
+// elements, or for long slices the median of three such medians.
+pub(super) fn choose_pivot<T, F>(arr: &[T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
+    let (lo, mid, hi) = (0, len / 2, len - 1);
+    if len < NINTHER_THRESHOLD {
+        return median_of_three(arr, lo, mid, hi, compare);
+    }
+    let step = len / 8;
+    let a = median_of_three(arr, lo, lo + step, lo + 2 * step, compare);
+    let b = median_of_three(arr, mid - step, mid, mid + step, compare);
+    let c = median_of_three(arr, hi - 2 * step, hi - step, hi, compare);
+    median_of_three(arr, a, b, c, compare)
+}
+
+fn median_of_three<T, F>(arr: &[T], mut a: usize, mut b: usize, c: usize, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if compare(&arr[b], &arr[a]) == Ordering::Less {
+        core::mem::swap(&mut a, &mut b);
+    }
+    // now arr[a] <= arr[b], if arr[c] is below both the median is the larger of a and c
+    if compare(&arr[c], &arr[b]) == Ordering::Less {
+        b = if compare(&arr[c], &arr[a]) == Ordering::Less {
+            a
+        } else {
+            c
+        };
+    }
+    b
+}
+
+/// Lomuto partition around the last element, returning its final index
+///
+/// ```no
+/// PARTITION(A, p, r)
+///     x = A[r]
+///     i = p - 1
+///     for j = p to r - 1
+///         if A[j] <= x
+///             i = i + 1
+///             exchange A[i] with A[j]
+///     exchange A[i + 1] with A[r]
+///     return i + 1
+/// ```
+pub fn lomuto_partition_by<T, F>(arr: &mut [T], mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let pivot = arr.len() - 1;
+    let mut i = 0;
+    for j in 0..pivot {
+        if compare(&arr[j], &arr[pivot]) != Ordering::Greater {
+            arr.swap(i, j);
+            i += 1;
+        }
+    }
+    arr.swap(i, pivot);
+    i
+}
+
+/// Hoare partition around the first element, returning its final index
+///
+/// Both scans stop at elements equal to the pivot, so runs of equal
+/// elements are split evenly between the two sides.
+pub fn hoare_partition_by<T, F>(arr: &mut [T], mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (mut i, mut j) = (1, arr.len() - 1);
+    loop {
+        // arr[1..i] <= pivot and arr[j + 1..] >= pivot
+        while i <= j && compare(&arr[i], &arr[0]) == Ordering::Less {
+            i += 1;
+        }
+        while i <= j && compare(&arr[0], &arr[j]) == Ordering::Less {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        arr.swap(i, j);
+        i += 1;
+        j -= 1;
+    }
+    arr.swap(0, j);
+    j
+}
+
+/// Three-way partition around the first element
+///
+/// Returns `(lt, gt)` such that `arr[..lt]` is less than the pivot,
+/// `arr[lt..gt]` is equal to it and `arr[gt..]` is greater.
+pub fn three_way_partition_by<T, F>(arr: &mut [T], mut compare: F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // arr[lt] is always an element equal to the pivot
+    let (mut lt, mut i, mut gt) = (0, 1, arr.len());
+    while i < gt {
+        match compare(&arr[i], &arr[lt]) {
+            Ordering::Less => {
+                arr.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                arr.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (lt, gt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const SCHEMES: [PartitionScheme; 3] = [
+        PartitionScheme::Lomuto,
+        PartitionScheme::Hoare,
+        PartitionScheme::ThreeWay,
+    ];
+
+    fn patterns(rng: &mut StdRng, len: usize) -> Vec<Vec<i32>> {
+        let n = len as i32;
+        vec![
+            (0..len).map(|_| rng.gen_range(-1000..1000)).collect(),
+            (0..len).map(|_| rng.gen_range(0..3)).collect(),
+            (0..n).collect(),
+            (0..n).rev().collect(),
+            vec![7; len],
+            // organ pipe
+            (0..n).map(|i| i.min(n - i)).collect(),
+        ]
+    }
+
+    #[test]
+    fn test_introsort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(44);
+        for len in [0, 1, 2, 15, 16, 17, 100, 127, 128, 1000] {
+            for values in patterns(&mut rng, len) {
+                let mut expected = values.clone();
+                expected.sort();
+                for scheme in SCHEMES {
+                    let mut actual = values.clone();
+                    introsort_with(&mut actual, scheme, i32::cmp);
+                    assert_eq!(actual, expected, "{scheme:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparisons_stay_n_lg_n() {
+        let len = 1 << 12;
+        let mut rng = StdRng::seed_from_u64(440);
+        for values in patterns(&mut rng, len) {
+            for scheme in SCHEMES {
+                let mut comparisons = 0usize;
+                let mut actual = values.clone();
+                introsort_with(&mut actual, scheme, |a, b| {
+                    comparisons += 1;
+                    a.cmp(b)
+                });
+                assert!(comparisons < 4 * len * 12, "{scheme:?}: {comparisons}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_heapsort_fallback() {
+        let mut rng = StdRng::seed_from_u64(4400);
+        let mut values: Vec<i32> = (0..500).map(|_| rng.gen_range(0..100)).collect();
+        let mut expected = values.clone();
+        expected.sort();
+        inner_introsort(&mut values, PartitionScheme::Hoare, 0, &mut i32::cmp);
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn test_partitions() {
+        let mut rng = StdRng::seed_from_u64(44000);
+        for len in 1..50 {
+            let values: Vec<i32> = (0..len).map(|_| rng.gen_range(0..5)).collect();
+
+            let mut arr = values.clone();
+            let pivot = arr[len - 1];
+            let mid = lomuto_partition_by(&mut arr, i32::cmp);
+            assert_eq!(arr[mid], pivot);
+            assert!(
+                arr[..mid].iter().all(|v| *v <= pivot) && arr[mid..].iter().all(|v| *v >= pivot)
+            );
+
+            let mut arr = values.clone();
+            let pivot = arr[0];
+            let mid = hoare_partition_by(&mut arr, i32::cmp);
+            assert_eq!(arr[mid], pivot);
+            assert!(
+                arr[..mid].iter().all(|v| *v <= pivot) && arr[mid..].iter().all(|v| *v >= pivot)
+            );
+
+            let mut arr = values;
+            let pivot = arr[0];
+            let (lt, gt) = three_way_partition_by(&mut arr, i32::cmp);
+            assert!(arr[..lt].iter().all(|v| *v < pivot));
+            assert!(arr[lt..gt].iter().all(|v| *v == pivot));
+            assert!(arr[gt..].iter().all(|v| *v > pivot));
+        }
+    }
+
+    #[test]
+    fn test_stable_sort_by_through_trait() {
+        let mut rng = StdRng::seed_from_u64(440000);
+        let values: Vec<(u8, usize)> = (0..300).map(|i| (rng.gen_range(0..4), i)).collect();
+        let mut expected = values.clone();
+        expected.sort_by_key(|v| v.0);
+        for scheme in SCHEMES {
+            let mut intro = IntroSort::from(values.clone()).with_scheme(scheme);
+            intro.sort_by_key(|v| v.0);
+            assert_eq!(intro.inner(), expected);
+            let mut intro = IntroSort::from(values.clone()).with_scheme(scheme);
+            intro.sort_by_cached_key(|v| v.0);
+            assert_eq!(intro.inner(), expected);
+        }
+    }
+}
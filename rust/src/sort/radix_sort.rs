@@ -0,0 +1,357 @@
+//! Radix sort, CLRS 8.3
+//!
+//! Keys are split into bytes whose unsigned order is the order of the keys:
+//! signed integers flip their sign bit, and floats flip their sign bit when
+//! positive or every bit when negative, which gives the `total_cmp` order.
+//! The LSD sort makes a stable counting sort pass per byte, least significant
+//! first, skipping bytes that every key shares. The MSD sort distributes the
+//! elements in place by their most significant byte, American flag style,
+//! and goes on with each bucket, which also handles variable-length byte
+//! strings.
+use super::insertion_sort_by;
+use core::cmp::Ordering;
+
+// buckets this small are finished by insertion sort
+const INSERTION_CUTOFF: usize = 32;
+
+/// Key that splits into bytes ordered like the key
+pub trait RadixKey: Copy {
+    /// Number of bytes in the key
+    const BYTES: usize;
+
+    /// The i-th byte, counting from the least significant
+    fn radix_byte(&self, i: usize) -> u8;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = core::mem::size_of::<$t>();
+
+                fn radix_byte(&self, i: usize) -> u8 {
+                    (*self >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = core::mem::size_of::<$t>();
+
+                fn radix_byte(&self, i: usize) -> u8 {
+                    let bits = (*self as $u) ^ (1 << (<$u>::BITS - 1));
+                    (bits >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_float {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = core::mem::size_of::<$t>();
+
+                fn radix_byte(&self, i: usize) -> u8 {
+                    let bits = self.to_bits();
+                    let sign = 1 << (8 * Self::BYTES - 1);
+                    let bits = if bits & sign == 0 { bits | sign } else { !bits };
+                    (bits >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+impl_radix_key_float!(f32, f64);
+
+/// Stable LSD radix sort, in O(n) time per byte of the key
+///
+/// ```no
+/// RADIX-SORT(A, d)
+///     for i = 1 to d
+///         use a stable sort to sort array A on digit i
+/// ```
+///
+/// ```rust
+/// use algorithms_rs::sort::radix_sort;
+///
+/// let mut ints = [170, -45, 75, -90, 802, 24, 2, 66];
+/// radix_sort(&mut ints);
+/// assert_eq!(ints, [-90, -45, 2, 24, 66, 75, 170, 802]);
+///
+/// let mut floats = [0.5, -0.0, -2.5, f64::INFINITY, 0.0];
+/// radix_sort(&mut floats);
+/// assert_eq!(floats, [-2.5, -0.0, 0.0, 0.5, f64::INFINITY]);
+/// ```
+pub fn radix_sort<T: RadixKey>(arr: &mut [T]) {
+    let len = arr.len();
+    let mut buf = arr.to_vec();
+    let mut src: &mut [T] = arr;
+    let mut dst: &mut [T] = &mut buf;
+    let mut flipped = false;
+    for byte in 0..T::BYTES {
+        let mut starts = [0usize; 256];
+        for v in src.iter() {
+            starts[v.radix_byte(byte) as usize] += 1;
+        }
+        if starts.contains(&len) {
+            continue;
+        }
+        let mut sum = 0;
+        for start in starts.iter_mut() {
+            (*start, sum) = (sum, sum + *start);
+        }
+        for v in src.iter() {
+            let b = v.radix_byte(byte) as usize;
+            dst[starts[b]] = *v;
+            starts[b] += 1;
+        }
+        core::mem::swap(&mut src, &mut dst);
+        flipped = !flipped;
+    }
+    // after an odd number of passes the result is in the buffer
+    if flipped {
+        dst.copy_from_slice(src);
+    }
+}
+
+/// Unstable in-place MSD radix sort
+///
+/// ```rust
+/// use algorithms_rs::sort::msd_radix_sort;
+///
+/// let mut keys = [0xbeef_u32, 0xdead, 0xbeef_0000, 7];
+/// msd_radix_sort(&mut keys);
+/// assert_eq!(keys, [7, 0xbeef, 0xdead, 0xbeef_0000]);
+/// ```
+pub fn msd_radix_sort<T: RadixKey>(arr: &mut [T]) {
+    if T::BYTES > 0 {
+        inner_msd_radix_sort(arr, T::BYTES - 1);
+    }
+}
+
+fn inner_msd_radix_sort<T: RadixKey>(arr: &mut [T], byte: usize) {
+    if arr.len() <= INSERTION_CUTOFF {
+        insertion_sort_by(arr, |a, b| radix_cmp(a, b, byte));
+        return;
+    }
+    let bounds = american_flag(arr, 256, |v| v.radix_byte(byte) as usize);
+    if byte > 0 {
+        for b in 0..256 {
+            inner_msd_radix_sort(&mut arr[bounds[b]..bounds[b + 1]], byte - 1);
+        }
+    }
+}
+
+// compare the bytes `byte` down to 0, the higher ones being equal
+fn radix_cmp<T: RadixKey>(a: &T, b: &T, byte: usize) -> Ordering {
+    (0..=byte)
+        .rev()
+        .map(|i| a.radix_byte(i).cmp(&b.radix_byte(i)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Unstable in-place MSD radix sort of byte strings in lexicographic order
+///
+/// ```rust
+/// use algorithms_rs::sort::msd_radix_sort_bytes;
+///
+/// let mut words = ["she", "sells", "sea", "shells", "by", "the", "sea", "shore"];
+/// msd_radix_sort_bytes(&mut words);
+/// assert_eq!(words, ["by", "sea", "sea", "sells", "she", "shells", "shore", "the"]);
+/// ```
+pub fn msd_radix_sort_bytes<S: AsRef<[u8]>>(arr: &mut [S]) {
+    // buckets still to sort as (start, end, depth), where every string is at
+    // least depth bytes long and they share that prefix; kept on a stack as
+    // shared prefixes, and so the depth, can be as long as the strings
+    let mut work = vec![(0, arr.len(), 0)];
+    while let Some((start, end, mut depth)) = work.pop() {
+        let bucket = &mut arr[start..end];
+        if bucket.len() <= INSERTION_CUTOFF {
+            insertion_sort_by(bucket, |a, b| a.as_ref()[depth..].cmp(&b.as_ref()[depth..]));
+            continue;
+        }
+        // a pass per byte that every string shares would find a single bucket
+        let first = &bucket[0].as_ref()[depth..];
+        depth += bucket[1..]
+            .iter()
+            .map(|s| {
+                let rest = &s.as_ref()[depth..];
+                first.iter().zip(rest).take_while(|(a, b)| a == b).count()
+            })
+            .min()
+            .unwrap_or(0);
+        // bucket 0 holds the strings that end here, they are all equal
+        let bounds = american_flag(bucket, 257, |s| {
+            s.as_ref().get(depth).map_or(0, |b| *b as usize + 1)
+        });
+        for b in 1..257 {
+            if bounds[b + 1] - bounds[b] > 1 {
+                work.push((start + bounds[b], start + bounds[b + 1], depth + 1));
+            }
+        }
+    }
+}
+
+// Distribute arr in place by digit, returning the radix + 1 bucket bounds.
+// Each element is swapped straight into the next free slot of its bucket.
+fn american_flag<T, D>(arr: &mut [T], radix: usize, digit: D) -> Vec<usize>
+where
+    D: Fn(&T) -> usize,
+{
+    let mut bounds = vec![0usize; radix + 1];
+    for v in arr.iter() {
+        bounds[digit(v) + 1] += 1;
+    }
+    for b in 1..bounds.len() {
+        bounds[b] += bounds[b - 1];
+    }
+    let mut next = bounds[..radix].to_vec();
+    for b in 0..radix {
+        while next[b] < bounds[b + 1] {
+            let d = digit(&arr[next[b]]);
+            if d == b {
+                next[b] += 1;
+            } else {
+                arr.swap(next[b], next[d]);
+                next[d] += 1;
+            }
+        }
+    }
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::{Distribution, Standard};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn check<T>(rng: &mut StdRng, compare: fn(&T, &T) -> Ordering)
+    where
+        T: RadixKey + PartialEq + core::fmt::Debug,
+        Standard: Distribution<T>,
+    {
+        for len in [0, 1, 2, 31, 32, 33, 100, 1000, 5000] {
+            let values: Vec<T> = (0..len).map(|_| rng.gen()).collect();
+            let mut expected = values.clone();
+            expected.sort_by(compare);
+            let mut lsd = values.clone();
+            radix_sort(&mut lsd);
+            assert_eq!(lsd, expected);
+            let mut msd = values;
+            msd_radix_sort(&mut msd);
+            assert_eq!(msd, expected);
+        }
+    }
+
+    #[test]
+    fn test_integers_match_std() {
+        let mut rng = StdRng::seed_from_u64(470);
+        check::<u8>(&mut rng, u8::cmp);
+        check::<u32>(&mut rng, u32::cmp);
+        check::<u64>(&mut rng, u64::cmp);
+        check::<u128>(&mut rng, u128::cmp);
+        check::<i16>(&mut rng, i16::cmp);
+        check::<i32>(&mut rng, i32::cmp);
+        check::<i64>(&mut rng, i64::cmp);
+        check::<usize>(&mut rng, usize::cmp);
+    }
+
+    #[test]
+    fn test_small_keys_skip_passes() {
+        let mut rng = StdRng::seed_from_u64(4700);
+        let values: Vec<u32> = (0..10_000).map(|_| rng.gen_range(0..200)).collect();
+        let mut expected = values.clone();
+        expected.sort();
+        let mut actual = values;
+        radix_sort(&mut actual);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_floats_follow_total_order() {
+        let mut rng = StdRng::seed_from_u64(47000);
+        let specials = [
+            0.0,
+            -0.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            -f64::NAN,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::MIN,
+        ];
+        let mut values: Vec<f64> = (0..500).map(|_| rng.gen_range(-1e6..1e6)).collect();
+        values.extend(specials);
+        let bits = |values: &[f64]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        let mut expected = values.clone();
+        expected.sort_by(f64::total_cmp);
+        let mut lsd = values.clone();
+        radix_sort(&mut lsd);
+        assert_eq!(bits(&lsd), bits(&expected));
+        let mut msd = values.clone();
+        msd_radix_sort(&mut msd);
+        assert_eq!(bits(&msd), bits(&expected));
+
+        let mut singles: Vec<f32> = values.iter().map(|v| *v as f32).collect();
+        let mut expected: Vec<f32> = singles.clone();
+        expected.sort_by(f32::total_cmp);
+        radix_sort(&mut singles);
+        let bits = |values: &[f32]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&singles), bits(&expected));
+    }
+
+    #[test]
+    fn test_byte_strings_match_std() {
+        let mut rng = StdRng::seed_from_u64(470000);
+        for len in [0, 1, 40, 2000] {
+            // short strings over a small alphabet share long prefixes
+            let values: Vec<Vec<u8>> = (0..len)
+                .map(|_| {
+                    let n = rng.gen_range(0..8);
+                    (0..n).map(|_| rng.gen_range(b'a'..b'e')).collect()
+                })
+                .collect();
+            let mut expected = values.clone();
+            expected.sort();
+            let mut actual = values;
+            msd_radix_sort_bytes(&mut actual);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_long_shared_prefixes() {
+        // identical long strings used to take a level of recursion per byte
+        let line = vec![b'x'; 200_000];
+        let mut lines = vec![line.clone(); 40];
+        msd_radix_sort_bytes(&mut lines);
+        assert!(lines.iter().all(|l| *l == line));
+
+        let mut rng = StdRng::seed_from_u64(4700000);
+        let mut values: Vec<Vec<u8>> = (0..200)
+            .map(|_| {
+                let mut value = line[..5000].to_vec();
+                value.extend((0..rng.gen_range(0..4)).map(|_| rng.gen_range(b'a'..b'c')));
+                value
+            })
+            .collect();
+        let mut expected = values.clone();
+        expected.sort();
+        msd_radix_sort_bytes(&mut values);
+        assert_eq!(values, expected);
+    }
+}
@@ -0,0 +1,287 @@
+//! Randomized quicksort and selection, CLRS 7.3 and 9.2
+//!
+//! Swapping a uniformly random element into the pivot slot before every
+//! partition makes the expected running time O(n lg n) for sorting and
+//! O(n) for selecting the k-th smallest element, whatever the input order.
+//! Unlike CLRS the partition is three-way, so elements equal to the pivot are
+//! set aside at once and duplicates cannot make either quadratic.
+//! The random source is any `rand::Rng`, so a seeded generator gives
+//! reproducible runs.
+use super::{sort_by_cached_key_with, stable_sort_by_positions, three_way_partition_by, Sort};
+use core::cmp::Ordering;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Randomized quicksort
+///
+/// `sort_by` breaks ties by position, which makes it stable.
+///
+/// ```rust
+/// use algorithms_rs::sort::{RandomizedQuickSort, Sort};
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let mut quick = RandomizedQuickSort::from(vec![3, 1, 4, 1, 5, 9, 2, 6])
+///     .with_rng(StdRng::seed_from_u64(7));
+///
+/// assert_eq!(*quick.randomized_select(2), 2);
+///
+/// quick.sort();
+/// assert_eq!(quick.inner(), vec![1, 1, 2, 3, 4, 5, 6, 9]);
+/// ```
+#[derive(Debug)]
+pub struct RandomizedQuickSort<T, R = StdRng> {
+    arr: Vec<T>,
+    rng: R,
+}
+
+impl<T, R> RandomizedQuickSort<T, R> {
+    /// Replacing the random source, such as with a seeded generator
+    pub fn with_rng<S: Rng>(self, rng: S) -> RandomizedQuickSort<T, S> {
+        RandomizedQuickSort { arr: self.arr, rng }
+    }
+}
+
+impl<T: Ord, R: Rng> RandomizedQuickSort<T, R> {
+    /// The k-th smallest element, counting from 0, in expected O(n) time
+    ///
+    /// The elements are left partitioned around it.
+    pub fn randomized_select(&mut self, k: usize) -> &T {
+        randomized_select_by(&mut self.arr, k, &mut self.rng, T::cmp)
+    }
+}
+
+impl<T> From<Vec<T>> for RandomizedQuickSort<T> {
+    fn from(arr: Vec<T>) -> Self {
+        Self {
+            arr,
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for RandomizedQuickSort<T> {
+    fn from(arr: &[T]) -> Self {
+        Self::from(arr.to_vec())
+    }
+}
+
+impl<T: PartialOrd + Clone, R: Rng> Sort<T> for RandomizedQuickSort<T, R> {
+    fn inner(&self) -> Vec<T> {
+        self.arr.clone()
+    }
+
+    fn sort_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let rng = &mut self.rng;
+        stable_sort_by_positions(&mut self.arr, f, |order, compare| {
+            randomized_quicksort_by(order, rng, compare)
+        });
+    }
+
+    fn sort_unstable_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        randomized_quicksort_by(&mut self.arr, &mut self.rng, f)
+    }
+
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let rng = &mut self.rng;
+        sort_by_cached_key_with(&mut self.arr, f, |order, compare| {
+            randomized_quicksort_by(order, rng, compare)
+        });
+    }
+}
+
+/// Unstable randomized quicksort of a slice in place with a comparator
+///
+/// ```no
+/// RANDOMIZED-QUICKSORT(A, p, r)
+///     if p < r
+///         q = RANDOMIZED-PARTITION(A, p, r)
+///         RANDOMIZED-QUICKSORT(A, p, q - 1)
+///         RANDOMIZED-QUICKSORT(A, q + 1, r)
+/// ```
+pub fn randomized_quicksort_by<T, R, F>(arr: &mut [T], rng: &mut R, mut compare: F)
+where
+    R: Rng + ?Sized,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    inner_randomized_quicksort(arr, rng, &mut compare)
+}
+
+fn inner_randomized_quicksort<T, R, F>(mut arr: &mut [T], rng: &mut R, compare: &mut F)
+where
+    R: Rng + ?Sized,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while arr.len() > 1 {
+        let (lt, gt) = randomized_partition(arr, rng, compare);
+        let (left, rest) = core::mem::take(&mut arr).split_at_mut(lt);
+        let right = &mut rest[gt - lt..];
+        // recurse into the smaller side so the stack stays O(lg n) deep
+        if left.len() < right.len() {
+            inner_randomized_quicksort(left, rng, compare);
+            arr = right;
+        } else {
+            inner_randomized_quicksort(right, rng, compare);
+            arr = left;
+        }
+    }
+}
+
+/// The k-th smallest element of a slice, counting from 0, in expected O(n) time
+///
+/// The slice is rearranged so that the element lands at index `k`, with no
+/// greater element before it and no smaller one after it.
+///
+/// ```no
+/// RANDOMIZED-SELECT(A, p, r, i)
+///     if p == r
+///         return A[p]
+///     q = RANDOMIZED-PARTITION(A, p, r)
+///     k = q - p + 1
+///     if i == k
+///         return A[q]
+///     elseif i < k
+///         return RANDOMIZED-SELECT(A, p, q - 1, i)
+///     else return RANDOMIZED-SELECT(A, q + 1, r, i - k)
+/// ```
+///
+/// # Panics
+///
+/// Panics if `k` is not less than the length of the slice.
+pub fn randomized_select_by<'a, T, R, F>(
+    arr: &'a mut [T],
+    k: usize,
+    rng: &mut R,
+    mut compare: F,
+) -> &'a T
+where
+    R: Rng + ?Sized,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(
+        k < arr.len(),
+        "index {k} out of range for length {}",
+        arr.len()
+    );
+    let (mut lo, mut hi) = (0, arr.len());
+    while hi - lo > 1 {
+        let (lt, gt) = randomized_partition(&mut arr[lo..hi], rng, &mut compare);
+        if k < lo + lt {
+            hi = lo + lt;
+        } else if k >= lo + gt {
+            lo += gt;
+        } else {
+            // k is in the band of elements equal to the pivot
+            break;
+        }
+    }
+    &arr[k]
+}
+
+/// The k-th smallest element of a slice, counting from 0, in expected O(n) time
+pub fn randomized_select<'a, T, R>(arr: &'a mut [T], k: usize, rng: &mut R) -> &'a T
+where
+    T: Ord,
+    R: Rng + ?Sized,
+{
+    randomized_select_by(arr, k, rng, T::cmp)
+}
+
+// swap a random element into the pivot slot, then partition three ways,
+// returning the bounds of the elements equal to the pivot
+fn randomized_partition<T, R, F>(arr: &mut [T], rng: &mut R, compare: &mut F) -> (usize, usize)
+where
+    R: Rng + ?Sized,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let i = rng.gen_range(0..arr.len());
+    arr.swap(0, i);
+    three_way_partition_by(arr, compare)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_randomized_quicksort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(45);
+        for len in 0..200 {
+            let values: Vec<i32> = (0..len).map(|_| rng.gen_range(-500..500)).collect();
+            let mut expected = values.clone();
+            expected.sort();
+            let mut ascending = values.clone();
+            ascending.sort();
+            for mut actual in [values, ascending] {
+                randomized_quicksort_by(&mut actual, &mut rng, i32::cmp);
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_randomized_select_every_rank() {
+        let mut rng = StdRng::seed_from_u64(450);
+        for len in 1..60 {
+            let values: Vec<i32> = (0..len).map(|_| rng.gen_range(0..20)).collect();
+            let mut sorted = values.clone();
+            sorted.sort();
+            for (k, expected) in sorted.iter().enumerate() {
+                let mut arr = values.clone();
+                assert_eq!(randomized_select(&mut arr, k, &mut rng), expected);
+                assert!(arr[..k].iter().all(|v| v <= expected));
+                assert!(arr[k..].iter().all(|v| v >= expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_equal_elements_take_linear_time() {
+        let mut rng = StdRng::seed_from_u64(4500);
+        let len = 20_000;
+        let mut comparisons = 0;
+        let mut values = vec![7u32; len];
+        randomized_select_by(&mut values, len / 2, &mut rng, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert!(comparisons < len, "{comparisons}");
+
+        comparisons = 0;
+        let mut values: Vec<u32> = (0..len).map(|i| i as u32 % 3).collect();
+        randomized_quicksort_by(&mut values, &mut rng, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        assert!(comparisons < 3 * len, "{comparisons}");
+    }
+
+    #[test]
+    fn test_seeded_runs_are_reproducible() {
+        let values: Vec<u32> = (0..500).map(|i| (i * 7919) % 1000).collect();
+        let run = |seed| {
+            let mut comparisons = 0;
+            let mut quick =
+                RandomizedQuickSort::from(values.clone()).with_rng(StdRng::seed_from_u64(seed));
+            quick.sort_unstable_by(|a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            assert!(quick.is_sort());
+            comparisons
+        };
+        assert_eq!(run(4500), run(4500));
+        assert_ne!(run(4500), run(4501));
+    }
+}
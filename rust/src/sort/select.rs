@@ -0,0 +1,336 @@
+//! Selection in worst-case linear time, CLRS 9.3
+//!
+//! The pivot is the median of the medians of groups of five, which has at
+//! least 3n/10 - 6 elements on either side, so each round discards a constant
+//! fraction of the elements and SELECT takes O(n) time even on adversarial
+//! input. The same pivot can stand in for a random one in quicksort.
+use super::{insertion_sort_by, three_way_partition_by};
+use core::cmp::Ordering;
+use thiserror::Error;
+
+/// Errors from computing quantiles
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum QuantileError {
+    /// there are no values to take quantiles of
+    #[error("no values to take quantiles of")]
+    Empty,
+    /// the probability is not within [0, 1]
+    #[error("probability {0} is not within [0, 1]")]
+    InvalidProbability(f64),
+    /// a value is NaN
+    #[error("the values contain NaN")]
+    NotANumber,
+}
+
+// size of the groups, and slices this short are sorted directly
+const GROUP: usize = 5;
+
+/// Reorder a slice so the element at `k` is the one that would be there if it were sorted
+///
+/// Returns the elements before `k`, none greater than the k-th, the k-th
+/// element itself and the elements after it, none smaller.
+///
+/// ```rust
+/// use algorithms_rs::sort::nth_element;
+///
+/// let mut arr = [9, 1, 8, 2, 7, 3, 6, 4, 5];
+/// let (below, nth, above) = nth_element(&mut arr, 3);
+///
+/// assert_eq!(*nth, 4);
+/// assert!(below.iter().all(|v| *v < 4) && above.iter().all(|v| *v > 4));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `k` is not less than the length of the slice.
+pub fn nth_element<T: Ord>(arr: &mut [T], k: usize) -> (&mut [T], &mut T, &mut [T]) {
+    nth_element_by(arr, k, T::cmp)
+}
+
+/// Reorder a slice with a comparator so the element at `k` is in its sorted position
+///
+/// # Panics
+///
+/// Panics if `k` is not less than the length of the slice.
+pub fn nth_element_by<T, F>(arr: &mut [T], k: usize, mut compare: F) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(
+        k < arr.len(),
+        "index {k} out of range for length {}",
+        arr.len()
+    );
+    inner_select(arr, k, &mut compare);
+    let (below, rest) = arr.split_at_mut(k);
+    let (nth, above) = rest.split_first_mut().expect("k is in range");
+    (below, nth, above)
+}
+
+/// The k-th smallest element, counting from 0, in worst-case O(n) time
+///
+/// ```no
+/// SELECT(A, i)
+///     divide A into groups of five and sort each group
+///     x = SELECT(medians of the groups, their lower median)
+///     partition A around x, into L < x, E == x and G > x
+///     if i < |L| return SELECT(L, i)
+///     elseif i < |L| + |E| return x
+///     else return SELECT(G, i - |L| - |E|)
+/// ```
+///
+/// # Panics
+///
+/// Panics if `k` is not less than the length of the slice.
+pub fn select<T: Ord>(arr: &mut [T], k: usize) -> &T {
+    nth_element(arr, k).1
+}
+
+/// The k-th smallest element under a comparator, in worst-case O(n) time
+///
+/// # Panics
+///
+/// Panics if `k` is not less than the length of the slice.
+pub fn select_by<T, F>(arr: &mut [T], k: usize, compare: F) -> &T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    nth_element_by(arr, k, compare).1
+}
+
+/// The lower median, None for an empty slice
+///
+/// ```rust
+/// use algorithms_rs::sort::median;
+///
+/// assert_eq!(median(&mut [5, 3, 1, 4]), Some(&3));
+/// assert_eq!(median(&mut [5, 3, 1]), Some(&3));
+/// assert_eq!(median::<i32>(&mut []), None);
+/// ```
+pub fn median<T: Ord>(arr: &mut [T]) -> Option<&T> {
+    if arr.is_empty() {
+        return None;
+    }
+    let k = (arr.len() - 1) / 2;
+    Some(select(arr, k))
+}
+
+/// Index of the median of medians of groups of five, a pivot with at least
+/// 3n/10 - 6 elements on either side
+///
+/// The slice is reordered to find it.
+///
+/// # Panics
+///
+/// Panics if the slice is empty.
+pub fn median_of_medians_by<T, F>(arr: &mut [T], mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(!arr.is_empty(), "no median of an empty slice");
+    median_of_medians(arr, &mut compare)
+}
+
+/// Quantiles of `values`, interpolating linearly between order statistics
+///
+/// The p-quantile of n values is at rank h = (n - 1)p, counting from 0, and
+/// a fractional rank lies between the two neighbouring order statistics.
+/// Interpolating next to an infinity would give NaN between -inf and +inf, so
+/// the quantile is -inf if the lower order statistic is, and otherwise +inf
+/// if either of them is.
+/// Every quantile takes O(n) time, and `values` is reordered.
+///
+/// ```rust
+/// use algorithms_rs::sort::quantiles;
+///
+/// let mut values = [7.0, 1.0, 3.0, 5.0, 9.0];
+///
+/// assert_eq!(quantiles(&mut values, &[0.5, 0.0, 1.0, 0.125]), Ok(vec![5.0, 1.0, 9.0, 2.0]));
+/// ```
+pub fn quantiles(values: &mut [f64], probabilities: &[f64]) -> Result<Vec<f64>, QuantileError> {
+    if values.is_empty() {
+        return Err(QuantileError::Empty);
+    }
+    if values.iter().any(|v| v.is_nan()) {
+        return Err(QuantileError::NotANumber);
+    }
+    if let Some(p) = probabilities.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+        return Err(QuantileError::InvalidProbability(*p));
+    }
+
+    // selecting the ranks in ascending order leaves every earlier rank in
+    // place, so each selection only searches the values above the last one
+    let mut order: Vec<usize> = (0..probabilities.len()).collect();
+    order.sort_by(|a, b| probabilities[*a].total_cmp(&probabilities[*b]));
+    let mut result = vec![0.0; probabilities.len()];
+    let mut start = 0;
+    for i in order {
+        let rank = (values.len() - 1) as f64 * probabilities[i];
+        let lo = rank.floor() as usize;
+        let below = *select_by(&mut values[start..], lo - start, f64::total_cmp);
+        start = lo;
+        let fraction = rank - lo as f64;
+        result[i] = if fraction == 0.0 {
+            below
+        } else {
+            // everything after the lo-th value is at least as large
+            let above = values[lo + 1..]
+                .iter()
+                .copied()
+                .min_by(f64::total_cmp)
+                .expect("a fractional rank is below the last one");
+            if below == f64::NEG_INFINITY {
+                below
+            } else if above == f64::INFINITY {
+                above
+            } else {
+                below + fraction * (above - below)
+            }
+        };
+    }
+    Ok(result)
+}
+
+fn inner_select<T, F>(mut arr: &mut [T], mut k: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        if arr.len() <= GROUP {
+            insertion_sort_by(arr, &mut *compare);
+            return;
+        }
+        let pivot = median_of_medians(arr, compare);
+        arr.swap(0, pivot);
+        let (lt, gt) = three_way_partition_by(arr, &mut *compare);
+        if k < lt {
+            arr = &mut arr[..lt];
+        } else if k >= gt {
+            k -= gt;
+            arr = &mut arr[gt..];
+        } else {
+            return;
+        }
+    }
+}
+
+fn median_of_medians<T, F>(arr: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // sort every group of five and gather the medians at the front; the
+    // front positions belong to groups that are already done
+    let groups = arr.len().div_ceil(GROUP);
+    for g in 0..groups {
+        let start = g * GROUP;
+        let end = arr.len().min(start + GROUP);
+        insertion_sort_by(&mut arr[start..end], &mut *compare);
+        arr.swap(g, start + (end - start - 1) / 2);
+    }
+    let mid = (groups - 1) / 2;
+    inner_select(&mut arr[..groups], mid, compare);
+    mid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_select_every_rank() {
+        let mut rng = StdRng::seed_from_u64(46);
+        for len in 1..80 {
+            let values: Vec<i32> = (0..len).map(|_| rng.gen_range(0..25)).collect();
+            let mut sorted = values.clone();
+            sorted.sort();
+            for (k, expected) in sorted.iter().enumerate() {
+                let mut arr = values.clone();
+                let (below, nth, above) = nth_element(&mut arr, k);
+                assert_eq!(nth, expected);
+                assert!(below.iter().all(|v| v <= expected));
+                assert!(above.iter().all(|v| v >= expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparisons_are_linear() {
+        let len = 1 << 14;
+        let mut rng = StdRng::seed_from_u64(460);
+        let inputs: Vec<Vec<i64>> = vec![
+            (0..len).map(|_| rng.gen_range(0..1 << 20)).collect(),
+            (0..len).collect(),
+            (0..len).rev().collect(),
+            vec![3; len as usize],
+        ];
+        for values in inputs {
+            for k in [0, len as usize / 2, len as usize - 1] {
+                let mut comparisons = 0usize;
+                let mut arr = values.clone();
+                select_by(&mut arr, k, |a, b| {
+                    comparisons += 1;
+                    a.cmp(b)
+                });
+                assert!(comparisons < 40 * len as usize, "{comparisons}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_median_of_medians_pivot_is_central() {
+        let mut rng = StdRng::seed_from_u64(4600);
+        let mut values: Vec<u32> = (0..1000).map(|_| rng.gen()).collect();
+        let index = median_of_medians_by(&mut values, u32::cmp);
+        let pivot = values[index];
+        let below = values.iter().filter(|v| **v < pivot).count();
+        let above = values.iter().filter(|v| **v > pivot).count();
+        assert!(below >= 3 * 1000 / 10 - 6 && above >= 3 * 1000 / 10 - 6);
+    }
+
+    #[test]
+    fn test_quantiles_against_sorted() {
+        let mut rng = StdRng::seed_from_u64(46000);
+        let probabilities = [0.9, 0.0, 0.25, 0.5, 0.5, 1.0, 0.33];
+        for len in 1..40 {
+            let mut values: Vec<f64> = (0..len).map(|_| rng.gen_range(-10.0..10.0)).collect();
+            let mut sorted = values.clone();
+            sorted.sort_by(f64::total_cmp);
+            let actual = quantiles(&mut values, &probabilities).unwrap();
+            for (p, q) in probabilities.iter().zip(actual) {
+                let rank = (len - 1) as f64 * p;
+                let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+                let expected = sorted[lo] + (rank - lo as f64) * (sorted[hi] - sorted[lo]);
+                assert!((q - expected).abs() < 1e-9, "p = {p}: {q} != {expected}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_quantile_errors() {
+        assert_eq!(quantiles(&mut [], &[0.5]), Err(QuantileError::Empty));
+        assert_eq!(
+            quantiles(&mut [1.0, f64::NAN], &[0.5]),
+            Err(QuantileError::NotANumber)
+        );
+        assert_eq!(
+            quantiles(&mut [1.0], &[1.5]),
+            Err(QuantileError::InvalidProbability(1.5))
+        );
+        assert_eq!(quantiles(&mut [1.0], &[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_quantiles_next_to_infinities() {
+        let inf = f64::INFINITY;
+        assert_eq!(quantiles(&mut [-inf, 1.0], &[0.5]), Ok(vec![-inf]));
+        assert_eq!(quantiles(&mut [1.0, inf], &[0.5]), Ok(vec![inf]));
+        assert_eq!(quantiles(&mut [inf, inf], &[0.5]), Ok(vec![inf]));
+        assert_eq!(quantiles(&mut [inf, -inf], &[0.5]), Ok(vec![-inf]));
+        assert_eq!(
+            quantiles(&mut [-inf, -inf, 2.0, inf], &[0.25, 0.5, 1.0]),
+            Ok(vec![-inf, -inf, inf])
+        );
+    }
+}
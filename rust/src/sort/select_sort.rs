@@ -1,6 +1,6 @@
-use super::Sort;
+use super::{sort_by_cached_key_with, Sort};
 use core::clone::Clone;
-use core::cmp::PartialOrd;
+use core::cmp::{Ordering, PartialOrd};
 
 /// select sort
 #[derive(Debug)]
@@ -21,43 +21,34 @@ impl<T: Clone> From<&[T]> for SelectSort<T> {
 }
 
 impl<T> SelectSort<T> {
+    /// Selection sort by swapping, which may reorder equal elements
     pub fn select_sort<F>(&mut self, f: F)
     where
-        F: FnOnce(&T, &T) -> bool + Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
-        for i in 0..self.arr.len() {
-            // 将a[i]和a[i+1..len]最小的元素交换
-            let mut min = i; // 最小元素的索引
-            for j in (i + 1)..self.arr.len() {
-                if f(&self.arr[j], &self.arr[min]) {
-                    min = j;
-                }
-            }
-            self.arr.swap(i, min);
-        }
+        selection_sort_unstable_by(&mut self.arr, f)
     }
 
-    pub fn select_sort_by_find_smallest<F>(&mut self, f: F) -> Vec<T>
+    pub fn select_sort_by_find_smallest<F>(&mut self, mut f: F) -> Vec<T>
     where
-        F: FnOnce(Option<&T>, Option<&T>) -> bool + Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
         let mut result = vec![];
-        for _ in 0..self.arr.len() {
-            let smallest = self.find_smallest(f);
-            result.push(self.arr.swap_remove(smallest));
+        while !self.arr.is_empty() {
+            let smallest = self.find_smallest(&mut f);
+            result.push(self.arr.remove(smallest));
         }
         result
     }
 
-    pub fn find_smallest<F>(&self, f: F) -> usize
+    /// Index of the first smallest element, 0 for an empty array
+    pub fn find_smallest<F>(&self, mut f: F) -> usize
     where
-        F: FnOnce(Option<&T>, Option<&T>) -> bool + Copy,
+        F: FnMut(&T, &T) -> Ordering,
     {
-        let mut smallest = self.arr.first();
         let mut smallest_index = 0usize;
         for i in 1..self.arr.len() {
-            if f(self.arr.get(i), smallest) {
-                smallest = self.arr.get(i);
+            if f(&self.arr[i], &self.arr[smallest_index]) == Ordering::Less {
                 smallest_index = i;
             }
         }
@@ -65,6 +56,49 @@ impl<T> SelectSort<T> {
     }
 }
 
+/// Selection sort a slice in place in ascending order
+pub fn selection_sort<T: Ord>(arr: &mut [T]) {
+    select_sort(arr, T::cmp, true)
+}
+
+/// Stable selection sort of a slice in place with a comparator
+pub fn selection_sort_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select_sort(arr, compare, true)
+}
+
+/// Selection sort of a slice in place by swapping, which may reorder equal elements
+pub fn selection_sort_unstable_by<T, F>(arr: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select_sort(arr, compare, false)
+}
+
+// Moves the first smallest remaining element to the front of the unsorted
+// part. Rotating it into place instead of swapping keeps the sort stable.
+fn select_sort<T, F>(arr: &mut [T], mut compare: F, stable: bool)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in 0..arr.len() {
+        // 将a[i]和a[i+1..len]最小的元素交换
+        let mut min = i; // 最小元素的索引
+        for j in (i + 1)..arr.len() {
+            if compare(&arr[j], &arr[min]) == Ordering::Less {
+                min = j;
+            }
+        }
+        if stable {
+            arr[i..=min].rotate_right(1);
+        } else {
+            arr.swap(i, min);
+        }
+    }
+}
+
 impl<T: PartialOrd + Clone> Sort<T> for SelectSort<T> {
     fn inner(&self) -> Vec<T> {
         self.arr.clone()
@@ -72,10 +106,27 @@ impl<T: PartialOrd + Clone> Sort<T> for SelectSort<T> {
 
     fn sort_by<F>(&mut self, f: F)
     where
-        F: FnOnce(&T, &T) -> bool + core::marker::Copy,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        selection_sort_by(&mut self.arr, f)
+    }
+
+    fn sort_unstable_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
     {
         self.select_sort(f)
     }
+
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        sort_by_cached_key_with(&mut self.arr, f, |order, compare| {
+            selection_sort_by(order, compare)
+        });
+    }
 }
 
 #[cfg(test)]
@@ -99,7 +150,19 @@ mod tests {
     #[test]
     fn test_select_sort_by_find_smallest() {
         let mut select = SelectSort::from(vec![10, 9, 8, 6, 5, 4, 3, 2, 1]);
-        let result = select.select_sort_by_find_smallest(|v1, v2| v1 < v2);
+        let result = select.select_sort_by_find_smallest(|v1, v2| v1.cmp(v2));
         assert_eq!(result, vec![1, 2, 3, 4, 5, 6, 8, 9, 10]);
     }
+
+    #[test]
+    fn test_select_sort_stable_and_unstable() {
+        let pairs = vec![(2, 'a'), (2, 'b'), (1, 'c')];
+        let mut stable = SelectSort::from(pairs.clone());
+        stable.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(stable.inner(), vec![(1, 'c'), (2, 'a'), (2, 'b')]);
+        // swapping 'a' to the back moves it past 'b'
+        let mut unstable = SelectSort::from(pairs);
+        unstable.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(unstable.inner(), vec![(1, 'c'), (2, 'b'), (2, 'a')]);
+    }
 }
@@ -0,0 +1,268 @@
+//! Sorting borrowed slices in place
+//!
+//! The free functions sort any `&mut [T]` without taking ownership, and
+//! `SliceSort` exposes them as methods on slices, vectors and arrays.
+use core::cmp::Ordering;
+
+/// Sorting algorithms as methods on slices
+///
+/// ```rust
+/// use algorithms_rs::sort::SliceSort;
+///
+/// let mut numbers = [5, 2, 9, 1, 5, 6];
+/// numbers.merge_sort();
+/// assert_eq!(numbers, [1, 2, 5, 5, 6, 9]);
+///
+/// let mut words = vec!["pear", "fig", "apple"];
+/// words[1..].insertion_sort_by(|a, b| b.len().cmp(&a.len()));
+/// assert_eq!(words, ["pear", "apple", "fig"]);
+/// ```
+pub trait SliceSort<T> {
+    /// Bubble sort in ascending order
+    fn bubble_sort(&mut self)
+    where
+        T: Ord;
+
+    /// Stable bubble sort with a comparator
+    fn bubble_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Insertion sort in ascending order
+    fn insertion_sort(&mut self)
+    where
+        T: Ord;
+
+    /// Stable insertion sort with a comparator
+    fn insertion_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Selection sort in ascending order
+    fn selection_sort(&mut self)
+    where
+        T: Ord;
+
+    /// Stable selection sort with a comparator
+    fn selection_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Selection sort with a comparator, equal elements may be reordered
+    fn selection_sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Introsort in ascending order
+    fn introsort(&mut self)
+    where
+        T: Ord;
+
+    /// Unstable introsort with a comparator
+    fn introsort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Merge sort in ascending order
+    fn merge_sort(&mut self)
+    where
+        T: Ord + Clone;
+
+    /// Stable merge sort with a comparator
+    fn merge_sort_by<F>(&mut self, compare: F)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Bottom-up merge sort in ascending order
+    fn bottom_up_merge_sort(&mut self)
+    where
+        T: Ord + Clone;
+
+    /// Stable bottom-up merge sort with a comparator
+    fn bottom_up_merge_sort_by<F>(&mut self, compare: F)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Adaptive natural merge sort in ascending order
+    fn tim_sort(&mut self)
+    where
+        T: Ord + Clone;
+
+    /// Stable adaptive natural merge sort with a comparator
+    fn tim_sort_by<F>(&mut self, compare: F)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering;
+}
+
+impl<T> SliceSort<T> for [T] {
+    fn bubble_sort(&mut self)
+    where
+        T: Ord,
+    {
+        super::bubble_sort(self)
+    }
+
+    fn bubble_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::bubble_sort_by(self, compare)
+    }
+
+    fn insertion_sort(&mut self)
+    where
+        T: Ord,
+    {
+        super::insertion_sort(self)
+    }
+
+    fn insertion_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::insertion_sort_by(self, compare)
+    }
+
+    fn selection_sort(&mut self)
+    where
+        T: Ord,
+    {
+        super::selection_sort(self)
+    }
+
+    fn selection_sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::selection_sort_by(self, compare)
+    }
+
+    fn selection_sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::selection_sort_unstable_by(self, compare)
+    }
+
+    fn introsort(&mut self)
+    where
+        T: Ord,
+    {
+        super::introsort(self)
+    }
+
+    fn introsort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::introsort_by(self, compare)
+    }
+
+    fn merge_sort(&mut self)
+    where
+        T: Ord + Clone,
+    {
+        super::merge_sort(self)
+    }
+
+    fn merge_sort_by<F>(&mut self, compare: F)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::merge_sort_by(self, compare)
+    }
+
+    fn bottom_up_merge_sort(&mut self)
+    where
+        T: Ord + Clone,
+    {
+        super::bottom_up_merge_sort(self)
+    }
+
+    fn bottom_up_merge_sort_by<F>(&mut self, compare: F)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::bottom_up_merge_sort_by(self, compare)
+    }
+
+    fn tim_sort(&mut self)
+    where
+        T: Ord + Clone,
+    {
+        super::tim_sort(self)
+    }
+
+    fn tim_sort_by<F>(&mut self, compare: F)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        super::tim_sort_by(self, compare)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_sorting_borrowed_subslices() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in 0..40 {
+            let values: Vec<i64> = (0..len).map(|_| rng.gen_range(-10..10)).collect();
+            let mid = len / 2;
+            let mut expected = values.clone();
+            expected[mid..].sort();
+            let sorts: [fn(&mut [i64]); 7] = [
+                <[i64]>::introsort,
+                <[i64]>::bubble_sort,
+                <[i64]>::insertion_sort,
+                <[i64]>::selection_sort,
+                <[i64]>::merge_sort,
+                <[i64]>::bottom_up_merge_sort,
+                <[i64]>::tim_sort,
+            ];
+            for sort in sorts {
+                let mut actual = values.clone();
+                sort(&mut actual[mid..]);
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparators_are_stable() {
+        let mut rng = StdRng::seed_from_u64(420);
+        let values: Vec<(u8, usize)> = (0..100).map(|i| (rng.gen_range(0..4), i)).collect();
+        let mut expected = values.clone();
+        expected.sort_by_key(|v| core::cmp::Reverse(v.0));
+        let by_key = |a: &(u8, usize), b: &(u8, usize)| b.0.cmp(&a.0);
+
+        let mut actual = values.clone();
+        actual.bubble_sort_by(by_key);
+        assert_eq!(actual, expected);
+        let mut actual = values.clone();
+        actual.insertion_sort_by(by_key);
+        assert_eq!(actual, expected);
+        let mut actual = values.clone();
+        actual.selection_sort_by(by_key);
+        assert_eq!(actual, expected);
+        let mut actual = values.clone();
+        actual.merge_sort_by(by_key);
+        assert_eq!(actual, expected);
+        let mut actual = values.clone();
+        actual.bottom_up_merge_sort_by(by_key);
+        assert_eq!(actual, expected);
+        let mut actual = values;
+        actual.selection_sort_unstable_by(by_key);
+        assert!(actual.windows(2).all(|w| w[0].0 >= w[1].0));
+    }
+}
@@ -0,0 +1,527 @@
+//! Adaptive natural merge sort in the style of TimSort
+//!
+//! The input is scanned for runs that are already ascending, or strictly
+//! descending and then reversed, and short runs are extended to a minimum
+//! length by binary insertion sort. The runs go on a stack whose lengths
+//! are kept shrinking faster than the Fibonacci numbers, so it stays
+//! O(lg n) deep and merges stay balanced. Before a merge, the elements
+//! already in place at either end are skipped by galloping, and during a
+//! merge a run that keeps winning is copied in blocks found by galloping.
+//! Sorted input takes n - 1 comparisons and no moves.
+use super::{sort_by_cached_key_with, Sort};
+use core::cmp::Ordering;
+
+// slices shorter than this are binary insertion sorted
+const MIN_MERGE: usize = 64;
+// wins in a row that switch a merge into galloping mode
+const MIN_GALLOP: usize = 7;
+
+/// Tim sort
+///
+/// ```rust
+/// use algorithms_rs::sort::{Sort, TimSort};
+///
+/// let mut log: Vec<u32> = (0..1000).collect();
+/// log.extend([17, 512, 3]);
+/// let mut tim = TimSort::from(log);
+///
+/// tim.sort();
+///
+/// assert!(tim.is_sort());
+/// ```
+#[derive(Debug)]
+pub struct TimSort<T> {
+    arr: Vec<T>,
+}
+
+impl<T> From<Vec<T>> for TimSort<T> {
+    fn from(arr: Vec<T>) -> Self {
+        Self { arr }
+    }
+}
+
+impl<T: Clone> From<&[T]> for TimSort<T> {
+    fn from(arr: &[T]) -> Self {
+        Self { arr: arr.into() }
+    }
+}
+
+impl<T: PartialOrd + Clone> Sort<T> for TimSort<T> {
+    fn inner(&self) -> Vec<T> {
+        self.arr.clone()
+    }
+
+    fn sort_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        tim_sort_by(&mut self.arr, f)
+    }
+
+    fn sort_by_cached_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        sort_by_cached_key_with(&mut self.arr, f, |order, compare| {
+            tim_sort_by(order, compare)
+        });
+    }
+}
+
+/// Tim sort a slice in place in ascending order
+pub fn tim_sort<T: Ord + Clone>(arr: &mut [T]) {
+    tim_sort_by(arr, T::cmp)
+}
+
+/// Stable adaptive merge sort of a slice in place with a comparator
+///
+/// The scratch buffer grows to at most half the length of the slice.
+pub fn tim_sort_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+    if len < MIN_MERGE {
+        let run = count_run(arr, &mut compare);
+        binary_insertion_sort(arr, run, &mut compare);
+        return;
+    }
+
+    let mut state = TimState {
+        buf: Vec::new(),
+        runs: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+    let min_run = min_run_length(len);
+    let mut start = 0;
+    while start < len {
+        let mut run = count_run(&mut arr[start..], &mut compare);
+        if run < min_run {
+            let forced = min_run.min(len - start);
+            binary_insertion_sort(&mut arr[start..start + forced], run, &mut compare);
+            run = forced;
+        }
+        state.runs.push(Run { start, len: run });
+        state.merge_collapse(arr, &mut compare);
+        start += run;
+    }
+    state.merge_force_collapse(arr, &mut compare);
+}
+
+// a sorted run arr[start..start + len]
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+struct TimState<T> {
+    // scratch space; its contents are clones of arbitrary elements
+    buf: Vec<T>,
+    runs: Vec<Run>,
+    min_gallop: usize,
+}
+
+impl<T: Clone> TimState<T> {
+    // Merge until the top runs satisfy runs[n - 1] > runs[n] + runs[n + 1]
+    // and runs[n] > runs[n + 1], checking the first rule one run further
+    // down too, as the original TimSort did not and could break it.
+    fn merge_collapse<F>(&mut self, arr: &mut [T], compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        while self.runs.len() > 1 {
+            let runs = &self.runs;
+            let mut n = runs.len() - 2;
+            if (n >= 1 && runs[n - 1].len <= runs[n].len + runs[n + 1].len)
+                || (n >= 2 && runs[n - 2].len <= runs[n - 1].len + runs[n].len)
+            {
+                if runs[n - 1].len < runs[n + 1].len {
+                    n -= 1;
+                }
+            } else if runs[n].len > runs[n + 1].len {
+                break;
+            }
+            self.merge_at(arr, n, compare);
+        }
+    }
+
+    fn merge_force_collapse<F>(&mut self, arr: &mut [T], compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            if n >= 1 && self.runs[n - 1].len < self.runs[n + 1].len {
+                n -= 1;
+            }
+            self.merge_at(arr, n, compare);
+        }
+    }
+
+    // merge the runs i and i + 1 of the stack
+    fn merge_at<F>(&mut self, arr: &mut [T], i: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (a, b) = (self.runs[i], self.runs[i + 1]);
+        self.runs[i].len += b.len;
+        self.runs.remove(i + 1);
+
+        // elements of the first run up to the head of the second stay put
+        let first = &arr[a.start..b.start];
+        let head = &arr[b.start];
+        let skip = gallop(first, |x| compare(x, head) != Ordering::Greater, false);
+        let start = a.start + skip;
+        let len1 = a.len - skip;
+        if len1 == 0 {
+            return;
+        }
+        // so do elements of the second run from the tail of the first on
+        let second = &arr[b.start..b.start + b.len];
+        let tail = &arr[b.start - 1];
+        let len2 = gallop(second, |x| compare(x, tail) == Ordering::Less, true);
+        if len2 == 0 {
+            return;
+        }
+
+        let shorter = len1.min(len2);
+        if self.buf.len() < shorter {
+            let missing = shorter - self.buf.len();
+            self.buf.extend_from_slice(&arr[..missing]);
+        }
+        let merged = &mut arr[start..start + len1 + len2];
+        if len1 <= len2 {
+            merge_lo(merged, len1, &mut self.buf, &mut self.min_gallop, compare);
+        } else {
+            merge_hi(merged, len1, &mut self.buf, &mut self.min_gallop, compare);
+        }
+    }
+}
+
+// Merge arr[..len1] and arr[len1..] with the first run, the shorter one,
+// swapped out into buf, filling arr from the front. The first element of
+// the second run is known to go first and the last of the first run last.
+fn merge_lo<T, F>(
+    arr: &mut [T],
+    len1: usize,
+    buf: &mut [T],
+    min_gallop: &mut usize,
+    compare: &mut F,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
+    let left = &mut buf[..len1];
+    arr[..len1].swap_with_slice(left);
+    let (mut i, mut j, mut k) = (0, len1, 0);
+
+    'merge: loop {
+        // one element at a time until a run wins often enough in a row
+        let (mut left_wins, mut right_wins) = (0, 0);
+        loop {
+            if compare(&arr[j], &left[i]) == Ordering::Less {
+                arr.swap(k, j);
+                j += 1;
+                right_wins += 1;
+                left_wins = 0;
+            } else {
+                core::mem::swap(&mut arr[k], &mut left[i]);
+                i += 1;
+                left_wins += 1;
+                right_wins = 0;
+            }
+            k += 1;
+            if i == len1 || j == len {
+                break 'merge;
+            }
+            if left_wins.max(right_wins) >= *min_gallop {
+                break;
+            }
+        }
+        // copy blocks found by galloping while they are long
+        loop {
+            let head = &arr[j];
+            let count1 = gallop(&left[i..], |x| compare(x, head) != Ordering::Greater, false);
+            arr[k..k + count1].swap_with_slice(&mut left[i..i + count1]);
+            i += count1;
+            k += count1;
+            if i == len1 {
+                break 'merge;
+            }
+            let head = &left[i];
+            let count2 = gallop(&arr[j..], |x| compare(x, head) == Ordering::Less, false);
+            for t in 0..count2 {
+                arr.swap(k + t, j + t);
+            }
+            j += count2;
+            k += count2;
+            if j == len {
+                break 'merge;
+            }
+            *min_gallop = min_gallop.saturating_sub(1).max(1);
+            if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                *min_gallop += 2;
+                break;
+            }
+        }
+    }
+    // the rest of the first run goes last, the rest of the second is in place
+    arr[k..].swap_with_slice(&mut left[i..]);
+}
+
+// Merge arr[..len1] and arr[len1..] with the second run, the shorter one,
+// swapped out into buf, filling arr from the back.
+fn merge_hi<T, F>(
+    arr: &mut [T],
+    len1: usize,
+    buf: &mut [T],
+    min_gallop: &mut usize,
+    compare: &mut F,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len2 = arr.len() - len1;
+    let right = &mut buf[..len2];
+    arr[len1..].swap_with_slice(right);
+    // i and j are the ends of what is left of the runs, k of the output
+    let (mut i, mut j, mut k) = (len1, len2, arr.len());
+
+    'merge: loop {
+        let (mut left_wins, mut right_wins) = (0, 0);
+        loop {
+            if compare(&right[j - 1], &arr[i - 1]) == Ordering::Less {
+                arr.swap(k - 1, i - 1);
+                i -= 1;
+                left_wins += 1;
+                right_wins = 0;
+            } else {
+                core::mem::swap(&mut arr[k - 1], &mut right[j - 1]);
+                j -= 1;
+                right_wins += 1;
+                left_wins = 0;
+            }
+            k -= 1;
+            if i == 0 || j == 0 {
+                break 'merge;
+            }
+            if left_wins.max(right_wins) >= *min_gallop {
+                break;
+            }
+        }
+        loop {
+            let last = &right[j - 1];
+            let keep = gallop(&arr[..i], |x| compare(x, last) != Ordering::Greater, true);
+            let count1 = i - keep;
+            for t in 1..=count1 {
+                arr.swap(k - t, i - t);
+            }
+            i -= count1;
+            k -= count1;
+            if i == 0 {
+                break 'merge;
+            }
+            let last = &arr[i - 1];
+            let keep = gallop(&right[..j], |x| compare(x, last) == Ordering::Less, true);
+            let count2 = j - keep;
+            arr[k - count2..k].swap_with_slice(&mut right[keep..j]);
+            j -= count2;
+            k -= count2;
+            if j == 0 {
+                break 'merge;
+            }
+            *min_gallop = min_gallop.saturating_sub(1).max(1);
+            if count1 < MIN_GALLOP && count2 < MIN_GALLOP {
+                *min_gallop += 2;
+                break;
+            }
+        }
+    }
+    // the rest of the second run goes first, the rest of the first is in place
+    arr[..j].swap_with_slice(&mut right[..j]);
+}
+
+// Partition point of `pred` in a slice where it holds for a prefix, found
+// by probing 1, 2, 4, ... elements from the front or the back and then
+// binary searching, in O(lg d) comparisons for an answer d from that end
+fn gallop<T, P>(slice: &[T], mut pred: P, from_end: bool) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    let len = slice.len();
+    let (lo, hi) = if from_end {
+        let (mut hi, mut step) = (len, 1);
+        while hi >= step && !pred(&slice[hi - step]) {
+            hi -= step;
+            step *= 2;
+        }
+        (hi.saturating_sub(step), hi)
+    } else {
+        let (mut lo, mut step) = (0, 1);
+        while lo + step <= len && pred(&slice[lo + step - 1]) {
+            lo += step;
+            step *= 2;
+        }
+        (lo, len.min(lo + step))
+    };
+    lo + slice[lo..hi].partition_point(pred)
+}
+
+// Length of the run at the start of arr, reversing it if strictly descending.
+// Descending runs must be strict so that reversing them keeps the sort stable.
+fn count_run<T, F>(arr: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = arr.len();
+    if len < 2 {
+        return len;
+    }
+    let mut end = 2;
+    if compare(&arr[1], &arr[0]) == Ordering::Less {
+        while end < len && compare(&arr[end], &arr[end - 1]) == Ordering::Less {
+            end += 1;
+        }
+        arr[..end].reverse();
+    } else {
+        while end < len && compare(&arr[end], &arr[end - 1]) != Ordering::Less {
+            end += 1;
+        }
+    }
+    end
+}
+
+// insertion sort of arr whose first `sorted` elements are already in
+// order, finding each position by binary search after any equal elements
+fn binary_insertion_sort<T, F>(arr: &mut [T], sorted: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in sorted.max(1)..arr.len() {
+        let (prefix, rest) = arr.split_at(i);
+        let pos = prefix.partition_point(|x| compare(x, &rest[0]) != Ordering::Greater);
+        arr[pos..=i].rotate_right(1);
+    }
+}
+
+// a run length in [32, 64] so that n / min_run is a power of two or just below
+fn min_run_length(mut len: usize) -> usize {
+    let mut low_bits = 0;
+    while len >= MIN_MERGE {
+        low_bits |= len & 1;
+        len >>= 1;
+    }
+    len + low_bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn sort_counting(values: &[(u32, usize)]) -> (Vec<(u32, usize)>, usize) {
+        let mut comparisons = 0;
+        let mut actual = values.to_vec();
+        tim_sort_by(&mut actual, |a, b| {
+            comparisons += 1;
+            a.0.cmp(&b.0)
+        });
+        (actual, comparisons)
+    }
+
+    #[test]
+    fn test_tim_sort_is_stable_on_many_shapes() {
+        let mut rng = StdRng::seed_from_u64(50);
+        for len in [0, 1, 2, 63, 64, 65, 200, 1000, 10_000] {
+            let n = len as u32;
+            let shapes: Vec<Vec<u32>> = vec![
+                (0..len).map(|_| rng.gen_range(0..1000)).collect(),
+                (0..len).map(|_| rng.gen_range(0..4)).collect(),
+                (0..n).collect(),
+                (0..n).rev().collect(),
+                // runs of random lengths, ascending and descending
+                (0..n)
+                    .map(|i| {
+                        if (i / 37) % 2 == 0 {
+                            i % 37
+                        } else {
+                            37 - i % 37
+                        }
+                    })
+                    .collect(),
+                // sorted with a few appended
+                (0..n)
+                    .map(|i| if i + 10 < n { i } else { rng.gen_range(0..n) })
+                    .collect(),
+            ];
+            for keys in shapes {
+                let values: Vec<(u32, usize)> = keys.into_iter().zip(0..).collect();
+                let mut expected = values.clone();
+                expected.sort_by_key(|v| v.0);
+                assert_eq!(sort_counting(&values).0, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_presorted_input_is_linear() {
+        let len = 100_000;
+        let ascending: Vec<(u32, usize)> = (0..len as u32).zip(0..).collect();
+        assert_eq!(sort_counting(&ascending).1, len - 1);
+        let descending: Vec<(u32, usize)> = (0..len as u32).rev().zip(0..).collect();
+        assert_eq!(sort_counting(&descending).1, len - 1);
+
+        // an appended tail merges with a few gallops
+        let mut rng = StdRng::seed_from_u64(500);
+        let mut appended = ascending;
+        appended.extend((0..100).map(|i| (rng.gen_range(0..len as u32), len + i)));
+        let (sorted, comparisons) = sort_counting(&appended);
+        assert!(sorted.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(comparisons < 2 * len, "{comparisons}");
+    }
+
+    #[test]
+    fn test_galloping_merges_interleaved_blocks() {
+        let mut rng = StdRng::seed_from_u64(5000);
+        // two long runs whose values interleave in blocks
+        let mut first: Vec<u32> = Vec::new();
+        let mut second: Vec<u32> = Vec::new();
+        let mut next = 0;
+        for block in 0..200 {
+            let size = rng.gen_range(1..300);
+            let run = if block % 2 == 0 {
+                &mut first
+            } else {
+                &mut second
+            };
+            run.extend(next..next + size);
+            next += size;
+        }
+        let keys = [first, second].concat();
+        let values: Vec<(u32, usize)> = keys.into_iter().zip(0..).collect();
+        let (sorted, comparisons) = sort_counting(&values);
+        assert!(sorted.windows(2).all(|w| w[0].0 < w[1].0));
+        // far fewer than one comparison per element in the merge
+        assert!(
+            comparisons < values.len() + values.len() / 2,
+            "{comparisons}"
+        );
+    }
+
+    #[test]
+    fn test_gallop_from_either_end() {
+        let values: Vec<u32> = (0..100).collect();
+        for key in 0..=100 {
+            for from_end in [false, true] {
+                assert_eq!(gallop(&values, |x| *x < key, from_end), key as usize);
+            }
+        }
+        assert_eq!(gallop::<u32, _>(&[], |_| true, true), 0);
+    }
+}